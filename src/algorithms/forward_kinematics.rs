@@ -1,5 +1,5 @@
 use super::robots::robot_info::RobotInfo;
-use roboprec::{Scalar, Vector, Matrix};
+use roboprec::{Vector, Matrix};
 
 fn act_motion_inv(
     translation: Vector,
@@ -29,41 +29,6 @@ fn act_motion_inv(
     ])
 }
 
-fn alpha_cross_linear(s: &Scalar, vin: Vector) -> Vector {
-    let alpha_cross1 = &(-s) * &vin.at(1);
-    let alpha_cross2 = s * &vin.at(0);
-    Vector!([alpha_cross1, alpha_cross2, Scalar!(0.0);])
-}
-
-fn alpha_cross_angular(s: &Scalar, vin: Vector) -> Vector {
-    let alpha_cross1 = &(-s) * &vin.at(1);
-    let alpha_cross2 = s * &vin.at(0);
-    Vector!([alpha_cross1, alpha_cross2, Scalar!(0.0);])
-}
-
-fn alpha_cross(s: Scalar, vin: Vector) -> Vector {
-    let vin_linear = Vector!([
-        vin.at(0),
-        vin.at(1),
-        vin.at(2);
-    ]);
-    let vin_angular = Vector!([
-        vin.at(3),
-        vin.at(4),
-        vin.at(5);
-    ]);
-    let alpha_cross_linear = alpha_cross_linear(&s, vin_linear);
-    let alpha_cross_angular = alpha_cross_angular(&s, vin_angular);
-    Vector!([
-        alpha_cross_linear.at(0),
-        alpha_cross_linear.at(1),
-        alpha_cross_linear.at(2),
-        alpha_cross_angular.at(0),
-        alpha_cross_angular.at(1),
-        alpha_cross_angular.at(2);
-    ])
-}
-
 fn act_inv(translation: Vector, rotation: Matrix, parent: Vector) -> Vector {
     let linear_parent = Vector!([parent.at(0), parent.at(1), parent.at(2);]);
     let angular_parent = Vector!([parent.at(3), parent.at(4), parent.at(5);]);
@@ -102,85 +67,85 @@ pub fn forward_kinematics_helper(
 
     robot_info: &RobotInfo,
 ) {
-    let rotation_matrix = Matrix!([
-        qcos.at(joint_index), -qsin.at(joint_index), Scalar!(0.0);
-        qsin.at(joint_index), qcos.at(joint_index), Scalar!(0.0);
-        Scalar!(0.0), Scalar!(0.0), Scalar!(1.0)
-    ])
-    .define(format!("limi_rotation_{}", joint_index));
+    let joint_type = robot_info.joint_types[joint_index];
+    let rotation_matrix = joint_type
+        .rotation(&qcos.at(joint_index), &qsin.at(joint_index))
+        .define(format!("limi_rotation_{}", joint_index));
 
     let limi_rotation = (robot_info.calc_limi)(rotation_matrix, joint_index);
 
     limi_rotations.push(limi_rotation.clone());
-    let limi_translation = limi_translations[joint_index].clone();
+    let limi_translation = joint_type.translation(
+        limi_translations[joint_index].clone(),
+        &limi_rotation,
+        &qsin.at(joint_index),
+    );
+
+    // jdata.v() = jdata.S() * v
+    let (joint_v_linear, joint_v_angular) = joint_type.motion(&all_joint_v.at(joint_index));
+    let joint_v = Vector!([
+        joint_v_linear.at(0),
+        joint_v_linear.at(1),
+        joint_v_linear.at(2),
+        joint_v_angular.at(0),
+        joint_v_angular.at(1),
+        joint_v_angular.at(2);
+    ]);
 
-    match joint_index {
-        0 => {
+    match robot_info.parents[joint_index] {
+        None => {
             omi_rotations.push(limi_rotation.clone());
             omi_translations.push(limi_translation.clone());
-            all_v.push(Vector!([
-                Scalar!(0.0),
-                Scalar!(0.0),
-                Scalar!(0.0),
-                Scalar!(0.0),
-                Scalar!(0.0),
-                all_joint_v.at(joint_index);
-            ]));
+            all_v.push(joint_v);
         }
-        _ => {
+        Some(parent) => {
             // the multiplication between oMi and liMi is defined as:
             //{ return SE3Tpl(rot*m2.rotation()
             //    ,translation()+rotation()*m2.translation());}
-            let omi_rotation_i = omi_rotations[joint_index - 1].matmul(&limi_rotation);
+            let omi_rotation_i = omi_rotations[parent].matmul(&limi_rotation);
             let omi_translation_to_add =
-                &omi_rotations[joint_index - 1].matmul_vec(&limi_translation);
-            let omi_translation_i = &omi_translations[joint_index - 1] + omi_translation_to_add;
+                &omi_rotations[parent].matmul_vec(&limi_translation);
+            let omi_translation_i = &omi_translations[parent] + omi_translation_to_add;
             //oMis.push((omi_rotation_i.clone(), omi_translation_i.clone()));
             omi_rotations.push(omi_rotation_i);
             omi_translations.push(omi_translation_i);
-            let temp_v = Vector!([
-                Scalar!(0.0),
-                Scalar!(0.0),
-                Scalar!(0.0),
-                Scalar!(0.0),
-                Scalar!(0.0),
-                all_joint_v.at(joint_index);
-            ]);
             let new_v = act_motion_inv(
                 limi_translation.clone(),
                 limi_rotation.clone(),
-                temp_v,
-                all_v[joint_index - 1].clone(),
+                joint_v,
+                all_v[parent].clone(),
             );
             all_v.push(new_v);
         }
     }
 
     // data.a[i]  = jdata.S() * jmodel.jointVelocitySelector(a) + jdata.c() + (data.v[i] ^ jdata.v()) ;
-    let minus_m_w = -&all_joint_v.at(joint_index);
-    let temp_a1 = alpha_cross(minus_m_w, all_v[joint_index].clone());
-
-    // jdata.S() * jmodel.jointVelocitySelector(a);
-    let temp_a2 = &all_joint_a.at(joint_index) + &temp_a1.at(5);
+    let v_i = all_v[joint_index].clone();
+    let (temp_a_linear, temp_a_angular) = joint_type.acceleration(
+        &all_joint_a.at(joint_index),
+        &all_joint_v.at(joint_index),
+        &Vector!([v_i.at(0), v_i.at(1), v_i.at(2);]),
+        &Vector!([v_i.at(3), v_i.at(4), v_i.at(5);]),
+    );
 
     let temp_a3 = Vector!([
-        temp_a1.at(0),
-        temp_a1.at(1),
-        temp_a1.at(2),
-        temp_a1.at(3),
-        temp_a1.at(4),
-        temp_a2;
+        temp_a_linear.at(0),
+        temp_a_linear.at(1),
+        temp_a_linear.at(2),
+        temp_a_angular.at(0),
+        temp_a_angular.at(1),
+        temp_a_angular.at(2);
     ]);
 
-    match joint_index {
-        0 => {
+    match robot_info.parents[joint_index] {
+        None => {
             all_a.push(temp_a3);
         }
-        _ => {
+        Some(parent) => {
             let add_a = act_inv(
                 limi_translation,
                 limi_rotation,
-                all_a[joint_index - 1].clone(),
+                all_a[parent].clone(),
             );
             let new_a = &temp_a3 + &add_a;
             all_a.push(new_a);
@@ -195,6 +160,10 @@ pub struct FKResult {
     pub all_a: Vec<Vector>,
 }
 
+/// Placement, spatial velocity and acceleration of every joint frame.
+/// `qcos` and `qsin` hold the cosine and sine of each revolute joint's angle;
+/// a prismatic joint reads its displacement from `qsin` instead and ignores
+/// `qcos`, as the inputs of `joint_inputs` are laid out.
 #[allow(dead_code)]
 pub fn forward_kinematics(
     qcos: Vector,
@@ -0,0 +1,92 @@
+use super::robots::robot_info::RobotInfo;
+use roboprec::{Matrix, Scalar, Vector};
+
+/// Frame a Jacobian is expressed in, as pinocchio's `ReferenceFrame`
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReferenceFrame {
+    /// Spatial velocities in the base frame, taken at its origin
+    World,
+    /// Spatial velocities in the frame of the link
+    Local,
+    /// Velocities of the link's origin, along the axes of the base frame
+    LocalWorldAligned,
+}
+
+/// The joints from the base up to `joint`, in order
+fn support(parents: &[Option<usize>], joint: usize) -> Vec<usize> {
+    let mut support = vec![joint];
+    while let Some(parent) = parents[*support.last().unwrap()] {
+        support.push(parent);
+    }
+    support.reverse();
+    support
+}
+
+/// Jacobian of the link moved by `joint`, as a 6 x n matrix whose column `j`
+/// is the spatial velocity of the link, linear part first, for a unit velocity
+/// of joint `j`. Joints that do not carry the link have zero columns.
+/// It follows pinocchio's computeJointJacobian. A prismatic joint reads its
+/// displacement from `qsin`, as in `forward_kinematics`.
+#[allow(dead_code)]
+pub fn jacobian(
+    qcos: Vector,
+    qsin: Vector,
+    robot_info: &RobotInfo,
+    joint: usize,
+    frame: ReferenceFrame,
+) -> Matrix {
+    let n_joints = robot_info.n_joints;
+    let support = support(&robot_info.parents, joint);
+
+    // data.oMi[i] = data.oMi[parent] * data.liMi[i], along the support only
+    let mut omis: Vec<Option<(Matrix, Vector)>> = vec![None; n_joints];
+    for &i in &support {
+        let joint_type = robot_info.joint_types[i];
+        let rotation_matrix = joint_type
+            .rotation(&qcos.at(i), &qsin.at(i))
+            .define(format!("limi_rotation_{}", i));
+        let limi_rotation = (robot_info.calc_limi)(rotation_matrix, i);
+        let limi_translation =
+            joint_type.translation(robot_info.limi_translations[i].clone(), &limi_rotation, &qsin.at(i));
+        omis[i] = Some(match robot_info.parents[i] {
+            None => (limi_rotation, limi_translation),
+            Some(parent) => {
+                let (parent_rotation, parent_translation) = omis[parent].as_ref().unwrap();
+                let omi_translation = parent_translation + &parent_rotation.matmul_vec(&limi_translation);
+                (parent_rotation.matmul(&limi_rotation), omi_translation)
+            }
+        });
+    }
+    let (link_rotation, link_translation) = omis[joint].clone().unwrap();
+    let link_rotation_t = link_rotation.transpose();
+
+    let zero = Scalar!(0.0);
+    let columns: Vec<Vec<Scalar>> = (0..n_joints)
+        .map(|j| {
+            let Some((rotation, translation)) = &omis[j] else {
+                return vec![zero.clone(); 6];
+            };
+            // J_cols = data.oMi[j].act(jdata.S());
+            let (linear, angular) = robot_info.joint_types[j].act(rotation, translation);
+            let (linear, angular) = match frame {
+                ReferenceFrame::World => (linear, angular),
+                // data.oMi[joint].actInv(J_cols)
+                ReferenceFrame::Local => {
+                    let linear = &linear - &link_translation.cross(&angular);
+                    (link_rotation_t.matmul_vec(&linear), link_rotation_t.matmul_vec(&angular))
+                }
+                // the velocity of the link's origin rather than the base's
+                ReferenceFrame::LocalWorldAligned => (&linear - &link_translation.cross(&angular), angular),
+            };
+            (0..6)
+                .map(|k| if k < 3 { linear.at(k) } else { angular.at(k - 3) })
+                .collect()
+        })
+        .collect();
+
+    let rows: Vec<Vec<&Scalar>> = (0..6)
+        .map(|k| columns.iter().map(|column| &column[k]).collect())
+        .collect();
+    Matrix::from_scalars(&format!("jacobian_{}", joint), rows)
+}
@@ -1,4 +1,5 @@
 pub mod forward_kinematics;
+pub mod jacobian;
 pub mod rnea;
 pub mod rnea_derivatives;
 
@@ -1,4 +1,4 @@
-use super::robots::robot_info::RobotInfo;
+use super::robots::robot_info::{BaseInfo, JointType, RobotInfo};
 use roboprec::{Matrix, Scalar, Vector};
 
 fn act_inv(
@@ -25,18 +25,6 @@ fn act_inv(
     (linear + &act_inv4, angular + &act_inv5)
 }
 
-fn alpha_cross_linear(s: &Scalar, vin: &Vector) -> Vector {
-    let alpha_cross1 = &(-s) * &vin.at(1);
-    let alpha_cross2 = s * &vin.at(0);
-    Vector!([alpha_cross1, alpha_cross2, Scalar!(0.0);])
-}
-
-fn alpha_cross_angular(s: &Scalar, vin: &Vector) -> Vector {
-    let alpha_cross1 = &(-s) * &vin.at(1);
-    let alpha_cross2 = s * &vin.at(0);
-    Vector!([alpha_cross1, alpha_cross2, Scalar!(0.0);])
-}
-
 fn rhs_mult(inertia: &Matrix, vin: &Vector) -> Vector {
     let vout_0 = &inertia.at(0, 0) * &vin.at(0)
         + &inertia.at(0, 1) * &vin.at(1)
@@ -73,15 +61,101 @@ fn act(rotation: Matrix, translation: Vector, f: Vector) -> Vector {
     ])
 }
 
+/// The momentum `h` and the force `f` of a body moving with spatial velocity
+/// `v` and acceleration `a_gf`; `label` names the traced values
+fn body_force(
+    mass: &Scalar,
+    lever: &Vector,
+    inertia: &Matrix,
+    v_linear: &Vector,
+    v_angular: &Vector,
+    a_gf_linear: &Vector,
+    a_gf_angular: &Vector,
+    label: &str,
+) -> (Vector, Vector) {
+    // this line updates spatial momenta
+    // model.inertias[i].__mult__(data.v[i],data.h[i]);
+    //let data_h = Vector!(0.0, 0.0, 0.0, 0.0, 0.0, 0.0).define(format!("data_h_{}", joint_id));
+    // data.v[i] is new_v at this point
+    // firstly mass * (v.linear - lever.cross(v.angular))
+    let h_linear_1 = lever
+        .cross(v_angular)
+        .define(format!("h_linear_1_{}", label));
+    let h_linear_2 = v_linear - h_linear_1;
+    let h_linear = mass * &h_linear_2;
+
+    // next line is Symmetric3::rhsMult(inertia(),v.angular(),f.angular());
+    let h_angular = rhs_mult(inertia, v_angular);
+
+    // next line is f.angular() += lever().cross(f.linear());
+    let h_angular_1 = lever.cross(&h_linear);
+    let h_angular_2 = &h_angular + &h_angular_1;
+
+    // next line is model.inertias[i].__mult__(data.a_gf[i],data.f[i]);
+    // firstly mass * (a_gf.linear - lever.cross(a_gf.angular))
+    let f_linear_1 = lever.cross(a_gf_angular);
+    let f_linear_2 = a_gf_linear - &f_linear_1;
+    let f_linear_3 = mass * &f_linear_2;
+
+    // next line is Symmetric3::rhsMult(inertia(),a_gf.angular(),f.angular());
+    let f_angular = rhs_mult(inertia, a_gf_angular);
+
+    // next line is f.angular() += lever().cross(f.linear());
+    let f_angular_1 = lever.cross(&f_linear_3);
+    let f_angular_2 = &f_angular + &f_angular_1;
+
+    // the cross here is not the regular cross product since the vectors are 6D
+    // it is implemented in pinocchio/include/pinocchio/spatial/motion-dense.hpp cross_impl,
+    // and it calls a motionAction, which is implemented in pinocchio/include/pinocchio/spatial/force-dense.hpp motionAction
+    // final line is data.f[i] += data.v[i].cross(data.h[i]);
+    /*
+    void motionAction(const MotionDense<M1> & v, ForceDense<M2> & fout) const
+    {
+      std::cout << "ForceDense::motionAction" << std::endl;
+      fout.linear().noalias() = v.angular().cross(linear());
+      fout.angular().noalias() = v.angular().cross(angular())+v.linear().cross(linear());
+    }
+    */
+    let f_linear_4_temp = v_angular.cross(&h_linear);
+    let f_linear_4 = &f_linear_3 + f_linear_4_temp;
+
+    let f_angular_3_temp = v_angular.cross(&h_angular_2);
+    let f_angular_3 = &f_angular_2 + f_angular_3_temp;
+    let f_angular_4_temp = v_linear.cross(&h_linear);
+    let f_angular_4 = &f_angular_3 + f_angular_4_temp;
+
+    let h = Vector!([
+        h_linear.at(0),
+        h_linear.at(1),
+        h_linear.at(2),
+        h_angular_2.at(0),
+        h_angular_2.at(1),
+        h_angular_2.at(2);
+    ])
+    .define(format!("h_{}", label));
+
+    let f = Vector!([
+        f_linear_4.at(0),
+        f_linear_4.at(1),
+        f_linear_4.at(2),
+        f_angular_4.at(0),
+        f_angular_4.at(1),
+        f_angular_4.at(2);
+    ])
+    .define(format!("f_{}", label));
+
+    (h, f)
+}
+
 fn first_pass(
     qsin: Scalar,
     qcos: Scalar,
-    data_v: &Vector,
     v: &Vector,
     a: &Vector,
-    parent_v: &Vector,
+    parent_v: Option<&Vector>,
     parent_a_gf: &Vector,
-    limi_translations: &[Vector],
+    f_ext: Option<&Vector>,
+    limi_translations: &mut [Vector],
     mut limi_rotations: Vec<Matrix>,
     joint_index: usize,
     levers: &[Vector],
@@ -89,30 +163,18 @@ fn first_pass(
     inertias: &[Matrix],
     robot_info: &RobotInfo,
 ) -> (Vec<Matrix>, Vector, Vector, Vector, Vector) {
-    let rotation_matrix = Matrix!([
-        qcos.clone(), -&qsin, Scalar!(0.0);
-        qsin, qcos, Scalar!(0.0);
-        Scalar!(0.0), Scalar!(0.0), Scalar!(1.0);
-    ]);
+    let joint_type = robot_info.joint_types[joint_index];
+    let rotation_matrix = joint_type.rotation(&qcos, &qsin);
 
     let limi_rotation = (robot_info.calc_limi)(rotation_matrix, joint_index);
     limi_rotations.push(limi_rotation.clone());
+    // a prismatic joint moves the placement, keep it for the second pass
+    limi_translations[joint_index] =
+        joint_type.translation(limi_translations[joint_index].clone(), &limi_rotation, &qsin);
     let limi_translation = limi_translations[joint_index].clone();
 
-    let mut new_v_linear = Vector!([
-        data_v.at(0), data_v.at(1), data_v.at(2);
-    ]);
-
-    let mut new_v_angular = Vector!([
-        data_v.at(3), data_v.at(4), v.at(joint_index);
-    ]);
-
-    let parent_v_linear = Vector!([
-        parent_v.at(0), parent_v.at(1), parent_v.at(2);
-    ]);
-    let parent_v_angular = Vector!([
-        parent_v.at(3), parent_v.at(4), parent_v.at(5);
-    ]);
+    //data.v[i] = jdata.v(), which is jdata.S() * v
+    let (mut new_v_linear, mut new_v_angular) = joint_type.motion(&v.at(joint_index));
 
     let parent_a_gf_linear = Vector!([
         parent_a_gf.at(0), parent_a_gf.at(1), parent_a_gf.at(2);
@@ -121,8 +183,14 @@ fn first_pass(
         parent_a_gf.at(3), parent_a_gf.at(4), parent_a_gf.at(5);
     ]);
 
-    // if parent > 0
-    if joint_index > 0 {
+    // if parent > 0, or the base moves
+    if let Some(parent_v) = parent_v {
+        let parent_v_linear = Vector!([
+            parent_v.at(0), parent_v.at(1), parent_v.at(2);
+        ]);
+        let parent_v_angular = Vector!([
+            parent_v.at(3), parent_v.at(4), parent_v.at(5);
+        ]);
         //data.v[i] += data.liMi[i].actInv(data.v[parent]);
         (new_v_linear, new_v_angular) = act_inv(
             limi_translation.clone(),
@@ -140,35 +208,12 @@ fn first_pass(
     //data.a_gf[i] = jdata.c() + (data.v[i] ^ jdata.v());
     // ^ operator is actually implemented in pinocchio/include/pinocchio/spatial/cartesian-axis.hpp inline void CartesianAxis<2>::alphaCross
     // vout_[0] = -s*vin[1]; vout_[1] = s*vin[0]; vout_[2] = 0.;
-    let minus_m_w = -&(v.at(joint_index));
-    let alpha_cross_linear = alpha_cross_linear(&minus_m_w, &new_v_linear);
-    let alpha_cross_angular = alpha_cross_angular(&minus_m_w, &new_v_angular);
-
-    let new_a_gf = Vector!([
-        alpha_cross_linear.at(0),
-        alpha_cross_linear.at(1),
-        alpha_cross_linear.at(2),
-        alpha_cross_angular.at(0),
-        alpha_cross_angular.at(1),
-        alpha_cross_angular.at(2);
-    ])
-    .define(format!("new_a_gf_{}", joint_index));
-
     // data.a_gf[i] += jdata.S() * jmodel.jointVelocitySelector(a);
     // jointVelocitySelector(a) is only a[joint_id]
-    // I couldn't print out info about jdata.S() easily but it is ConstraintRevoluteTpl, and I believe the only thing this line does is
-    // data.a_gf[i][5] = jmodel.jointVelocitySelector(a)
-
-    let new_a_gf_up1 = &a.at(joint_index) + &new_a_gf.at(5);
-
-    let new_a_gf2_linear = Vector!([
-        new_a_gf.at(0), new_a_gf.at(1), new_a_gf.at(2);
-    ])
-    .define(format!("new_a_gf2_linear_{}", joint_index));
-    let new_a_gf2_angular = Vector!([
-        new_a_gf.at(3), new_a_gf.at(4), new_a_gf_up1;
-    ])
-    .define(format!("new_a_gf2_angular_{}", joint_index));
+    let (mut new_a_gf2_linear, mut new_a_gf2_angular) =
+        joint_type.acceleration(&a.at(joint_index), &v.at(joint_index), &new_v_linear, &new_v_angular);
+    new_a_gf2_linear = new_a_gf2_linear.define(format!("new_a_gf2_linear_{}", joint_index));
+    new_a_gf2_angular = new_a_gf2_angular.define(format!("new_a_gf2_angular_{}", joint_index));
 
     let (new_a_gf_up2_linear, new_a_gf_up2_angular) = act_inv(
         limi_translation.clone(),
@@ -190,76 +235,21 @@ fn first_pass(
     ])
     .define(format!("new_a_gf_up_final_{}", joint_index));
 
-    // this line updates spatial momenta
-    // model.inertias[i].__mult__(data.v[i],data.h[i]);
-    //let data_h = Vector!(0.0, 0.0, 0.0, 0.0, 0.0, 0.0).define(format!("data_h_{}", joint_id));
-    // data.v[i] is new_v at this point
-    // firstly mass * (v.linear - lever.cross(v.angular))
-    let h_linear_1 = &levers[joint_index]
-        .cross(&new_v_angular)
-        .define(format!("h_linear_1_{}", joint_index));
-    let h_linear_2 = &new_v_linear - h_linear_1;
-    let h_linear = &masses.at(joint_index) * &h_linear_2;
-
-    // next line is Symmetric3::rhsMult(inertia(),v.angular(),f.angular());
-    let h_angular = rhs_mult(&inertias[joint_index].clone(), &new_v_angular);
-
-    // next line is f.angular() += lever().cross(f.linear());
-    let h_angular_1 = levers[joint_index].cross(&h_linear);
-    let h_angular_2 = &h_angular + &h_angular_1;
-
-    // next line is model.inertias[i].__mult__(data.a_gf[i],data.f[i]);
-    // firstly mass * (a_gf.linear - lever.cross(a_gf.angular))
-    let f_linear_1 = levers[joint_index].cross(&new_a_gf_up2_angular);
-    let f_linear_2 = &new_a_gf_up2_linear - &f_linear_1;
-    let f_linear_3 = &masses.at(joint_index) * &f_linear_2;
-
-    // next line is Symmetric3::rhsMult(inertia(),a_gf.angular(),f.angular());
-    let f_angular = rhs_mult(&inertias[joint_index].clone(), &new_a_gf_up2_angular);
-
-    // next line is f.angular() += lever().cross(f.linear());
-    let f_angular_1 = levers[joint_index].cross(&f_linear_3);
-    let f_angular_2 = &f_angular + &f_angular_1;
-
-    // the cross here is not the regular cross product since the vectors are 6D
-    // it is implemented in pinocchio/include/pinocchio/spatial/motion-dense.hpp cross_impl,
-    // and it calls a motionAction, which is implemented in pinocchio/include/pinocchio/spatial/force-dense.hpp motionAction
-    // final line is data.f[i] += data.v[i].cross(data.h[i]);
-    /*
-    void motionAction(const MotionDense<M1> & v, ForceDense<M2> & fout) const
-    {
-      std::cout << "ForceDense::motionAction" << std::endl;
-      fout.linear().noalias() = v.angular().cross(linear());
-      fout.angular().noalias() = v.angular().cross(angular())+v.linear().cross(linear());
-    }
-    */
-    let f_linear_4_temp = &new_v_angular.cross(&h_linear);
-    let f_linear_4 = &f_linear_3 + f_linear_4_temp;
-
-    let f_angular_3_temp = &new_v_angular.cross(&h_angular_2);
-    let f_angular_3 = &f_angular_2 + f_angular_3_temp;
-    let f_angular_4_temp = &new_v_linear.cross(&h_linear);
-    let f_angular_4 = &f_angular_3 + f_angular_4_temp;
-
-    let h = Vector!([
-        h_linear.at(0),
-        h_linear.at(1),
-        h_linear.at(2),
-        h_angular_2.at(0),
-        h_angular_2.at(1),
-        h_angular_2.at(2);
-    ])
-    .define(format!("h_{}", joint_index));
-
-    let f = Vector!([
-        f_linear_4.at(0),
-        f_linear_4.at(1),
-        f_linear_4.at(2),
-        f_angular_4.at(0),
-        f_angular_4.at(1),
-        f_angular_4.at(2);
-    ])
-    .define(format!("f_{}", joint_index));
+    let (h, f) = body_force(
+        &masses.at(joint_index),
+        &levers[joint_index],
+        &inertias[joint_index],
+        &new_v_linear,
+        &new_v_angular,
+        &new_a_gf_up2_linear,
+        &new_a_gf_up2_angular,
+        &joint_index.to_string(),
+    );
+    // data.f[i] -= fext[i];
+    let f = match f_ext {
+        Some(f_ext) => (&f - f_ext).define(format!("f_with_ext_{}", joint_index)),
+        None => f,
+    };
 
     let new_v = Vector!([
         new_v_linear.at(0),
@@ -278,6 +268,8 @@ fn sec_pass(
     mut all_f: Vec<Vector>,
     limi_rotations: Vec<Matrix>,
     limi_translations:&[Vector],
+    joint_types: &[JointType],
+    parents: &[Option<usize>],
     n_joints: usize,
 ) -> (Vec<Vector>, Vector) {
     // jmodel.jointVelocitySelector(data.tau) = jdata.S().transpose()*data.f[i];
@@ -300,22 +292,23 @@ fn sec_pass(
     // 0.381501
     // 0.471101
     // in each iteration, we set data.tau[joint_id] = data.f[i].angular[2];
-    // the behaviour of S() ConstraintTpl was similar in forward pass
+    // the behaviour of S() ConstraintTpl was similar in forward pass,
+    // other joint types project data.f[i] on their own axis
 
     let mut data_taus: Vec<Scalar> = vec![];
 
     for i in (0..n_joints).rev() {
-        data_taus.push(all_f[i].at(5));
+        data_taus.push(joint_types[i].force(&all_f[i]));
 
         //if(parent>0) data.f[parent] += data.liMi[i].act(data.f[i]);
-        if i > 0 {
+        if let Some(parent) = parents[i] {
             let new_data_f_parent_add = act(
                 limi_rotations[i].clone(),
                 limi_translations[i].clone(),
                 all_f[i].clone()
             );
-            let new_data_f_parent = all_f[i - 1].clone() + new_data_f_parent_add;
-            all_f[i - 1] = new_data_f_parent;
+            let new_data_f_parent = all_f[parent].clone() + new_data_f_parent_add;
+            all_f[parent] = new_data_f_parent;
         }
     }
 
@@ -325,70 +318,59 @@ fn sec_pass(
     (all_f, data_tau)
 }
 
-#[allow(dead_code)]
-pub fn rnea(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot_info: &RobotInfo) -> Vector {
+/// Both passes of rnea on a base moving with velocity `base_v`, `None` for a
+/// base at rest, and acceleration `base_a_gf`, with the external forces `f_ext`
+/// on the links. Returns the forces the joints transmit to their parents, the
+/// placements they are transmitted through and the joint torques.
+fn passes(
+    qcos: Vector,
+    qsin: Vector,
+    v: Vector,
+    a: Vector,
+    base_v: Option<&Vector>,
+    base_a_gf: &Vector,
+    f_ext: Option<&[Vector]>,
+    robot_info: &RobotInfo,
+) -> (Vec<Vector>, Vec<Matrix>, Vec<Vector>, Vector) {
     let n_joints = robot_info.n_joints;
-    let limi_translations = robot_info.limi_translations.clone();
+    let mut limi_translations = robot_info.limi_translations.clone();
     let levers = robot_info.levers.clone();
     let masses = robot_info.masses.clone();
     let inertias = robot_info.inertias.clone();
 
     let mut limi_rotations: Vec<Matrix> = vec![];
 
-    // we also have data.v, which v[0] is set to zero explicitly, and I assume the rest should also be zero
-    // I will keep it constant here, too, but keep in mind
-    let mut data_v: Vec<Vector> = vec![];
-
     let mut all_v: Vec<Vector> = vec![];
     let mut all_a_gf: Vec<Vector> = vec![];
     let mut all_h: Vec<Vector> = vec![];
     let mut all_f: Vec<Vector> = vec![];
 
-    let parent_v = Vector!([0.0, 0.0, 0.0, 0.0, 0.0, 0.0]).define("parent_v".to_string());
-    let parent_a_gf = Vector!([0.0, 0.0, 9.81, 0.0, 0.0, 0.0]).define("parent_a_gf".to_string());
-
     let mut new_v: Vector;
     let mut new_a_gf: Vector;
     let mut new_h: Vector;
     let mut new_f: Vector;
 
     for i in 0..n_joints {
-        data_v.push(Vector!([0.0, 0.0, 0.0, 0.0, 0.0, 0.0]).define(format!("data_v_{}", i)));
-        if i == 0 {
-            (limi_rotations, new_v, new_a_gf, new_h, new_f) = first_pass(
-                qsin.at(i).clone(),
-                qcos.at(i).clone(),
-                &data_v[i],
-                &v,
-                &a,
-                &parent_v,
-                &parent_a_gf,
-                &limi_translations,
-                limi_rotations,
-                i,
-                &levers,
-                &masses,
-                &inertias,
-                robot_info,
-            );
-        } else {
-            (limi_rotations, new_v, new_a_gf, new_h, new_f) = first_pass(
-                qsin.at(i).clone(),
-                qcos.at(i).clone(),
-                &data_v[i],
-                &v,
-                &a,
-                &all_v[i - 1],
-                &all_a_gf[i - 1],
-                &limi_translations,
-                limi_rotations,
-                i,
-                &levers,
-                &masses,
-                &inertias,
-                robot_info,
-            );
-        }
+        let (parent_v, parent_a_gf) = match robot_info.parents[i] {
+            Some(parent) => (Some(&all_v[parent]), &all_a_gf[parent]),
+            None => (base_v, base_a_gf),
+        };
+        (limi_rotations, new_v, new_a_gf, new_h, new_f) = first_pass(
+            qsin.at(i).clone(),
+            qcos.at(i).clone(),
+            &v,
+            &a,
+            parent_v,
+            parent_a_gf,
+            f_ext.map(|f_ext| &f_ext[i]),
+            &mut limi_translations,
+            limi_rotations,
+            i,
+            &levers,
+            &masses,
+            &inertias,
+            robot_info,
+        );
 
         all_v.push(new_v.clone());
         all_a_gf.push(new_a_gf.clone());
@@ -397,7 +379,97 @@ pub fn rnea(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot_info: &Robot
     }
 
     // sec_pass will do its own iteration
-    let (_new_f, taus) = sec_pass(all_f, limi_rotations, &limi_translations, n_joints);
+    let (all_f, taus) = sec_pass(
+        all_f,
+        limi_rotations.clone(),
+        &limi_translations,
+        &robot_info.joint_types,
+        &robot_info.parents,
+        n_joints,
+    );
+
+    (all_f, limi_rotations, limi_translations, taus)
+}
+
+/// Joint torques of a fixed-base robot under gravity along -z. Like
+/// `forward_kinematics`, it takes the displacement of a prismatic joint in
+/// its `qsin` entry, and leaves its `qcos` entry unused.
+#[allow(dead_code)]
+pub fn rnea(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot_info: &RobotInfo) -> Vector {
+    // the base is at rest, and accelerates upwards against gravity
+    let parent_a_gf = Vector!([0.0, 0.0, 9.81, 0.0, 0.0, 0.0]).define("parent_a_gf".to_string());
+    let (_all_f, _limi_rotations, _limi_translations, taus) =
+        passes(qcos, qsin, v, a, None, &parent_a_gf, None, robot_info);
+
+    taus
+}
+
+/// rnea under the gravity `gravity`, the linear acceleration of free fall in
+/// the base frame: `[0, 0, -9.81]` for an upright arm, `[0, 0, 9.81]` for one
+/// mounted upside-down. `f_ext` holds one external spatial force per joint,
+/// linear part first, that the environment applies to the joint's link,
+/// expressed in the joint frame like pinocchio's `fext`. Both may be constants
+/// or ranged inputs.
+#[allow(dead_code)]
+pub fn rnea_with_forces(
+    qcos: Vector,
+    qsin: Vector,
+    v: Vector,
+    a: Vector,
+    gravity: &Vector,
+    f_ext: Option<&[Vector]>,
+    robot_info: &RobotInfo,
+) -> Vector {
+    // the base is at rest, and accelerates against gravity
+    let zero = Scalar!(0.0);
+    let mut parent_a_gf =
+        Vector!([-&gravity.at(0), -&gravity.at(1), -&gravity.at(2), zero, zero, zero;]);
+    parent_a_gf = parent_a_gf.define("parent_a_gf".to_string());
+    let (_all_f, _limi_rotations, _limi_translations, taus) =
+        passes(qcos, qsin, v, a, None, &parent_a_gf, f_ext, robot_info);
 
     taus
 }
+
+/// rnea for a robot on a floating base, such as a legged robot. `base_v` is
+/// the spatial velocity of the base and `base_a_gf` its spatial acceleration
+/// less gravity, both in the base frame, so a base resting level has
+/// `base_a_gf = [0, 0, 9.81, 0, 0, 0]` like an accelerometer reads. Returns
+/// the wrench on the base in the base frame, the first six entries of
+/// pinocchio's tau for a free-flyer, and the joint torques.
+#[allow(dead_code)]
+pub fn rnea_floating_base(
+    qcos: Vector,
+    qsin: Vector,
+    v: Vector,
+    a: Vector,
+    base_v: Vector,
+    base_a_gf: Vector,
+    robot_info: &RobotInfo,
+    base_info: &BaseInfo,
+) -> (Vector, Vector) {
+    let (all_f, limi_rotations, limi_translations, taus) =
+        passes(qcos, qsin, v, a, Some(&base_v), &base_a_gf, None, robot_info);
+
+    let base_v_linear = Vector!([base_v.at(0), base_v.at(1), base_v.at(2);]);
+    let base_v_angular = Vector!([base_v.at(3), base_v.at(4), base_v.at(5);]);
+    let base_a_gf_linear = Vector!([base_a_gf.at(0), base_a_gf.at(1), base_a_gf.at(2);]);
+    let base_a_gf_angular = Vector!([base_a_gf.at(3), base_a_gf.at(4), base_a_gf.at(5);]);
+    let (_base_h, mut base_f) = body_force(
+        &base_info.mass,
+        &base_info.lever,
+        &base_info.inertia,
+        &base_v_linear,
+        &base_v_angular,
+        &base_a_gf_linear,
+        &base_a_gf_angular,
+        "base",
+    );
+
+    // the joints on the base pass on the forces of their subtrees
+    for i in (0..robot_info.n_joints).filter(|&i| robot_info.parents[i].is_none()) {
+        base_f = base_f + act(limi_rotations[i].clone(), limi_translations[i].clone(), all_f[i].clone());
+    }
+
+    (base_f.define("base_wrench".to_string()), taus)
+}
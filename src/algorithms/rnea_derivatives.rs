@@ -1,18 +1,6 @@
 use super::robots::robot_info::RobotInfo;
 use roboprec::{Matrix, Scalar, Vector};
 
-fn alpha_cross_linear(s: &Scalar, vin: &Vector) -> Vector {
-    let alpha_cross1 = &(-s) * &vin.at(1);
-    let alpha_cross2 = s * &vin.at(0);
-    Vector!([alpha_cross1, alpha_cross2, Scalar!(0.0);])
-}
-
-fn alpha_cross_angular(s: &Scalar, vin: &Vector) -> Vector {
-    let alpha_cross1 = &(-s) * &vin.at(1);
-    let alpha_cross2 = s * &vin.at(0);
-    Vector!([alpha_cross1, alpha_cross2, Scalar!(0.0);])
-}
-
 fn rhs_mult(inertia: &Matrix, vin: &Vector) -> Vector {
     let vout_0 = &inertia.at(0, 0) * &vin.at(0)
         + &inertia.at(0, 1) * &vin.at(1)
@@ -292,30 +280,6 @@ fn add_force_cross_matrix(f_linear: Vector, f_angular: Vector, mout: Matrix) ->
     ])
 }
 
-// it uses a variable "axis", and it is always 2 for some reason.
-//template<typename S1, int O1>
-//typename SE3GroupAction<ConstraintRevoluteTpl>::ReturnType
-//se3Action(const SE3Tpl<S1,O1> & m) const
-//{
-//  typedef typename SE3GroupAction<ConstraintRevoluteTpl>::ReturnType ReturnType;
-//  ReturnType res;
-//  res.template segment<3>(LINEAR) = m.translation().cross(m.rotation().col(axis));
-//  res.template segment<3>(ANGULAR) = m.rotation().col(axis);
-//  std::cout << "res: " << res << std::endl;
-//  return res;
-//}
-fn act_constraint(rotation: &Matrix, translation: &Vector, joint_axis: usize) -> (Vector, Vector) {
-    let rotation_col = Vector!([
-        rotation.at(0, joint_axis),
-        rotation.at(1, joint_axis),
-        rotation.at(2, joint_axis);
-    ]);
-
-    let linear = translation.cross(&rotation_col);
-
-    (linear, rotation_col)
-}
-
 // not to be confused with motion_action.
 //      v.angular().noalias() = m.rotation()*angular();
 //      v.linear().noalias() = m.rotation()*linear() + m.translation().cross(v.angular());
@@ -369,6 +333,29 @@ fn act_motion(rotation: &Matrix, translation: &Vector, t: &Vector) -> Vector {
     ])
 }
 
+// the dual of act_motion, in pinocchio/include/pinocchio/spatial/force-dense.hpp:
+//      f.linear().noalias() = m.rotation()*linear();
+//      f.angular().noalias() = m.rotation()*angular() + m.translation().cross(f.linear());
+fn act_force(rotation: &Matrix, translation: &Vector, f: &Vector) -> Vector {
+    let linear = Vector!([f.at(0), f.at(1), f.at(2);]);
+    let angular = Vector!([f.at(3), f.at(4), f.at(5);]);
+
+    let res_linear = rotation.matmul_vec(&linear);
+    let rotation_cross_angular = rotation.matmul_vec(&angular);
+
+    let cross = translation.cross(&res_linear);
+    let res_angular = &rotation_cross_angular + &cross;
+
+    Vector!([
+        res_linear.at(0),
+        res_linear.at(1),
+        res_linear.at(2),
+        res_angular.at(0),
+        res_angular.at(1),
+        res_angular.at(2);
+    ])
+}
+
 // I strongly disagree with this function's name, but it is actInv in pinocchio,
 // so I will leave it as is for now
 fn act_motion_inv(
@@ -571,7 +558,6 @@ fn inertia_vec_mult(
 fn first_pass(
     qsin: Scalar,
     qcos: Scalar,
-    data_v: &Vector,
     v: &Vector,
     a: &Vector,
     all_v: &mut Vec<Vector>,
@@ -595,29 +581,25 @@ fn first_pass(
     dadq_cols: &mut Vec<Vector>,
     dvdq_cols: &mut Vec<Vector>,
     dadv_cols: &mut Vec<Vector>,
+    gravity: &Vector,
+    f_ext: Option<&Vector>,
     robot_info: &RobotInfo,
 ) {
-    let rotation_matrix = Matrix!([
-        qcos.clone(), -&qsin, Scalar!(0.0);
-        qsin, qcos, Scalar!(0.0);
-        Scalar!(0.0), Scalar!(0.0), Scalar!(1.0)
-    ]);
+    let joint_type = robot_info.joint_types[joint_index];
+    let rotation_matrix = joint_type.rotation(&qcos, &qsin);
 
     let limi_rotation = (robot_info.calc_limi)(rotation_matrix.clone(), joint_index);
     limi_rotations.push(limi_rotation.clone());
-    let limi_translation = limi_translations[joint_index].clone();
-
-    let mut new_v_linear = Vector!([
-        data_v.at(0), data_v.at(1), data_v.at(2);
-    ]);
+    let limi_translation =
+        joint_type.translation(limi_translations[joint_index].clone(), &limi_rotation, &qsin);
+    let parent = robot_info.parents[joint_index];
 
-    let mut new_v_angular = Vector!([
-        data_v.at(3), data_v.at(4), v.at(joint_index);
-    ]);
+    //data.v[i] = jdata.v(), which is jdata.S() * v
+    let (mut new_v_linear, mut new_v_angular) = joint_type.motion(&v.at(joint_index));
 
-    let parent_v = match joint_index {
-        0 => Vector!([0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
-        _ => all_v[joint_index - 1].clone(),
+    let parent_v = match parent {
+        None => Vector!([0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+        Some(parent) => all_v[parent].clone(),
     };
 
     let parent_v_linear = Vector!([
@@ -627,9 +609,9 @@ fn first_pass(
         parent_v.at(3), parent_v.at(4), parent_v.at(5);
     ]);
 
-    let parent_a = match joint_index {
-        0 => Vector!([0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
-        _ => all_a[joint_index - 1].clone(),
+    let parent_a = match parent {
+        None => Vector!([0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+        Some(parent) => all_a[parent].clone(),
     };
 
     let parent_a_linear = Vector!([
@@ -646,15 +628,15 @@ fn first_pass(
     //  }
     //  else
     //    data.oMi[i] = data.liMi[i];
-    match joint_index {
-        0 => omis.push((limi_rotation.clone(), limi_translation.clone())),
-        _ => {
+    match parent {
+        None => omis.push((limi_rotation.clone(), limi_translation.clone())),
+        Some(parent) => {
             // the multiplication between oMi and liMi is defined as:
             //{ return SE3Tpl(rot*m2.rotation()
             //    ,translation()+rotation()*m2.translation());}
-            let omi_rotation_i = &omis[joint_index - 1].0.matmul(&limi_rotation);
-            let omi_translation_to_add = &omis[joint_index - 1].0.matmul_vec(&limi_translation);
-            let omi_translation_i = omis[joint_index - 1].1.clone() + omi_translation_to_add;
+            let omi_rotation_i = &omis[parent].0.matmul(&limi_rotation);
+            let omi_translation_to_add = &omis[parent].0.matmul_vec(&limi_translation);
+            let omi_translation_i = omis[parent].1.clone() + omi_translation_to_add;
             omis.push((omi_rotation_i.clone(), omi_translation_i));
             (new_v_linear, new_v_angular) = act_motion_inv(
                 &limi_translation,
@@ -684,22 +666,18 @@ fn first_pass(
     //data.a_gf[i] = jdata.c() + (data.v[i] ^ jdata.v());
     // ^ operator is actually implemented in pinocchio/include/pinocchio/spatial/cartesian-axis.hpp inline void CartesianAxis<2>::alphaCross
     // vout_[0] = -s*vin[1]; vout_[1] = s*vin[0]; vout_[2] = 0.;
-    let minus_m_w = -&(v.at(joint_index));
-    let alpha_cross_linear = alpha_cross_linear(&minus_m_w, &new_v_linear);
-    let alpha_cross_angular = alpha_cross_angular(&minus_m_w, &new_v_angular);
-
     // data.a_gf[i] += jdata.S() * jmodel.jointVelocitySelector(a);
     // jointVelocitySelector(a) is only a[joint_id]
-    // I couldn't print out info about jdata.S() easily but it is ConstraintRevoluteTpl, and I believe the only thing this line does is
-    // data.a_gf[i][5] = jmodel.jointVelocitySelector(a)
+    let (data_a_linear, data_a_angular) =
+        joint_type.acceleration(&a.at(joint_index), &v.at(joint_index), &new_v_linear, &new_v_angular);
 
     let mut new_data_a = Vector!([
-        alpha_cross_linear.at(0),
-        alpha_cross_linear.at(1),
-        alpha_cross_linear.at(2),
-        alpha_cross_angular.at(0),
-        alpha_cross_angular.at(1),
-        a.at(joint_index);
+        data_a_linear.at(0),
+        data_a_linear.at(1),
+        data_a_linear.at(2),
+        data_a_angular.at(0),
+        data_a_angular.at(1),
+        data_a_angular.at(2);
     ]);
 
     let temp_a_linear = Vector!([
@@ -718,26 +696,23 @@ fn first_pass(
     //  {
     //    data.a[i] += data.liMi[i].actInv(data.a[parent]);
     //  }
-    match joint_index {
-        0 => (),
-        _ => {
-            let limi_actinv_a_parent = act_motion_inv(
-                &limi_translation,
-                &limi_rotation,
-                &temp_a_linear,
-                &temp_a_angular,
-                &parent_a_linear,
-                &parent_a_angular,
-            );
-            new_data_a = Vector!([
-                limi_actinv_a_parent.0.at(0),
-                limi_actinv_a_parent.0.at(1),
-                limi_actinv_a_parent.0.at(2),
-                limi_actinv_a_parent.1.at(0),
-                limi_actinv_a_parent.1.at(1),
-                limi_actinv_a_parent.1.at(2);
-            ]);
-        }
+    if parent.is_some() {
+        let limi_actinv_a_parent = act_motion_inv(
+            &limi_translation,
+            &limi_rotation,
+            &temp_a_linear,
+            &temp_a_angular,
+            &parent_a_linear,
+            &parent_a_angular,
+        );
+        new_data_a = Vector!([
+            limi_actinv_a_parent.0.at(0),
+            limi_actinv_a_parent.0.at(1),
+            limi_actinv_a_parent.0.at(2),
+            limi_actinv_a_parent.1.at(0),
+            limi_actinv_a_parent.1.at(1),
+            limi_actinv_a_parent.1.at(2);
+        ]);
     }
 
     all_a.push(new_data_a.clone());
@@ -769,7 +744,8 @@ fn first_pass(
     all_oa.push(oa.clone());
 
     //oa_gf = oa - model.gravity; // add gravity contribution
-    let model_gravity = Vector!([0.0, 0.0, -9.81, 0.0, 0.0, 0.0]);
+    let zero = Scalar!(0.0);
+    let model_gravity = Vector!([gravity.at(0), gravity.at(1), gravity.at(2), zero, zero, zero;]);
     let oa_gf = &oa - &model_gravity;
 
     all_oa_gf.push(oa_gf.clone());
@@ -835,24 +811,26 @@ fn first_pass(
 
     let data_of_linear = data_of_linear_temp3 + data_of_linear_temp4;
     let data_of_angular = data_of_angular_temp3 + data_of_angular_temp6;
-    all_of.push(Vector!([
+    let data_of = Vector!([
         data_of_linear.at(0),
         data_of_linear.at(1),
         data_of_linear.at(2),
         data_of_angular.at(0),
         data_of_angular.at(1),
         data_of_angular.at(2);
-    ]));
+    ]);
+    // data.of[i] -= data.oMi[i].act(fext[i]);
+    let data_of = match f_ext {
+        Some(f_ext) => &data_of - &act_force(&omis[joint_index].0, &omis[joint_index].1, f_ext),
+        None => data_of,
+    };
+    all_of.push(data_of);
     // Correct until here
 
     // J_cols = data.oMi[i].act(jdata.S());
-    // S is ConstraintRevoluteTpl, and this function can be found in:
+    // For a revolute joint S is ConstraintRevoluteTpl, and this function can be found in:
     // include/pinocchio/multibody/joint/joint-revolute.hpp, ConstraintRevoluteTpl::se3Action
-    let (j_cols_linear, j_cols_angular) = act_constraint(
-        &omis[joint_index].0,
-        &omis[joint_index].1,
-        robot_info.joint_axes[joint_index],
-    );
+    let (j_cols_linear, j_cols_angular) = joint_type.act(&omis[joint_index].0, &omis[joint_index].1);
 
     j_cols.push(Vector!([
         j_cols_linear.at(0),
@@ -876,20 +854,21 @@ fn first_pass(
         dj_cols_angular.at(2);
     ]));
 
-    let oa_gf_parent_linear = match joint_index {
-        0 => Vector!([0.0, 0.0, 0.0]),
-        _ => Vector!([
-            all_oa_gf[joint_index - 1].at(0),
-            all_oa_gf[joint_index - 1].at(1),
-            all_oa_gf[joint_index - 1].at(2);
+    // data.oa_gf[0] is -model.gravity, a linear acceleration
+    let oa_gf_parent_linear = match parent {
+        None => Vector!([-&gravity.at(0), -&gravity.at(1), -&gravity.at(2);]),
+        Some(parent) => Vector!([
+            all_oa_gf[parent].at(0),
+            all_oa_gf[parent].at(1),
+            all_oa_gf[parent].at(2);
         ]),
     };
-    let oa_gf_parent_angular = match joint_index {
-        0 => Vector!([0.0, 0.0, 9.81]),
-        _ => Vector!([
-            all_oa_gf[joint_index - 1].at(3),
-            all_oa_gf[joint_index - 1].at(4),
-            all_oa_gf[joint_index - 1].at(5);
+    let oa_gf_parent_angular = match parent {
+        None => Vector!([0.0, 0.0, 0.0]),
+        Some(parent) => Vector!([
+            all_oa_gf[parent].at(3),
+            all_oa_gf[parent].at(4),
+            all_oa_gf[parent].at(5);
         ]),
     };
 
@@ -916,19 +895,19 @@ fn first_pass(
     //    dVdq_cols.setZero();
     //  }
 
-    let (dvdq_cols_linear, dvdq_cols_angular) = match joint_index {
-        0 => (Vector!([0.0, 0.0, 0.0]), Vector!([0.0, 0.0, 0.0])),
-        _ => {
+    let (dvdq_cols_linear, dvdq_cols_angular) = match parent {
+        None => (Vector!([0.0, 0.0, 0.0]), Vector!([0.0, 0.0, 0.0])),
+        Some(parent) => {
             // joint_index is one more than what it actually should be, so in parent for oa_gfs I should check joint_index for parent
             let data_ov_parent_linear = Vector!([
-                all_ov[joint_index - 1].at(0),
-                all_ov[joint_index - 1].at(1),
-                all_ov[joint_index - 1].at(2);
+                all_ov[parent].at(0),
+                all_ov[parent].at(1),
+                all_ov[parent].at(2);
             ]);
             let data_ov_parent_angular = Vector!([
-                all_ov[joint_index - 1].at(3),
-                all_ov[joint_index - 1].at(4),
-                all_ov[joint_index - 1].at(5);
+                all_ov[parent].at(3),
+                all_ov[parent].at(4),
+                all_ov[parent].at(5);
             ]);
             motion_action(
                 &data_ov_parent_linear,
@@ -941,19 +920,19 @@ fn first_pass(
 
     //    motionSet::motion_action<ADDTO>(data.ov[parent],dVdq_cols,dAdq_cols);
     //    dAdv_cols.noalias() += dVdq_cols;
-    match joint_index {
-        0 => (),
-        _ => {
+    match parent {
+        None => (),
+        Some(parent) => {
             // joint_index is one more than what it actually should be, so in parent for oa_gfs I should check joint_index for parent
             let data_ov_parent_linear = Vector!([
-                all_ov[joint_index - 1].at(0),
-                all_ov[joint_index - 1].at(1),
-                all_ov[joint_index - 1].at(2);
+                all_ov[parent].at(0),
+                all_ov[parent].at(1),
+                all_ov[parent].at(2);
             ]);
             let data_ov_parent_angular = Vector!([
-                all_ov[joint_index - 1].at(3),
-                all_ov[joint_index - 1].at(4),
-                all_ov[joint_index - 1].at(5);
+                all_ov[parent].at(3),
+                all_ov[parent].at(4),
+                all_ov[parent].at(5);
             ]);
             let (dadq_add_linear, dadq_add_angular) = motion_action(
                 &data_ov_parent_linear,
@@ -1077,6 +1056,20 @@ fn add_inertia(
     (new_mass, new_lever.clone(), new_inertia)
 }
 
+/// Whether `joint` is `root` or one of its descendants. Columns of joints
+/// outside the subtree are left out of the backward pass, like pinocchio's
+/// `middleCols(idx_v, nvSubtree)` blocks.
+fn in_subtree(parents: &[Option<usize>], root: usize, joint: usize) -> bool {
+    let mut joint = Some(joint);
+    while let Some(j) = joint {
+        if j <= root {
+            return j == root;
+        }
+        joint = parents[j];
+    }
+    false
+}
+
 fn second_pass(
     j_cols_vec: &mut [Vector],
     dvdq_cols: &mut [Vector],
@@ -1096,15 +1089,17 @@ fn second_pass(
     all_oycrb: &mut [(Matrix, Vector)],
     all_doycrb: &mut [Matrix],
     masses: &mut [Scalar],
+    parents: &[Option<usize>],
     joint_index: usize,
 ) {
     let n = masses.len();
+    let parent = parents[joint_index];
     let j_cols = j_cols_vec[joint_index].clone();
     //jmodel.jointVelocitySelector(data.tau).noalias() = J_cols.transpose()*data.of[i].toVector();
     let of_i = of[joint_index].clone();
-    let of_parent = match joint_index {
-        0 => Vector!([0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
-        _ => of[joint_index - 1].clone(),
+    let of_parent = match parent {
+        None => Vector!([0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+        Some(parent) => of[parent].clone(),
     };
     let tau = j_cols.dot(&of_i).define(format!("tau_{}", joint_index));
     taus[joint_index] = tau.clone();
@@ -1131,7 +1126,7 @@ fn second_pass(
     //        })
     //    });
     let mut dfda_cols_matrix = Matrix::zero(6, n);
-    for (i, dfda_col) in dfda_cols.iter().enumerate() {
+    for (i, dfda_col) in dfda_cols.iter().enumerate().filter(|(i, _)| in_subtree(parents, joint_index, *i)) {
         for j in 0..dfda_col.size() {
             dfda_cols_matrix.set((j, i), &dfda_col.at(j).clone()); // dfda last row first and last values are wrong
         }
@@ -1209,7 +1204,7 @@ fn second_pass(
     //).define(format!("dfdv_cols_final_{}", joint_index));
 
     let mut dfdv_cols_matrix = Matrix::zero(6, n);
-    for (i, dfdv_col) in dfdv_cols.iter().enumerate() {
+    for (i, dfdv_col) in dfdv_cols.iter().enumerate().filter(|(i, _)| in_subtree(parents, joint_index, *i)) {
         for j in 0..dfdv_col.size() {
             dfdv_cols_matrix.set((j, i), &dfdv_col.at(j).clone());
         }
@@ -1236,9 +1231,9 @@ fn second_pass(
     //   motionSet::inertiaAction(data.oYcrb[i],dAdq_cols,dFdq_cols);
     //   std::cout << "dFdq_cols temp 2: \n" << dFdq_cols << std::endl;
     // }
-    dfdq_cols[joint_index] = match joint_index {
+    dfdq_cols[joint_index] = match parent {
         // motionSet::inertiaAction(data.oYcrb[i],dAdq_cols,dFdq_cols);
-        0 => inertia_vec_mult(
+        None => inertia_vec_mult(
             masses[joint_index].clone(),
             &all_oycrb[joint_index].1,
             &all_oycrb[joint_index].0,
@@ -1246,7 +1241,7 @@ fn second_pass(
         ),
         //   dFdq_cols.noalias() = data.doYcrb[i] * dVdq_cols;
         //   motionSet::inertiaAction<ADDTO>(data.oYcrb[i],dAdq_cols,dFdq_cols);
-        _ => {
+        Some(_) => {
             let dfdq_temp = &doycrb_i.clone().matmul_vec(&dvdq_cols[joint_index].clone());
             let dfdq_temp2 = inertia_vec_mult(
                 masses[joint_index].clone(),
@@ -1268,7 +1263,7 @@ fn second_pass(
     //let dfdq_temp = Matrix::<6, N>::new(dfdq_data)
     //    .define(format!("dfdq_temp_generic_{}", joint_index));
     let mut dfdq_matrix = Matrix::zero(6, n);
-    for (i, dfdq_col) in dfdq_cols.iter().enumerate() {
+    for (i, dfdq_col) in dfdq_cols.iter().enumerate().filter(|(i, _)| in_subtree(parents, joint_index, *i)) {
         for j in 0..dfdq_col.size() {
             dfdq_matrix.set((j, i), &dfdq_col.at(j).clone());
         }
@@ -1303,7 +1298,7 @@ fn second_pass(
     //    dytj_cols[5].clone().define(format!("dytj_cols_5_{}", joint_index)),
     //).define(format!("dytj_final_{}", joint_index));
     let mut dytj = Matrix::zero(6, n);
-    for (i, dytj_col) in dytj_cols.iter().enumerate() {
+    for (i, dytj_col) in dytj_cols.iter().enumerate().filter(|(i, _)| in_subtree(parents, joint_index, *i)) {
         for j in 0..dytj_col.size() {
             //println!("i: {}, j: {}, dytj_cols[i].at(j): {:?}", i, j, dytj_cols[i].at(j));
             dytj.set((j, i), &dytj_col.at(j).clone());
@@ -1432,24 +1427,24 @@ fn second_pass(
     //    data.doYcrb[parent] += data.doYcrb[i];
     //    data.of[parent] += data.of[i];
     //  }
-    match joint_index {
-        0 => (),
-        _ => {
+    match parent {
+        None => (),
+        Some(parent) => {
             let oy = add_inertia(
-                &masses[joint_index - 1],
-                &all_oycrb[joint_index - 1].1,
-                &all_oycrb[joint_index - 1].0,
+                &masses[parent],
+                &all_oycrb[parent].1,
+                &all_oycrb[parent].0,
                 &masses[joint_index],
                 &all_oycrb[joint_index].1,
                 &all_oycrb[joint_index].0,
             );
-            masses[joint_index - 1] = oy.0; // correct
-            all_oycrb[joint_index - 1] = (oy.2, oy.1); // correct
+            masses[parent] = oy.0; // correct
+            all_oycrb[parent] = (oy.2, oy.1); // correct
 
-            let new_doycrb = all_doycrb[joint_index - 1].clone() + all_doycrb[joint_index].clone();
-            all_doycrb[joint_index - 1] = new_doycrb;
+            let new_doycrb = all_doycrb[parent].clone() + all_doycrb[joint_index].clone();
+            all_doycrb[parent] = new_doycrb;
 
-            of[joint_index - 1] = of_parent + of_i;
+            of[parent] = of_parent + of_i;
         }
     }
 }
@@ -1460,6 +1455,8 @@ fn second_pass(
 /// \param[in] a The joint acceleration vector (dim model.nv).
 /// jointPlacements are model.jointPlacements in pinocchio, it is a vector of SE3 objects
 /// SE3 has a rotation and a translation element
+/// q is passed as `qcos` and `qsin`, except for prismatic joints, whose
+/// displacement is their `qsin` entry; their `qcos` entry is not read.
 #[allow(dead_code)]
 pub fn rneaderivatives(
     qcos: Vector,
@@ -1467,6 +1464,23 @@ pub fn rneaderivatives(
     v: Vector,
     a: Vector,
     robot_info: &RobotInfo,
+) -> (Matrix, Matrix, Matrix, Vec<Scalar>) {
+    let gravity = Vector!([0.0, 0.0, -9.81]);
+    rneaderivatives_with_forces(qcos, qsin, v, a, &gravity, None, robot_info)
+}
+
+/// rneaderivatives under the gravity `gravity` with the external forces
+/// `f_ext` on the links, as in `rnea_with_forces`. The forces are held fixed
+/// in the joint frames, so they contribute to the derivative in q.
+#[allow(dead_code)]
+pub fn rneaderivatives_with_forces(
+    qcos: Vector,
+    qsin: Vector,
+    v: Vector,
+    a: Vector,
+    gravity: &Vector,
+    f_ext: Option<&[Vector]>,
+    robot_info: &RobotInfo,
 ) -> (Matrix, Matrix, Matrix, Vec<Scalar>) {
     let n_joints = robot_info.n_joints;
     let limi_translations = robot_info.limi_translations.clone();
@@ -1480,10 +1494,6 @@ pub fn rneaderivatives(
 
     let mut limi_rotations: Vec<Matrix> = vec![];
 
-    // we also have data.v, which v[0] is set to zero explicitly, and I assume the rest should also be zero
-    // I will keep it constant here, too, but keep in mind
-    let mut data_v = vec![];
-
     let mut all_v: Vec<Vector> = vec![];
     let mut all_of: Vec<Vector> = vec![];
     let mut all_oh: Vec<Vector> = vec![];
@@ -1502,11 +1512,9 @@ pub fn rneaderivatives(
 
     // first pass, it takes model.joints[i], data.joints[i], model, data, q, v, a
     for i in 0..n_joints {
-        data_v.push(Vector!([0.0, 0.0, 0.0, 0.0, 0.0, 0.0]));
         first_pass(
             qsin.at(i), // qsin and qcos will not change, therefore no reference needed
             qcos.at(i),
-            &data_v[i],
             &v,
             &a,
             &mut all_v,
@@ -1530,6 +1538,8 @@ pub fn rneaderivatives(
             &mut dadq_cols,
             &mut dadv_cols,
             &mut dvdq_cols,
+            gravity,
+            f_ext.map(|f_ext| &f_ext[i]),
             robot_info,
         );
     }
@@ -1570,6 +1580,7 @@ pub fn rneaderivatives(
             &mut all_oycrb,
             &mut all_doycrb,
             &mut vec_masses,
+            &robot_info.parents,
             i,
         );
     }
@@ -0,0 +1,133 @@
+use super::frames::{Inertial, Mat3, Transform, mat_mul, matrix_to_rpy, sin_cos};
+use super::model::{BaseModel, JointModel, RobotModel};
+use super::robot_info::{JointType, RobotInfo};
+
+/// Which of the two Denavit–Hartenberg conventions a table follows
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DhConvention {
+    /// Classic DH: link `i` is `Rz(theta_i) Tz(d_i) Tx(a_i) Rx(alpha_i)`, and
+    /// the frame of link `i` sits at its distal joint
+    Standard,
+    /// Craig's modified DH: link `i` is `Rx(alpha_{i-1}) Tx(a_{i-1}) Rz(theta_i) Tz(d_i)`,
+    /// and the frame of link `i` sits at joint `i`
+    Modified,
+}
+
+/// One row of a DH table; `theta = q + theta_offset`. Rows of a modified
+/// table hold `a_{i-1}` and `alpha_{i-1}`, as in Craig's and Franka's tables.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DhParameters {
+    pub a: f64,
+    pub alpha: f64,
+    pub d: f64,
+    pub theta_offset: f64,
+    pub bounds: (f64, f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DhTable {
+    pub convention: DhConvention,
+    pub joints: Vec<DhParameters>,
+}
+
+/// Mass properties of one link, given in the link's DH frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkInertial {
+    pub mass: f64,
+    /// Center of mass
+    pub lever: [f64; 3],
+    /// Inertia about the center of mass
+    pub inertia: [[f64; 3]; 3],
+}
+
+fn rotation_x(angle: f64) -> Mat3 {
+    let (s, c) = sin_cos(angle);
+    [[1.0, 0.0, 0.0], [0.0, c, -s], [0.0, s, c]]
+}
+
+fn rotation_z(angle: f64) -> Mat3 {
+    let (s, c) = sin_cos(angle);
+    [[c, -s, 0.0], [s, c, 0.0], [0.0, 0.0, 1.0]]
+}
+
+/// `Rz(theta_offset) Tz(d) Tx(a) Rx(alpha)`, the fixed part of a classic DH
+/// link after its joint
+fn standard_link(joint: &DhParameters) -> Transform {
+    let (s, c) = sin_cos(joint.theta_offset);
+    Transform {
+        rotation: mat_mul(&rotation_z(joint.theta_offset), &rotation_x(joint.alpha)),
+        translation: [c * joint.a, s * joint.a, joint.d],
+    }
+}
+
+/// `Rx(alpha) Tx(a) Tz(d) Rz(theta_offset)`, the fixed part of a modified DH
+/// link before its joint; `Tz(d)` commutes with the joint rotation
+fn modified_link(joint: &DhParameters) -> Transform {
+    let (s, c) = sin_cos(joint.alpha);
+    Transform {
+        rotation: mat_mul(&rotation_x(joint.alpha), &rotation_z(joint.theta_offset)),
+        translation: [joint.a, -s * joint.d, c * joint.d],
+    }
+}
+
+impl DhTable {
+    /// The joints of the table, with the link inertials moved into the joint
+    /// frames the kinematics use
+    pub fn model(&self, name: &str, link_inertials: &[LinkInertial]) -> RobotModel {
+        assert_eq!(
+            self.joints.len(),
+            link_inertials.len(),
+            "A DH table needs one link inertial per joint"
+        );
+        let joints = self
+            .joints
+            .iter()
+            .zip(link_inertials)
+            .enumerate()
+            .map(|(i, (joint, link))| {
+                // the placement of the joint in the previous joint frame, and of the link frame in the joint frame
+                let (placement, link_frame) = match self.convention {
+                    DhConvention::Standard => {
+                        let previous = i.checked_sub(1).map_or(Transform::IDENTITY, |i| standard_link(&self.joints[i]));
+                        (previous, standard_link(joint))
+                    }
+                    DhConvention::Modified => (modified_link(joint), Transform::IDENTITY),
+                };
+                let body = Inertial {
+                    mass: link.mass,
+                    com: link.lever,
+                    inertia: link.inertia,
+                }
+                .transformed(&link_frame);
+
+                JointModel {
+                    name: format!("joint_{}", i),
+                    parent: i.checked_sub(1),
+                    translation: placement.translation,
+                    rpy: matrix_to_rpy(&placement.rotation),
+                    joint_type: JointType::Revolute([0.0, 0.0, 1.0]),
+                    bounds: joint.bounds,
+                    mass: body.mass,
+                    lever: body.com,
+                    inertia: body.inertia,
+                }
+            })
+            .collect();
+        RobotModel {
+            name: name.to_string(),
+            base: BaseModel::default(),
+            joints,
+        }
+    }
+}
+
+impl RobotInfo {
+    /// Builds a robot from a DH table and the mass properties of its links,
+    /// see [`DhTable::model`] for the joint bounds. Twists and offsets of
+    /// multiples of pi/2 are traced with exact sines and cosines.
+    #[allow(dead_code)]
+    pub fn from_dh(table: &DhTable, link_inertials: &[LinkInertial]) -> RobotInfo {
+        table.model("dh", link_inertials).robot_info()
+    }
+}
@@ -0,0 +1,136 @@
+
+pub(super) type Vec3 = [f64; 3];
+pub(super) type Mat3 = [[f64; 3]; 3];
+
+pub(super) const IDENTITY: Mat3 = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Rigid transform, a rotation followed by a translation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Transform {
+    pub(super) rotation: Mat3,
+    pub(super) translation: Vec3,
+}
+
+impl Transform {
+    pub(super) const IDENTITY: Transform = Transform {
+        rotation: IDENTITY,
+        translation: [0.0; 3],
+    };
+
+    pub(super) fn compose(&self, other: &Transform) -> Transform {
+        Transform {
+            rotation: mat_mul(&self.rotation, &other.rotation),
+            translation: add(&self.translation, &mat_vec(&self.rotation, &other.translation)),
+        }
+    }
+}
+
+/// Mass properties of a link, the inertia taken about the center of mass
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Inertial {
+    pub(super) mass: f64,
+    pub(super) com: Vec3,
+    pub(super) inertia: Mat3,
+}
+
+impl Inertial {
+    pub(super) fn transformed(&self, transform: &Transform) -> Inertial {
+        let rotation = &transform.rotation;
+        Inertial {
+            mass: self.mass,
+            com: add(&transform.translation, &mat_vec(rotation, &self.com)),
+            inertia: mat_mul(&mat_mul(rotation, &self.inertia), &transpose(rotation)),
+        }
+    }
+}
+
+pub(super) fn add(a: &Vec3, b: &Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(super) fn mat_vec(m: &Mat3, v: &Vec3) -> Vec3 {
+    [0, 1, 2].map(|i| m[i][0] * v[0] + m[i][1] * v[1] + m[i][2] * v[2])
+}
+
+pub(super) fn mat_mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    [0, 1, 2].map(|i| [0, 1, 2].map(|j| a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j]))
+}
+
+pub(super) fn transpose(m: &Mat3) -> Mat3 {
+    [0, 1, 2].map(|i| [0, 1, 2].map(|j| m[j][i]))
+}
+
+/// The unit vector along `v`, `None` for the zero vector
+pub(super) fn normalize(v: &Vec3) -> Option<Vec3> {
+    let norm = v.iter().map(|value| value * value).sum::<f64>().sqrt();
+    (norm > 0.0).then(|| v.map(|value| value / norm))
+}
+
+/// Sine and cosine, exact at multiples of pi/2 such as the common twists of
+/// +-pi/2 where `cos` would return about 6e-17 instead of 0
+pub(super) fn sin_cos(angle: f64) -> (f64, f64) {
+    let quarters = angle / std::f64::consts::FRAC_PI_2;
+    if (quarters - quarters.round()).abs() > 1e-12 {
+        return angle.sin_cos();
+    }
+    match (quarters.round() as i64).rem_euclid(4) {
+        0 => (0.0, 1.0),
+        1 => (1.0, 0.0),
+        2 => (0.0, -1.0),
+        _ => (-1.0, 0.0),
+    }
+}
+
+/// Rotation of URDF `rpy` angles, `Rz(yaw) * Ry(pitch) * Rx(roll)`
+pub(super) fn rpy_to_matrix(rpy: &Vec3) -> Mat3 {
+    let (sr, cr) = sin_cos(rpy[0]);
+    let (sp, cp) = sin_cos(rpy[1]);
+    let (sy, cy) = sin_cos(rpy[2]);
+    [
+        [cy * cp, cy * sp * sr - sy * cr, cy * sp * cr + sy * sr],
+        [sy * cp, sy * sp * sr + cy * cr, sy * sp * cr - cy * sr],
+        [-sp, cp * sr, cp * cr],
+    ]
+}
+
+/// Angles of a rotation, the inverse of [`rpy_to_matrix`]
+pub(super) fn matrix_to_rpy(m: &Mat3) -> Vec3 {
+    // more accurate than the arcsine of m[2][0] close to the poles
+    let pitch = (-m[2][0]).atan2(m[0][0].hypot(m[1][0]));
+    if m[2][0].abs() < 1.0 - 1e-12 {
+        [m[2][1].atan2(m[2][2]), pitch, m[1][0].atan2(m[0][0])]
+    } else {
+        // gimbal lock, only the sum or difference of roll and yaw is defined
+        [0.0, pitch, (-m[0][1]).atan2(m[1][1])]
+    }
+}
+
+/// Combines rigidly attached bodies into one, about their common center of mass
+pub(super) fn merge_inertials(inertials: &[Inertial]) -> Inertial {
+    // keeps the values of the file exact for a single body
+    if let [inertial] = inertials {
+        return *inertial;
+    }
+    let mass: f64 = inertials.iter().map(|inertial| inertial.mass).sum();
+    if mass == 0.0 {
+        return Inertial {
+            mass,
+            com: [0.0; 3],
+            inertia: [[0.0; 3]; 3],
+        };
+    }
+    let com = [0, 1, 2].map(|k| inertials.iter().map(|inertial| inertial.mass * inertial.com[k]).sum::<f64>() / mass);
+    let mut inertia = [[0.0; 3]; 3];
+    for inertial in inertials {
+        // parallel axis theorem
+        let d = [0, 1, 2].map(|k| inertial.com[k] - com[k]);
+        let d2 = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+        for i in 0..3 {
+            for j in 0..3 {
+                let shift = if i == j { d2 } else { 0.0 } - d[i] * d[j];
+                inertia[i][j] += inertial.inertia[i][j] + inertial.mass * shift;
+            }
+        }
+    }
+    Inertial { mass, com, inertia }
+}
@@ -0,0 +1,344 @@
+use super::frames::{IDENTITY, Inertial, Mat3, Transform, Vec3, mat_mul, matrix_to_rpy, merge_inertials, normalize};
+use super::robot_info::JointType;
+use super::model::{BaseModel, JointModel, RobotModel};
+
+use anyhow::{Context, Result, bail};
+use roxmltree::{Document, Node};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Settings of the `<compiler>` element and the `<default>` classes
+struct Settings<'a, 'input> {
+    degrees: bool,
+    eulerseq: String,
+    /// `<default>` elements by class name
+    defaults: HashMap<&'a str, Node<'a, 'input>>,
+}
+
+impl<'a, 'input> Settings<'a, 'input> {
+    fn new(mujoco: Node<'a, 'input>) -> Result<Self> {
+        let mut settings = Settings {
+            degrees: true,
+            eulerseq: "xyz".to_string(),
+            defaults: HashMap::new(),
+        };
+        for compiler in mujoco.children().filter(|node| node.has_tag_name("compiler")) {
+            match compiler.attribute("angle") {
+                Some("degree") => settings.degrees = true,
+                Some("radian") => settings.degrees = false,
+                Some(angle) => bail!("Invalid angle=\"{}\" in <compiler>", angle),
+                None => {}
+            }
+            if let Some(eulerseq) = compiler.attribute("eulerseq") {
+                if eulerseq.len() != 3 || !eulerseq.chars().all(|axis| "xyzXYZ".contains(axis)) {
+                    bail!("Invalid eulerseq=\"{}\" in <compiler>", eulerseq);
+                }
+                settings.eulerseq = eulerseq.to_string();
+            }
+        }
+        for default in mujoco.descendants().filter(|node| node.has_tag_name("default")) {
+            let class = match default.attribute("class") {
+                Some(class) => class,
+                // only the top-level default may omit its class
+                None if default.parent().is_some_and(|parent| parent.has_tag_name("mujoco")) => "main",
+                None => bail!("Nested <default> without a class"),
+            };
+            settings.defaults.insert(class, default);
+        }
+        Ok(settings)
+    }
+
+    fn angle(&self, value: f64) -> f64 {
+        if self.degrees { value.to_radians() } else { value }
+    }
+
+    /// The attribute of `node`, or else of the `<tag>` of its default class
+    /// and the classes that class is nested in
+    fn attribute(&self, node: Node<'a, 'input>, name: &str) -> Option<&'a str> {
+        if let Some(value) = node.attribute(name) {
+            return Some(value);
+        }
+        let class = node.attribute("class").or_else(|| {
+            node.ancestors()
+                .skip(1)
+                .find_map(|ancestor| ancestor.attribute("childclass"))
+        });
+        let mut default = self.defaults.get(class.unwrap_or("main")).copied();
+        while let Some(current) = default {
+            let value = current
+                .children()
+                .find(|child| child.tag_name() == node.tag_name())
+                .and_then(|child| child.attribute(name));
+            if value.is_some() {
+                return value;
+            }
+            default = current.parent().filter(|parent| parent.has_tag_name("default"));
+        }
+        None
+    }
+
+    fn numbers(&self, node: Node<'a, 'input>, name: &str) -> Result<Option<Vec<f64>>> {
+        let Some(text) = self.attribute(node, name) else {
+            return Ok(None);
+        };
+        let values = text
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<f64>, _>>()
+            .with_context(|| format!("Invalid {}=\"{}\" in <{}>", name, text, node.tag_name().name()))?;
+        Ok(Some(values))
+    }
+
+    /// Exactly `N` numbers of an attribute, `default` when it is missing
+    fn array<const N: usize>(&self, node: Node<'a, 'input>, name: &str, default: [f64; N]) -> Result<[f64; N]> {
+        match self.numbers(node, name)? {
+            Some(values) => values
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Expected {} values in {} of <{}>", N, name, node.tag_name().name())),
+            None => Ok(default),
+        }
+    }
+
+    /// The frame given by `pos` and one of `quat` or `euler`
+    fn frame(&self, node: Node<'a, 'input>) -> Result<Transform> {
+        for unsupported in ["axisangle", "xyaxes", "zaxis"] {
+            if node.has_attribute(unsupported) {
+                bail!("<{}> uses {}, only quat and euler are supported", node.tag_name().name(), unsupported);
+            }
+        }
+        let rotation = match (node.has_attribute("quat"), node.has_attribute("euler")) {
+            (true, true) => bail!("<{}> has both quat and euler", node.tag_name().name()),
+            (_, true) => euler_to_matrix(&self.array(node, "euler", [0.0; 3])?.map(|angle| self.angle(angle)), &self.eulerseq),
+            _ => quat_to_matrix(&self.array(node, "quat", [1.0, 0.0, 0.0, 0.0])?),
+        };
+        Ok(Transform {
+            rotation,
+            translation: self.array(node, "pos", [0.0; 3])?,
+        })
+    }
+}
+
+/// Rotation of a `w x y z` quaternion, which need not be normalized
+fn quat_to_matrix(quat: &[f64; 4]) -> Mat3 {
+    let norm = quat.iter().map(|value| value * value).sum::<f64>().sqrt();
+    let [w, x, y, z] = quat.map(|value| value / norm);
+    [
+        [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y)],
+        [2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x)],
+        [2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y)],
+    ]
+}
+
+/// Rotation of Euler angles; lowercase axes of `sequence` rotate with the
+/// frame, uppercase axes are fixed
+fn euler_to_matrix(angles: &Vec3, sequence: &str) -> Mat3 {
+    let mut rotation = IDENTITY;
+    for (axis, angle) in sequence.chars().zip(angles) {
+        let (s, c) = angle.sin_cos();
+        let elementary = match axis.to_ascii_lowercase() {
+            'x' => [[1.0, 0.0, 0.0], [0.0, c, -s], [0.0, s, c]],
+            'y' => [[c, 0.0, s], [0.0, 1.0, 0.0], [-s, 0.0, c]],
+            _ => [[c, -s, 0.0], [s, c, 0.0], [0.0, 0.0, 1.0]],
+        };
+        rotation = if axis.is_ascii_lowercase() {
+            mat_mul(&rotation, &elementary)
+        } else {
+            mat_mul(&elementary, &rotation)
+        };
+    }
+    rotation
+}
+
+fn parse_inertial<'a, 'input>(body: Node<'a, 'input>, settings: &Settings<'a, 'input>) -> Result<Option<Inertial>> {
+    let Some(inertial) = body.children().find(|node| node.has_tag_name("inertial")) else {
+        return Ok(None);
+    };
+    let mass = settings.numbers(inertial, "mass")?.context("<inertial> has no mass")?;
+    let [mass] = mass[..] else {
+        bail!("Expected one value in mass of <inertial>");
+    };
+    let inertia = match (settings.numbers(inertial, "diaginertia")?, settings.numbers(inertial, "fullinertia")?) {
+        (Some(diagonal), None) => {
+            let [ixx, iyy, izz] = diagonal[..] else {
+                bail!("Expected three values in diaginertia of <inertial>");
+            };
+            [[ixx, 0.0, 0.0], [0.0, iyy, 0.0], [0.0, 0.0, izz]]
+        }
+        (None, Some(full)) => {
+            let [ixx, iyy, izz, ixy, ixz, iyz] = full[..] else {
+                bail!("Expected six values in fullinertia of <inertial>");
+            };
+            [[ixx, ixy, ixz], [ixy, iyy, iyz], [ixz, iyz, izz]]
+        }
+        _ => bail!("<inertial> needs one of diaginertia and fullinertia"),
+    };
+
+    // the inertia is given in the frame of the inertial element
+    let local = Inertial {
+        mass,
+        com: [0.0; 3],
+        inertia,
+    };
+    Ok(Some(local.transformed(&settings.frame(inertial)?)))
+}
+
+/// The joint of a body, `None` for a body welded to its parent
+fn body_joint<'a, 'input>(body: Node<'a, 'input>, settings: &Settings<'a, 'input>) -> Result<Option<Node<'a, 'input>>> {
+    let name = body.attribute("name").unwrap_or("unnamed");
+    let joints: Vec<Node> = body
+        .children()
+        .filter(|node| node.has_tag_name("joint") || node.has_tag_name("freejoint"))
+        .collect();
+    let [joint] = joints[..] else {
+        if joints.is_empty() {
+            return Ok(None);
+        }
+        bail!("Body {} has {} joints, only one joint per body is supported", name, joints.len());
+    };
+    let kind = if joint.has_tag_name("freejoint") {
+        "free"
+    } else {
+        settings.attribute(joint, "type").unwrap_or("hinge")
+    };
+    if kind != "hinge" && kind != "slide" {
+        bail!("Body {} has a {} joint, only hinge and slide joints are supported", name, kind);
+    }
+    Ok(Some(joint))
+}
+
+/// Collects the inertials of `body` and the bodies welded to it, in the frame
+/// `placement` of `body`, and returns the jointed bodies below them with
+/// their frames
+fn rigid_body<'a, 'input>(
+    body: Node<'a, 'input>,
+    placement: Transform,
+    settings: &Settings<'a, 'input>,
+    inertials: &mut Vec<Inertial>,
+) -> Result<Vec<(Node<'a, 'input>, Transform)>> {
+    if let Some(inertial) = parse_inertial(body, settings)? {
+        inertials.push(inertial.transformed(&placement));
+    }
+    let mut jointed = vec![];
+    for child in body.children().filter(|node| node.has_tag_name("body")) {
+        let frame = placement.compose(&settings.frame(child)?);
+        match body_joint(child, settings)? {
+            Some(_) => jointed.push((child, frame)),
+            None => jointed.extend(rigid_body(child, frame, settings, inertials)?),
+        }
+    }
+    Ok(jointed)
+}
+
+/// Adds the joint of `body`, whose frame is `frame` in its parent joint's
+/// frame, to `model`, followed depth-first by the joints below it
+fn add_subtree<'a, 'input>(
+    body: Node<'a, 'input>,
+    frame: Transform,
+    parent: Option<usize>,
+    settings: &Settings<'a, 'input>,
+    model: &mut RobotModel,
+) -> Result<()> {
+    let joint = body_joint(body, settings)?.expect("bodies are only returned with a joint");
+    let name = joint.attribute("name").or(body.attribute("name")).unwrap_or("unnamed");
+    let axis = settings.array(joint, "axis", [0.0, 0.0, 1.0])?;
+    let axis = normalize(&axis).with_context(|| format!("Joint {} has a zero axis", name))?;
+    let slide = settings.attribute(joint, "type") == Some("slide");
+    let range = match (settings.attribute(joint, "limited"), settings.numbers(joint, "range")?) {
+        (Some("false"), _) | (_, None) => None,
+        (_, Some(range)) => match range[..] {
+            [lower, upper] => Some((lower, upper)),
+            _ => bail!("Expected two values in range of joint {}", name),
+        },
+    };
+    let (joint_type, bounds) = match (slide, range) {
+        (true, Some(range)) => (JointType::Prismatic(axis), range),
+        (true, None) => bail!("Slide joint {} has no range", name),
+        (false, Some((lower, upper))) => (JointType::Revolute(axis), (settings.angle(lower), settings.angle(upper))),
+        (false, None) => (JointType::Revolute(axis), (-std::f64::consts::PI, std::f64::consts::PI)),
+    };
+
+    // the joint frame sits at the joint's position in the body
+    let position = settings.array(joint, "pos", [0.0; 3])?;
+    let origin = frame.compose(&Transform {
+        rotation: IDENTITY,
+        translation: position,
+    });
+    let mut inertials = vec![];
+    let contents = Transform {
+        rotation: IDENTITY,
+        translation: position.map(|value| -value),
+    };
+    let children = rigid_body(body, contents, settings, &mut inertials)?;
+    let inertial = merge_inertials(&inertials);
+
+    let index = model.joints.len();
+    model.joints.push(JointModel {
+        name: name.to_string(),
+        parent,
+        translation: origin.translation,
+        rpy: matrix_to_rpy(&origin.rotation),
+        joint_type,
+        bounds,
+        mass: inertial.mass,
+        lever: inertial.com,
+        inertia: inertial.inertia,
+    });
+
+    for (child, frame) in children {
+        add_subtree(child, frame, Some(index), settings, model)?;
+    }
+    Ok(())
+}
+
+/// Reads a manipulator from the text of a MuJoCo MJCF file.
+///
+/// Every body with a hinge or slide joint becomes a joint of the model,
+/// depth-first from the world body so that every joint follows its parent.
+/// Bodies without a joint are welded to their parent: they move the frame of
+/// the joints below them and add their `<inertial>` to the moving body, or to
+/// the base when welded to the world body. Hinges without a range are bounded
+/// by `[-pi, pi]`, slide joints need a range. The `<compiler>` angle unit and
+/// euler sequence and the `<default>` classes are applied; inertias are not
+/// inferred from geoms, so bodies without an `<inertial>` are massless.
+#[allow(dead_code)]
+pub fn parse_mjcf(text: &str) -> Result<RobotModel> {
+    let document = Document::parse(text).context("Failed to parse MJCF")?;
+    let mujoco = document.root_element();
+    if !mujoco.has_tag_name("mujoco") {
+        bail!("Expected a <mujoco> element, found <{}>", mujoco.tag_name().name());
+    }
+    let settings = Settings::new(mujoco)?;
+    let world = mujoco
+        .children()
+        .find(|node| node.has_tag_name("worldbody"))
+        .context("MJCF has no <worldbody>")?;
+
+    let mut base = vec![];
+    let jointed = rigid_body(world, Transform::IDENTITY, &settings, &mut base)?;
+    let base = merge_inertials(&base);
+    let mut model = RobotModel {
+        name: mujoco.attribute("model").unwrap_or_default().to_string(),
+        base: BaseModel {
+            mass: base.mass,
+            lever: base.com,
+            inertia: base.inertia,
+        },
+        joints: vec![],
+    };
+    for (body, frame) in jointed {
+        add_subtree(body, frame, None, &settings, &mut model)?;
+    }
+
+    if model.joints.is_empty() {
+        bail!("MJCF has no hinge or slide joints");
+    }
+    Ok(model)
+}
+
+/// Reads a manipulator from an MJCF file, see [`parse_mjcf`]
+#[allow(dead_code)]
+pub fn load_mjcf<P: AsRef<Path>>(path: P) -> Result<RobotModel> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read MJCF {}", path.display()))?;
+    parse_mjcf(&text).with_context(|| format!("Failed to load MJCF {}", path.display()))
+}
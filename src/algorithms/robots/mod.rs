@@ -1,7 +1,12 @@
+pub mod dh;
 pub mod indy7;
+pub mod mjcf;
+pub mod model;
 pub mod panda;
 pub mod roarm_m2;
 pub mod roarm_m3;
 pub mod robot_info;
+pub mod urdf;
 
+mod frames;
 mod helper;
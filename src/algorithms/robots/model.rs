@@ -0,0 +1,130 @@
+use super::frames::sin_cos;
+use super::robot_info::{BaseInfo, JointType, RobotInfo};
+
+use crate::algorithms::robots::helper::rpy_to_matrix_from_trig_components;
+use roboprec::{Matrix, Real, Scalar, Vector};
+
+/// One joint of a kinematic tree and the link it moves
+#[derive(Debug, Clone, PartialEq)]
+pub struct JointModel {
+    pub name: String,
+    /// Index of the joint this joint is mounted on, `None` for the base. It is
+    /// smaller than the joint's own index.
+    pub parent: Option<usize>,
+    /// Placement of the joint frame in the parent joint frame
+    pub translation: [f64; 3],
+    /// Fixed rotation of the joint frame as roll, pitch, yaw (ZYX intrinsic)
+    pub rpy: [f64; 3],
+    /// Motion of the joint, with the axis in the joint frame
+    pub joint_type: JointType,
+    pub bounds: (f64, f64),
+    /// Mass, center of mass and inertia about the center of mass of the
+    /// moved link, in the joint frame
+    pub mass: f64,
+    pub lever: [f64; 3],
+    pub inertia: [[f64; 3]; 3],
+}
+
+/// Mass, center of mass and inertia about the center of mass of the links
+/// fixed to the base, in the base frame. Only a floating base uses them.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BaseModel {
+    pub mass: f64,
+    pub lever: [f64; 3],
+    pub inertia: [[f64; 3]; 3],
+}
+
+/// Numeric description of a manipulator, e.g. read from a URDF file
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobotModel {
+    pub name: String,
+    pub base: BaseModel,
+    pub joints: Vec<JointModel>,
+}
+
+#[allow(dead_code)]
+impl RobotModel {
+    pub fn n_joints(&self) -> usize {
+        self.joints.len()
+    }
+
+    /// Joint limits, in the form returned by e.g. `panda_get_bounds`
+    pub fn bounds(&self) -> Vec<(Real, Real)> {
+        self.joints
+            .iter()
+            .map(|joint| (Real::from_f64(joint.bounds.0), Real::from_f64(joint.bounds.1)))
+            .collect()
+    }
+
+    /// Motion of every joint, for `joint_inputs` along with `bounds`
+    pub fn joint_types(&self) -> Vec<JointType> {
+        self.joints.iter().map(|joint| joint.joint_type).collect()
+    }
+
+    /// Traces the model's constants, named like the hand-written robots. Angles
+    /// at multiples of pi/2 get exact sines and cosines.
+    pub fn robot_info(&self) -> RobotInfo {
+        let limi_translations = self
+            .joints
+            .iter()
+            .enumerate()
+            .map(|(i, joint)| Vector::new("vector", joint.translation.to_vec()).define(format!("limi_translation_{}", i)))
+            .collect();
+
+        let levers = self
+            .joints
+            .iter()
+            .enumerate()
+            .map(|(i, joint)| Vector::new("vector", joint.lever.to_vec()).define(format!("lever_{}", i)))
+            .collect();
+
+        let masses = self.joints.iter().map(|joint| joint.mass).collect();
+        let masses = Vector::new("vector", masses).define("masses".to_string());
+
+        let inertias = self
+            .joints
+            .iter()
+            .enumerate()
+            .map(|(i, joint)| {
+                let rows = joint.inertia.iter().map(|row| row.to_vec()).collect();
+                Matrix::new("matrix", rows).define(format!("inertia_{}", i))
+            })
+            .collect();
+
+        let rpys: Vec<[f64; 3]> = self.joints.iter().map(|joint| joint.rpy).collect();
+        let calc_limi = move |rotation_matrix_joint: Matrix, joint_index: usize| {
+            let [(sin_r, cos_r), (sin_p, cos_p), (sin_y, cos_y)] = rpys[joint_index].map(sin_cos);
+            let r_fix = rpy_to_matrix_from_trig_components(
+                Scalar!(cos_r),
+                Scalar!(sin_r),
+                Scalar!(cos_p),
+                Scalar!(sin_p),
+                Scalar!(cos_y),
+                Scalar!(sin_y),
+            )
+            .define(format!("R_fix_{}", joint_index));
+            r_fix.matmul(&rotation_matrix_joint)
+        };
+
+        RobotInfo {
+            n_joints: self.n_joints(),
+            limi_translations,
+            calc_limi: Box::new(calc_limi),
+            joint_types: self.joint_types(),
+            parents: self.joints.iter().map(|joint| joint.parent).collect(),
+            levers,
+            masses,
+            inertias,
+        }
+    }
+
+    /// Traces the mass properties of the base, for `rnea_floating_base`
+    pub fn base_info(&self) -> BaseInfo {
+        let rows = self.base.inertia.iter().map(|row| row.to_vec()).collect();
+        BaseInfo {
+            mass: Scalar!(self.base.mass),
+            lever: Vector::new("vector", self.base.lever.to_vec()).define("base_lever".to_string()),
+            inertia: Matrix::new("matrix", rows).define("base_inertia".to_string()),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::robot_info::RobotInfo;
+use super::robot_info::{JointType, RobotInfo, serial_parents};
 
 use roboprec::{Matrix, Scalar, Vector, Real};
 use crate::algorithms::robots::helper::rpy_to_matrix_from_trig_components;
@@ -193,13 +193,14 @@ pub fn panda() -> RobotInfo {
         .define("inertia_6".to_string()),
     ];
 
-    let joint_axes = vec![2, 2, 2, 2, 2, 2, 2]; // All joints are <axis xyz="0 0 1"/> (Z-axis)
+    let joint_types = vec![JointType::Revolute([0.0, 0.0, 1.0]); 7]; // All joints are <axis xyz="0 0 1"/> (Z-axis)
 
     RobotInfo {
         n_joints,
         limi_translations,
         calc_limi: Box::new(calc_limi),
-        joint_axes,
+        joint_types,
+        parents: serial_parents(n_joints),
         levers,
         masses,
         inertias,
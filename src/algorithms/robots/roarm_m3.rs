@@ -1,4 +1,4 @@
-use super::robot_info::RobotInfo;
+use super::robot_info::{JointType, RobotInfo, serial_parents};
 
 use roboprec::{Matrix, Scalar, Vector, Real};
 use crate::algorithms::robots::helper::rpy_to_matrix_from_trig_components;
@@ -94,7 +94,7 @@ pub fn calc_limi(
 
 #[allow(clippy::approx_constant)]
 #[allow(dead_code)]
-pub fn roarm_m2_get_bounds() -> Vec<(Real, Real)> {
+pub fn roarm_m3_get_bounds() -> Vec<(Real, Real)> {
     let joint_bounds = [
         (-3.1416, 3.1416),
         (-1.5708, 1.5708),
@@ -109,7 +109,7 @@ pub fn roarm_m2_get_bounds() -> Vec<(Real, Real)> {
 }
 
 #[allow(dead_code)]
-pub fn roarm_m2() -> RobotInfo {
+pub fn roarm_m3() -> RobotInfo {
     let limi_translations = vec![
         Vector!([0.0, 0.0, 0.0]).define("limi_translation_0".to_string()), // base_link_to_link1
         Vector!([0.0, 0.0, 0.051959]).define("limi_translation_1".to_string()), // link1_to_link2
@@ -169,13 +169,14 @@ pub fn roarm_m2() -> RobotInfo {
     ];
 
     // All revolute joints in the URDF specify <axis xyz="0 0 1"/> (Z-axis)
-    let joint_axes = vec![2, 2, 2, 2, 2];
+    let joint_types = vec![JointType::Revolute([0.0, 0.0, 1.0]); 5];
 
     RobotInfo {
         n_joints,
         limi_translations,
         calc_limi: Box::new(calc_limi),
-        joint_axes,
+        joint_types,
+        parents: serial_parents(n_joints),
         levers,
         masses,
         inertias,
@@ -1,11 +1,263 @@
-use roboprec::{Matrix, Vector};
+use roboprec::{Matrix, Real, Scalar, Vector, add_input_matrix, add_input_scalar, add_input_vector};
+
+/// How a joint moves its link relative to the joint's placement, with the
+/// axis given as a unit vector in the joint frame
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JointType {
+    /// Rotation about the axis by the angle given through `qcos` and `qsin`
+    Revolute([f64; 3]),
+    /// Translation along the axis; the displacement is read from `qsin`, and
+    /// the joint's `qcos` is unused
+    Prismatic([f64; 3]),
+    /// No motion, the link moves rigidly with its parent and its inertia acts
+    /// on the parent's joint. Its entries of `q`, `v` and `a` are unused, and
+    /// its torque and derivatives are zero.
+    Fixed,
+}
 
 pub struct RobotInfo {
     pub n_joints: usize,
     pub limi_translations: Vec<Vector>,
     pub calc_limi: Box<dyn Fn(Matrix, usize) -> Matrix>,
-    pub joint_axes: Vec<usize>,
+    pub joint_types: Vec<JointType>,
+    /// Parent of each joint, `None` for joints attached to the base. Parents
+    /// come before their children, as in pinocchio's `model.parents`.
+    pub parents: Vec<Option<usize>>,
     pub levers: Vec<Vector>,
     pub masses: Vector,
     pub inertias: Vec<Matrix>,
 }
+
+/// Mass, center of mass and inertia about the center of mass of a floating
+/// base, in the base frame
+pub struct BaseInfo {
+    pub mass: Scalar,
+    pub lever: Vector,
+    pub inertia: Matrix,
+}
+
+/// Parents of a serial chain, where each joint moves the one after it
+pub fn serial_parents(n_joints: usize) -> Vec<Option<usize>> {
+    (0..n_joints).map(|i| i.checked_sub(1)).collect()
+}
+
+fn input_range((min, max): (f64, f64)) -> (Real, Real) {
+    (Real::from_f64(min), Real::from_f64(max))
+}
+
+/// The dynamic parameters are constants unless marked uncertain here. Each
+/// marked parameter becomes an input ranging over the given interval, with its
+/// constant value as the default, so that the analysis bounds the results over
+/// all parameters in range, e.g. over a payload envelope.
+#[allow(dead_code)]
+impl RobotInfo {
+    /// Makes the mass of `joint`'s link the input `param_mass_{joint}`
+    pub fn ranged_mass(&mut self, joint: usize, range: (f64, f64)) {
+        let nominal = self.masses.at(joint).value_f64();
+        let mass = add_input_scalar(&format!("param_mass_{}", joint), input_range(range), nominal);
+        let masses: Vec<Scalar> = (0..self.n_joints)
+            .map(|i| if i == joint { mass.clone() } else { self.masses.at(i) })
+            .collect();
+        self.masses = Vector::from_scalars("vector", masses.iter().collect());
+    }
+
+    /// Makes the center of mass of `joint`'s link the input `param_lever_{joint}`
+    pub fn ranged_lever(&mut self, joint: usize, ranges: [(f64, f64); 3]) {
+        let nominal = self.levers[joint].value_f64();
+        let ranges = ranges.into_iter().map(input_range).collect();
+        self.levers[joint] = add_input_vector(&format!("param_lever_{}", joint), ranges, nominal);
+    }
+
+    /// Makes the inertia of `joint`'s link the input `param_inertia_{joint}`.
+    /// Only the upper triangle is read, so the lower one may repeat it.
+    pub fn ranged_inertia(&mut self, joint: usize, ranges: [[(f64, f64); 3]; 3]) {
+        let nominal = self.inertias[joint].value_f64();
+        let ranges = ranges
+            .into_iter()
+            .map(|row| row.into_iter().map(input_range).collect())
+            .collect();
+        self.inertias[joint] = add_input_matrix(&format!("param_inertia_{}", joint), ranges, nominal);
+    }
+}
+
+/// `coefficient * scalar`, without tracing a multiplication for plus or minus one
+fn scale(coefficient: f64, scalar: &Scalar) -> Scalar {
+    if coefficient == 1.0 {
+        scalar.clone()
+    } else if coefficient == -1.0 {
+        -scalar
+    } else {
+        &Scalar!(coefficient) * scalar
+    }
+}
+
+/// Sum of the terms, a constant zero if there are none
+fn sum(terms: Vec<Scalar>) -> Scalar {
+    terms
+        .into_iter()
+        .reduce(|sum, term| &sum + &term)
+        .unwrap_or(Scalar!(0.0))
+}
+
+/// `axis * scalar`, with constant zeros where the axis has none
+fn scale_axis(axis: &[f64; 3], scalar: &Scalar) -> Vector {
+    let components: Vec<Scalar> = axis
+        .iter()
+        .map(|&coefficient| match coefficient {
+            0.0 => Scalar!(0.0),
+            _ => scale(coefficient, scalar),
+        })
+        .collect();
+    Vector::from_scalars("vector", components.iter().collect())
+}
+
+/// `s * (axis x vin)`, the generalisation of pinocchio's CartesianAxis::alphaCross
+fn axis_cross(axis: &[f64; 3], s: &Scalar, vin: &Vector) -> Vector {
+    let components: Vec<Scalar> = (0..3)
+        .map(|i| {
+            let (j, k) = ((i + 1) % 3, (i + 2) % 3);
+            // (axis x vin)[i] = axis[j] * vin[k] - axis[k] * vin[j]
+            let terms = [(axis[j], k), (-axis[k], j)]
+                .into_iter()
+                .filter(|(coefficient, _)| *coefficient != 0.0)
+                .map(|(coefficient, index)| &scale(coefficient, s) * &vin.at(index))
+                .collect();
+            sum(terms)
+        })
+        .collect();
+    Vector::from_scalars("vector", components.iter().collect())
+}
+
+fn zero_vector() -> Vector {
+    Vector!([0.0, 0.0, 0.0])
+}
+
+#[allow(dead_code)]
+impl JointType {
+    /// Rotation of the joint, `u u^T + cos(q) (I - u u^T) + sin(q) [u]x` for a
+    /// revolute joint about `u`. Zero terms are left out, so a joint about z
+    /// traces `[[qcos, -qsin, 0], [qsin, qcos, 0], [0, 0, 1]]`.
+    pub fn rotation(&self, qcos: &Scalar, qsin: &Scalar) -> Matrix {
+        let JointType::Revolute(u) = self else {
+            return Matrix::new("matrix", vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]]);
+        };
+        let skew = [[0.0, -u[2], u[1]], [u[2], 0.0, -u[0]], [-u[1], u[0], 0.0]];
+        let entries: Vec<Vec<Scalar>> = (0..3)
+            .map(|i| {
+                (0..3)
+                    .map(|j| {
+                        let outer = u[i] * u[j];
+                        let identity = if i == j { 1.0 } else { 0.0 };
+                        let mut terms = vec![];
+                        if outer != 0.0 {
+                            terms.push(Scalar!(outer));
+                        }
+                        if identity - outer != 0.0 {
+                            terms.push(scale(identity - outer, qcos));
+                        }
+                        if skew[i][j] != 0.0 {
+                            terms.push(scale(skew[i][j], qsin));
+                        }
+                        sum(terms)
+                    })
+                    .collect()
+            })
+            .collect();
+        Matrix::from_scalars("matrix", entries.iter().map(|row| row.iter().collect()).collect())
+    }
+
+    /// Translation of the joint frame in the previous joint frame: the fixed
+    /// placement, moved along the axis for a prismatic joint. `limi_rotation`
+    /// is the fixed rotation of the placement, as a prismatic joint does not turn.
+    pub fn translation(&self, placement: Vector, limi_rotation: &Matrix, qsin: &Scalar) -> Vector {
+        match self {
+            JointType::Prismatic(u) => &placement + &limi_rotation.matmul_vec(&scale_axis(u, qsin)),
+            _ => placement,
+        }
+    }
+
+    /// `jdata.S() * x` as linear and angular parts
+    pub fn motion(&self, x: &Scalar) -> (Vector, Vector) {
+        match self {
+            JointType::Revolute(u) => (zero_vector(), scale_axis(u, x)),
+            JointType::Prismatic(u) => (scale_axis(u, x), zero_vector()),
+            JointType::Fixed => (zero_vector(), zero_vector()),
+        }
+    }
+
+    /// `jdata.S() * a + (v ^ jdata.S() * velocity)`, the acceleration a joint adds
+    /// to a body moving with spatial velocity `v`, as linear and angular parts
+    pub fn acceleration(&self, a: &Scalar, velocity: &Scalar, v_linear: &Vector, v_angular: &Vector) -> (Vector, Vector) {
+        let (linear, angular, u) = match self {
+            // v ^ (S w) = (-w u x v.linear, -w u x v.angular)
+            JointType::Revolute(u) => {
+                let minus_velocity = -velocity;
+                (
+                    axis_cross(u, &minus_velocity, v_linear),
+                    axis_cross(u, &minus_velocity, v_angular),
+                    u,
+                )
+            }
+            // v ^ (S w) = (-w u x v.angular, 0)
+            JointType::Prismatic(u) => (axis_cross(u, &-velocity, v_angular), zero_vector(), u),
+            JointType::Fixed => return (zero_vector(), zero_vector()),
+        };
+
+        // S * a only touches the components along the axis
+        let add_along_axis = |bias: &Vector| {
+            let components: Vec<Scalar> = (0..3)
+                .map(|k| match u[k] {
+                    0.0 => bias.at(k),
+                    _ => &scale(u[k], a) + &bias.at(k),
+                })
+                .collect();
+            Vector::from_scalars("vector", components.iter().collect())
+        };
+        match self {
+            JointType::Prismatic(_) => (add_along_axis(&linear), angular),
+            _ => (linear, add_along_axis(&angular)),
+        }
+    }
+
+    /// `jdata.S().transpose() * f` for a 6D force
+    pub fn force(&self, f: &Vector) -> Scalar {
+        let (u, offset) = match self {
+            JointType::Revolute(u) => (u, 3),
+            JointType::Prismatic(u) => (u, 0),
+            JointType::Fixed => return Scalar!(0.0),
+        };
+        let terms = (0..3)
+            .filter(|&k| u[k] != 0.0)
+            .map(|k| scale(u[k], &f.at(offset + k)))
+            .collect();
+        sum(terms)
+    }
+
+    /// `m.act(jdata.S())`, the joint's motion subspace moved by the placement `m`,
+    /// as linear and angular parts
+    //res.template segment<3>(LINEAR) = m.translation().cross(m.rotation().col(axis));
+    //res.template segment<3>(ANGULAR) = m.rotation().col(axis);
+    pub fn act(&self, rotation: &Matrix, translation: &Vector) -> (Vector, Vector) {
+        let u = match self {
+            JointType::Revolute(u) | JointType::Prismatic(u) => u,
+            JointType::Fixed => return (zero_vector(), zero_vector()),
+        };
+        // rotation * u, a column of the rotation for a joint about x, y or z
+        let components: Vec<Scalar> = (0..3)
+            .map(|i| {
+                let terms = (0..3)
+                    .filter(|&k| u[k] != 0.0)
+                    .map(|k| scale(u[k], &rotation.at(i, k)))
+                    .collect();
+                sum(terms)
+            })
+            .collect();
+        let rotated = Vector::from_scalars("vector", components.iter().collect());
+
+        match self {
+            JointType::Prismatic(_) => (rotated, zero_vector()),
+            _ => (translation.cross(&rotated), rotated),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use super::frames::{Inertial, Transform, Vec3, matrix_to_rpy, merge_inertials, normalize, rpy_to_matrix};
+use super::model::{BaseModel, JointModel, RobotModel};
+use super::robot_info::JointType;
+
+use anyhow::{Context, Result, bail};
+use roxmltree::{Document, Node};
+use std::collections::HashMap;
+use std::path::Path;
+
+struct Joint<'a> {
+    name: &'a str,
+    kind: &'a str,
+    child: &'a str,
+    origin: Transform,
+    rpy: Vec3,
+    axis: Vec3,
+    limit: Option<(f64, f64)>,
+}
+
+/// Parses a whitespace separated triple such as `xyz="0 0 0.333"`
+fn parse_triple(node: Node, attribute: &str, default: Vec3) -> Result<Vec3> {
+    let Some(text) = node.attribute(attribute) else {
+        return Ok(default);
+    };
+    let values = text
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<Vec<f64>, _>>()
+        .with_context(|| format!("Invalid {}=\"{}\" in <{}>", attribute, text, node.tag_name().name()))?;
+    values
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Expected three values in {}=\"{}\"", attribute, text))
+}
+
+fn parse_number(node: Node, attribute: &str) -> Result<f64> {
+    let text = node
+        .attribute(attribute)
+        .with_context(|| format!("<{}> has no {} attribute", node.tag_name().name(), attribute))?;
+    text.trim()
+        .parse()
+        .with_context(|| format!("Invalid {}=\"{}\" in <{}>", attribute, text, node.tag_name().name()))
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(tag))
+}
+
+/// The `xyz` and `rpy` of an `<origin>` child, zero when there is none
+fn parse_origin(node: Node) -> Result<(Vec3, Vec3)> {
+    match child(node, "origin") {
+        Some(origin) => Ok((parse_triple(origin, "xyz", [0.0; 3])?, parse_triple(origin, "rpy", [0.0; 3])?)),
+        None => Ok(([0.0; 3], [0.0; 3])),
+    }
+}
+
+fn parse_inertial(link: Node) -> Result<Option<Inertial>> {
+    let Some(inertial) = child(link, "inertial") else {
+        return Ok(None);
+    };
+    let (com, rpy) = parse_origin(inertial)?;
+    let mass = child(inertial, "mass").context("<inertial> has no <mass>")?;
+    let inertia = child(inertial, "inertia").context("<inertial> has no <inertia>")?;
+    let moment = |attribute| parse_number(inertia, attribute);
+    let (ixx, ixy, ixz) = (moment("ixx")?, moment("ixy")?, moment("ixz")?);
+    let (iyy, iyz, izz) = (moment("iyy")?, moment("iyz")?, moment("izz")?);
+    let inertia = [[ixx, ixy, ixz], [ixy, iyy, iyz], [ixz, iyz, izz]];
+
+    // the inertia is given in the frame of the inertial origin
+    let frame = Transform {
+        rotation: rpy_to_matrix(&rpy),
+        translation: com,
+    };
+    let local = Inertial {
+        mass: parse_number(mass, "value")?,
+        com: [0.0; 3],
+        inertia,
+    };
+    Ok(Some(local.transformed(&frame)))
+}
+
+fn parse_joint<'a>(joint: Node<'a, 'a>) -> Result<Joint<'a>> {
+    let name = joint.attribute("name").context("<joint> has no name")?;
+    let (xyz, rpy) = parse_origin(joint).with_context(|| format!("In joint {}", name))?;
+    let axis = match child(joint, "axis") {
+        Some(axis) => parse_triple(axis, "xyz", [1.0, 0.0, 0.0])?,
+        None => [1.0, 0.0, 0.0],
+    };
+    let limit = match child(joint, "limit") {
+        Some(limit) if limit.has_attribute("lower") || limit.has_attribute("upper") => Some((
+            parse_number(limit, "lower").with_context(|| format!("In joint {}", name))?,
+            parse_number(limit, "upper").with_context(|| format!("In joint {}", name))?,
+        )),
+        _ => None,
+    };
+    Ok(Joint {
+        name,
+        kind: joint.attribute("type").with_context(|| format!("Joint {} has no type", name))?,
+        child: child(joint, "child")
+            .and_then(|child| child.attribute("link"))
+            .with_context(|| format!("Joint {} has no child link", name))?,
+        origin: Transform {
+            rotation: rpy_to_matrix(&rpy),
+            translation: xyz,
+        },
+        rpy,
+        axis,
+        limit,
+    })
+}
+
+/// The motion of a moving joint and its bounds
+fn joint_type(joint: &Joint) -> Result<(JointType, (f64, f64))> {
+    let axis = normalize(&joint.axis).with_context(|| format!("Joint {} has a zero axis", joint.name))?;
+    match (joint.kind, joint.limit) {
+        ("continuous", _) => Ok((JointType::Revolute(axis), (-std::f64::consts::PI, std::f64::consts::PI))),
+        ("revolute", Some(limit)) => Ok((JointType::Revolute(axis), limit)),
+        ("prismatic", Some(limit)) => Ok((JointType::Prismatic(axis), limit)),
+        ("revolute", None) => bail!("Revolute joint {} has no limit", joint.name),
+        _ => bail!("Prismatic joint {} has no limit", joint.name),
+    }
+}
+
+struct Tree<'a> {
+    joints: Vec<Joint<'a>>,
+    links: HashMap<&'a str, Option<Inertial>>,
+    /// Joints by parent link
+    children: HashMap<&'a str, Vec<usize>>,
+}
+
+impl<'a> Tree<'a> {
+    /// Collects the inertials of `link` and the links fixed to it, in the frame
+    /// `placement` of `link`, and returns the moving joints below them with
+    /// their placements
+    fn rigid_body(
+        &self,
+        link: &str,
+        placement: Transform,
+        inertials: &mut Vec<Inertial>,
+    ) -> Result<Vec<(&Joint<'a>, Transform)>> {
+        if let Some(Some(inertial)) = self.links.get(link) {
+            inertials.push(inertial.transformed(&placement));
+        }
+        let mut moving = vec![];
+        for &index in self.children.get(link).into_iter().flatten() {
+            let joint = &self.joints[index];
+            match joint.kind {
+                "fixed" => moving.extend(self.rigid_body(joint.child, placement.compose(&joint.origin), inertials)?),
+                "revolute" | "continuous" | "prismatic" => moving.push((joint, placement)),
+                kind => bail!(
+                    "Joint {} has type {}, only revolute, continuous, prismatic and fixed joints are supported",
+                    joint.name,
+                    kind
+                ),
+            }
+        }
+        Ok(moving)
+    }
+
+    /// Adds the moving `joint` placed at `placement` in its parent's frame to
+    /// `model`, followed depth-first by the joints below it
+    fn add_subtree(&self, joint: &Joint, placement: Transform, parent: Option<usize>, model: &mut RobotModel) -> Result<()> {
+        let (joint_type, bounds) = joint_type(joint)?;
+
+        let mut inertials = vec![];
+        let children = self.rigid_body(joint.child, Transform::IDENTITY, &mut inertials)?;
+        let body = merge_inertials(&inertials);
+
+        // keep the literal angles of the file unless fixed joints were folded in
+        let origin = placement.compose(&joint.origin);
+        let rpy = if placement == Transform::IDENTITY {
+            joint.rpy
+        } else {
+            matrix_to_rpy(&origin.rotation)
+        };
+        let index = model.joints.len();
+        model.joints.push(JointModel {
+            name: joint.name.to_string(),
+            parent,
+            translation: origin.translation,
+            rpy,
+            joint_type,
+            bounds,
+            mass: body.mass,
+            lever: body.com,
+            inertia: body.inertia,
+        });
+
+        for (child, placement) in children {
+            self.add_subtree(child, placement, Some(index), model)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a manipulator from the text of a URDF file.
+///
+/// Revolute, continuous and prismatic joints become the joints of the model,
+/// depth-first from the root link so that every joint follows its parent;
+/// continuous joints are bounded by `[-pi, pi]`.
+/// Fixed joints are folded into the placement of the moving joints below
+/// them, and the links they attach into the mass properties of the moving
+/// link. The root link and the links fixed to it make up the base, whose mass
+/// properties only a floating base uses.
+#[allow(dead_code)]
+pub fn parse_urdf(text: &str) -> Result<RobotModel> {
+    let document = Document::parse(text).context("Failed to parse URDF")?;
+    let robot = document.root_element();
+    if !robot.has_tag_name("robot") {
+        bail!("Expected a <robot> element, found <{}>", robot.tag_name().name());
+    }
+
+    let mut links = HashMap::new();
+    for link in robot.children().filter(|node| node.has_tag_name("link")) {
+        let name = link.attribute("name").context("<link> has no name")?;
+        let inertial = parse_inertial(link).with_context(|| format!("In link {}", name))?;
+        links.insert(name, inertial);
+    }
+
+    let mut joints = Vec::new();
+    let mut children: HashMap<&str, Vec<usize>> = HashMap::new();
+    for node in robot.children().filter(|node| node.has_tag_name("joint")) {
+        let joint = parse_joint(node)?;
+        let parent = child(node, "parent")
+            .and_then(|parent| parent.attribute("link"))
+            .with_context(|| format!("Joint {} has no parent link", joint.name))?;
+        for link in [parent, joint.child] {
+            if !links.contains_key(link) {
+                bail!("Joint {} refers to unknown link {}", joint.name, link);
+            }
+        }
+        children.entry(parent).or_default().push(joints.len());
+        joints.push(joint);
+    }
+
+    let roots: Vec<&str> = robot
+        .children()
+        .filter(|node| node.has_tag_name("link"))
+        .filter_map(|link| link.attribute("name"))
+        .filter(|link| joints.iter().all(|joint| joint.child != *link))
+        .collect();
+    let [root] = roots[..] else {
+        bail!("Expected one root link, found {:?}", roots);
+    };
+
+    let tree = Tree { joints, links, children };
+    let mut base = vec![];
+    let moving = tree.rigid_body(root, Transform::IDENTITY, &mut base)?;
+    let base = merge_inertials(&base);
+    let mut model = RobotModel {
+        name: robot.attribute("name").unwrap_or_default().to_string(),
+        base: BaseModel {
+            mass: base.mass,
+            lever: base.com,
+            inertia: base.inertia,
+        },
+        joints: vec![],
+    };
+    for (joint, placement) in moving {
+        tree.add_subtree(joint, placement, None, &mut model)?;
+    }
+
+    if model.joints.is_empty() {
+        bail!("URDF has no revolute, continuous or prismatic joints");
+    }
+    Ok(model)
+}
+
+/// Reads a manipulator from a URDF file, see [`parse_urdf`]
+#[allow(dead_code)]
+pub fn load_urdf<P: AsRef<Path>>(path: P) -> Result<RobotModel> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read URDF {}", path.display()))?;
+    parse_urdf(&text).with_context(|| format!("Failed to load URDF {}", path.display()))
+}
@@ -2,8 +2,20 @@ use anyhow::Result;
 use log::info;
 
 use crate::{
-    analysis::daisy::{write_errors_to_file, write_precisions_to_file, write_ranges_to_file},
-    codegen::{c::generate_c, c_with_conversion::generate_c_with_conversion, daisy_dsl::generate_daisy_dsl}, 
+    analysis::{
+        daisy::{
+            DaisyErrors, DaisyPrecisions, DaisyRanges, write_errors_to_file, write_precisions_to_file,
+            write_ranges_to_file,
+        },
+        attribution::{attribute_errors, write_attribution_to_file},
+        monte_carlo::{monte_carlo, write_monte_carlo_to_file},
+        report::AnalysisReport,
+        result::{AnalysisResult, GeneratedFiles, StageTimings},
+    },
+    codegen::{
+        c::generate_c, c_with_conversion::generate_c_with_conversion, daisy_dsl::generate_daisy_dsl,
+        test_harness::generate_c_test_harness,
+    },
     config::Config,
     ir::{
         program::{Program, get_program, report_analysis_errors, report_analysis_ranges, report_worst_values, update_program_outputs},
@@ -11,27 +23,131 @@ use crate::{
     }, logger::setup_logger
 };
 
+/// Range and error analysis methods Daisy is run with
+pub const DAISY_RANGE_METHOD: &str = "interval";
+pub const DAISY_ERROR_METHOD: &str = "interval";
+
+/// Everything Daisy reports for one kernel, keyed by unrolled variable name
+#[derive(Debug, Clone)]
+pub struct DaisyResults {
+    pub ranges: DaisyRanges,
+    pub errors: DaisyErrors,
+    pub precisions: DaisyPrecisions,
+}
+
 /// This one runs error analysis and returns its results
 /// The range results should be slightly different than analysis_range_only,
 /// Because in this version we also care about roundoff errors
-pub fn analysis(config: Config) -> Result<Program> {
+///
+/// The result is also written to `analysis_data/{kernel_name}/analysis_result.json`,
+/// next to a full report of all variables in `report.json` and `report.html`.
+pub fn analysis(config: Config) -> Result<AnalysisResult> {
     let log_file_path = match setup_logger() {
         Ok(path) => path,
         Err(e) => anyhow::bail!("Failed to set up logger: {}", e),
     };
     let start_time = std::time::Instant::now();
+    let mut timings = StageTimings::default();
 
+    config.check_kernel_name()?;
     info!("Current precision: {:#?}", config.precision);
+    info!("Kernel name: {}", config.kernel_name);
 
     // Perform unrolling here
+    let stage_start = std::time::Instant::now();
     let mut program = unroll_ir(&get_program());
+    timings.unroll = stage_start.elapsed();
 
     println!("Starting worst case analysis...");
+    let stage_start = std::time::Instant::now();
+    let results = run_daisy(&program, &config)?;
+    timings.daisy = stage_start.elapsed();
+
+    // after getting results, we can generate C now!
+    let stage_start = std::time::Instant::now();
+    generate_c(&program, &results.precisions, &config)?;
+    generate_c_with_conversion(&program, &results.precisions, &config)?;
+
+    update_program_outputs(
+        &mut program,
+        &results.ranges,
+        &results.errors,
+    );
+
+
+    report_analysis_ranges(&program);
+    report_analysis_errors(&program);
+    report_worst_values(&results.ranges, &program);
+
+    // the harness needs the analysed error bounds of the outputs
+    generate_c_test_harness(
+        &program,
+        &config,
+        &format!("{}_with_conversion.cpp", config.kernel_name),
+    )?;
+    timings.codegen = stage_start.elapsed();
+
+    let output_dir = analysis_data_dir(&config);
+    write_analysis_data(&results, &output_dir)?;
+    AnalysisReport::new(&program, &results, &config)?.write(&output_dir, "")?;
+
+    // how close the observed errors get to the bounds
+    if let Some(settings) = &config.monte_carlo {
+        let report = monte_carlo(&program, &results.precisions, settings)?;
+        write_monte_carlo_to_file(&report, output_dir.join("monte_carlo.txt"))?;
+    }
+
+    // which operations the output errors come from
+    if let Some(top) = config.error_attribution {
+        let attributions = attribute_errors(&program, &results.precisions)?;
+        write_attribution_to_file(&attributions, top, output_dir.join("error_attribution.txt"))?;
+    }
+
+    let duration = start_time.elapsed();
+    timings.total = duration;
+    let result = AnalysisResult::new(program, &results, &config, generated_files(&config), timings);
+    result.write_json(output_dir.join("analysis_result.json"))?;
+
+    println!("Total analysis time: {:?}", duration);
+    println!("Logs are saved in {}", log_file_path.display());
+    println!("Codegen output is saved in {}", config.output_dir.join("codegen/").display());
+    println!("Analysis data is saved in {}", output_dir.display());
+
+    Ok(result)
+}
+
+/// Folder of the analysis data of `config.kernel_name`, so that kernels
+/// analysed into the same output directory keep their own data
+pub fn analysis_data_dir(config: &Config) -> std::path::PathBuf {
+    config.output_dir.join("analysis_data").join(&config.kernel_name)
+}
+
+/// Paths of the files `analysis` writes for `config.kernel_name`
+pub fn generated_files(config: &Config) -> GeneratedFiles {
+    let codegen = config.output_dir.join("codegen");
+    let name = &config.kernel_name;
+    GeneratedFiles {
+        c: Some(codegen.join("C").join(format!("{}.cpp", name))),
+        c_with_conversion: Some(codegen.join("C").join(format!("{}_with_conversion.cpp", name))),
+        test_harness: Some(codegen.join("C").join(format!("test_{}.cpp", name))),
+        daisy: Some(codegen.join("daisy").join(format!("{}.scala", name))),
+        apfixed: Some(codegen.join("apfixed").join(format!("{}.cpp", name))),
+        analysis_data: Some(analysis_data_dir(config)),
+    }
+}
+
+/// Runs Daisy on an unrolled program and parses its results.
+///
+/// The Daisy DSL input is written to `codegen/daisy/{kernel_name}.scala` and
+/// Daisy's own fixed-point code is copied to `codegen/apfixed/{kernel_name}.cpp`.
+/// Daisy always writes its result files to the same place, so the results are
+/// read back before this returns and concurrent runs are not supported.
+pub fn run_daisy(program: &Program, config: &Config) -> Result<DaisyResults> {
     // create folder if not exist
     let folder = &config.output_dir;
     std::fs::create_dir_all(folder).unwrap();
     // TODO: call daisy here
-    match generate_daisy_dsl(&program, &config) {
+    match generate_daisy_dsl(program, config) {
         Ok(_) => (),
         Err(e) => anyhow::bail!("Code generation failed: {}", e),
     }
@@ -57,7 +173,7 @@ pub fn analysis(config: Config) -> Result<Program> {
 
     let scala_file = config.output_dir
         .join("codegen/daisy")
-        .join("codegen.scala");
+        .join(format!("{}.scala", config.kernel_name));
     let scala_file = std::fs::canonicalize(scala_file)?;
 
     // before running, run mkdir daisy_directory + "output"
@@ -73,10 +189,8 @@ pub fn analysis(config: Config) -> Result<Program> {
             "--apfixed",
         ])
         .arg(format!("--precision={}", config.precision))
-        .args([
-            "--rangeMethod=interval",
-            "--errorMethod=interval",
-        ])
+        .arg(format!("--rangeMethod={}", DAISY_RANGE_METHOD))
+        .arg(format!("--errorMethod={}", DAISY_ERROR_METHOD))
         .arg(&scala_file)
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
@@ -87,55 +201,41 @@ pub fn analysis(config: Config) -> Result<Program> {
     }
     info!("Daisy analysis completed successfully");
 
-    let range_results =
+    let ranges =
         crate::analysis::daisy::parse_daisy_ranges(daisy_directory.join("ranges.txt"))?;
-    let error_results =
+    let errors =
         crate::analysis::daisy::parse_daisy_errors(daisy_directory.join("errors.txt"))?;
-    let precision_results =
-        crate::analysis::daisy::parse_daisy_precisions(daisy_directory.join("precisions.txt"), &range_results)?;
-    
-    // after getting results, we can generate C now!
-    generate_c(&program, &precision_results, &config)?;
-    generate_c_with_conversion(&program, &precision_results, &config)?;
-    
-    update_program_outputs(
-        &mut program,
-        &range_results,
-        &error_results,
-    );
-
-
-    report_analysis_ranges(&program);
-    report_analysis_errors(&program);
-    report_worst_values(&range_results, &program);
+    let precisions =
+        crate::analysis::daisy::parse_daisy_precisions(daisy_directory.join("precisions.txt"), &ranges)?;
 
     // Finally, we copy the codegen to our output directory and log the new file path to user
     // from daisy_directory + "output" + scala_file.name() to config.output_dir + scala_file.name()
-    let input_file = daisy_directory.join("output").join("codegen.cpp"); // TODO: make this dynamic
+    // Daisy names its output after the analysed object, which is the kernel name
+    let input_file = daisy_directory.join("output").join(format!("{}.cpp", config.kernel_name));
     let output_dir = config.output_dir.join("codegen/apfixed");
     // generate output directory if not exist
     std::fs::create_dir_all(&output_dir)?;
-    let output_file = output_dir.join("codegen.cpp"); // TODO: make this dynamic
+    let output_file = output_dir.join(format!("{}.cpp", config.kernel_name));
     std::fs::copy(
         input_file,
         &output_file,
     )?;
 
-    let output_dir = config.output_dir.join("analysis_data");
-    std::fs::create_dir_all(&output_dir)?;
-    // Write all ranges and all errors in output directory, too
+    Ok(DaisyResults {
+        ranges,
+        errors,
+        precisions,
+    })
+}
+
+/// Writes all ranges, errors and precisions to `output_dir`
+pub fn write_analysis_data(results: &DaisyResults, output_dir: &std::path::Path) -> Result<()> {
+    std::fs::create_dir_all(output_dir)?;
     let ranges_output_file = output_dir.join("analysis_ranges.txt");
     let errors_output_file = output_dir.join("analysis_errors.txt");
     let precisions_output_file = output_dir.join("analysis_precisions.txt");
-    write_ranges_to_file(&range_results, &ranges_output_file)?;
-    write_errors_to_file(&error_results, &errors_output_file)?;
-    write_precisions_to_file(&precision_results, &precisions_output_file)?;
-    
-    let duration = start_time.elapsed();
-    println!("Total analysis time: {:?}", duration);
-    println!("Logs are saved in {}", log_file_path.display());
-    println!("Codegen output is saved in {}", config.output_dir.join("codegen/").display());
-    println!("Analysis data is saved in {}", output_dir.display());
-
-    Ok(program)
+    write_ranges_to_file(&results.ranges, &ranges_output_file)?;
+    write_errors_to_file(&results.errors, &errors_output_file)?;
+    write_precisions_to_file(&results.precisions, &precisions_output_file)?;
+    Ok(())
 }
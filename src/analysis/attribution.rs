@@ -0,0 +1,417 @@
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use log::info;
+use std::io::Write;
+use std::path::Path;
+
+use crate::{
+    codegen::interface::{output_error_bound, output_fields, scalar_input_range},
+    config::CInterface,
+    ir::{
+        expr::{Expr, Opr, OprBinary, OprUnary},
+        precision::Precision,
+        program::Program,
+    },
+};
+
+/// Closed interval of doubles
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Interval {
+    lo: f64,
+    hi: f64,
+}
+
+impl Interval {
+    fn point(value: f64) -> Self {
+        Self { lo: value, hi: value }
+    }
+
+    fn magnitude(&self) -> f64 {
+        self.lo.abs().max(self.hi.abs())
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Self { lo: self.lo + other.lo, hi: self.hi + other.hi }
+    }
+
+    fn neg(&self) -> Self {
+        Self { lo: -self.hi, hi: -self.lo }
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let products = [self.lo * other.lo, self.lo * other.hi, self.hi * other.lo, self.hi * other.hi];
+        Self {
+            lo: products.iter().cloned().fold(f64::INFINITY, f64::min),
+            hi: products.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+
+    fn recip(&self) -> Option<Self> {
+        if self.lo <= 0.0 && self.hi >= 0.0 {
+            return None;
+        }
+        Some(Self { lo: 1.0 / self.hi, hi: 1.0 / self.lo })
+    }
+}
+
+/// What one unrolled variable adds to the error of one output element
+#[derive(Debug, Clone)]
+pub struct Contribution {
+    /// Unrolled variable, as named in the Daisy results
+    pub variable: String,
+    /// The traced name the variable was unrolled from, e.g. `act_inv_fun`
+    /// for `act_inv_fun_4_2_1`; see [`source_name`]
+    pub source: String,
+    /// `Add`, `Sub`, `Mul`, `Div`, `Neg`, `Assign`, `input` or `constant`
+    pub operation: String,
+    /// Algorithm line the variable was traced at, `None` for inputs
+    pub location: Option<String>,
+    /// Worst-case roundoff of storing the variable in its precision
+    pub roundoff: f64,
+    /// Largest magnitude of the derivative of the output by the variable
+    pub sensitivity: f64,
+    /// `sensitivity * roundoff`
+    pub contribution: f64,
+}
+
+/// Per-operation breakdown of the error of one output element
+#[derive(Debug, Clone)]
+pub struct ErrorAttribution {
+    /// Output element, named like the fields of the generated output struct
+    pub output: String,
+    /// Every variable with a non-zero contribution, largest first
+    pub contributions: Vec<Contribution>,
+    /// Sum of all contributions, the first-order error estimate
+    pub first_order_bound: f64,
+    /// Worst-case error bound from the analysis
+    pub bound: f64,
+}
+
+impl ErrorAttribution {
+    /// The `n` largest contributions
+    pub fn top(&self, n: usize) -> &[Contribution] {
+        &self.contributions[..n.min(self.contributions.len())]
+    }
+
+    /// Contributions summed per source name, largest first
+    pub fn by_source(&self) -> Vec<(String, f64)> {
+        let mut sources: IndexMap<String, f64> = IndexMap::new();
+        for contribution in &self.contributions {
+            *sources.entry(contribution.source.clone()).or_default() += contribution.contribution;
+        }
+        let mut sources: Vec<(String, f64)> = sources.into_iter().collect();
+        sources.sort_by(|a, b| b.1.total_cmp(&a.1));
+        sources
+    }
+
+    /// Share of the first-order estimate caused by `contribution`
+    pub fn fraction(&self, contribution: &Contribution) -> f64 {
+        contribution.contribution / self.first_order_bound
+    }
+
+    /// Prefixes the output with `{kernel}.`, as in the combined library report
+    pub fn prefixed(mut self, kernel: &str) -> Self {
+        self.output = format!("{}.{}", kernel, self.output);
+        self
+    }
+}
+
+/// The traced name an unrolled variable comes from: unique-name prefixes
+/// (`r_12_`) and trailing element and joint indices (`_2_1`) are removed.
+pub fn source_name(variable: &str) -> String {
+    let mut name = variable;
+    if let Some((counter, rest)) = name.strip_prefix("r_").and_then(|rest| rest.split_once('_'))
+        && !counter.is_empty()
+        && counter.chars().all(|c| c.is_ascii_digit())
+        && !rest.is_empty()
+    {
+        name = rest;
+    }
+    while let Some((rest, index)) = name.rsplit_once('_') {
+        if rest.is_empty() || index.is_empty() || !index.chars().all(|c| c.is_ascii_digit()) {
+            break;
+        }
+        name = rest;
+    }
+    name.to_string()
+}
+
+/// One scalar operation of the unrolled program
+enum Operation {
+    Input,
+    Constant(f64),
+    Neg(String),
+    Assign(String),
+    Binary(OprBinary, String, String),
+}
+
+impl Operation {
+    fn label(&self) -> String {
+        match self {
+            Operation::Input => "input".to_string(),
+            Operation::Constant(_) => "constant".to_string(),
+            Operation::Neg(_) => "Neg".to_string(),
+            Operation::Assign(_) => "Assign".to_string(),
+            Operation::Binary(opr_type, _, _) => format!("{:?}", opr_type),
+        }
+    }
+}
+
+fn operations(program: &Program) -> Result<IndexMap<String, Operation>> {
+    let mut operations = IndexMap::new();
+    for id in program.get_inputs().keys() {
+        operations.insert(id.name().clone(), Operation::Input);
+    }
+    for expr in program.get_body() {
+        let Expr::Let { id, opr, .. } = expr;
+        let operation = match opr {
+            Opr::ConstantScalar { value } => Operation::Constant(value.to_f64()),
+            Opr::ConstructScalar { id } => Operation::Assign(id.name().clone()),
+            Opr::Unary { opr1, opr_type: OprUnary::Neg } => Operation::Neg(opr1.name().clone()),
+            Opr::Unary { opr1, opr_type: OprUnary::Assign | OprUnary::AssignNoOpt } => {
+                Operation::Assign(opr1.name().clone())
+            }
+            Opr::Binary {
+                opr1,
+                opr2,
+                opr_type: opr_type @ (OprBinary::Add | OprBinary::Sub | OprBinary::Mul | OprBinary::Div),
+            } => Operation::Binary(opr_type.clone(), opr1.name().clone(), opr2.name().clone()),
+            _ => anyhow::bail!("Error attribution expects an unrolled program, {} is not a scalar operation", id.name()),
+        };
+        operations.insert(id.name().clone(), operation);
+    }
+    Ok(operations)
+}
+
+/// Ranges of all variables by interval arithmetic from the declared input ranges
+fn ranges(program: &Program, operations: &IndexMap<String, Operation>) -> Result<IndexMap<String, Interval>> {
+    let mut ranges: IndexMap<String, Interval> = IndexMap::new();
+    for (name, operation) in operations {
+        let range = |name: &String| ranges.get(name).copied().with_context(|| format!("Variable {} used before definition", name));
+        let value = match operation {
+            Operation::Input => {
+                let (lo, hi) = scalar_input_range(program, name)?;
+                Interval { lo: lo.to_f64(), hi: hi.to_f64() }
+            }
+            Operation::Constant(value) => Interval::point(*value),
+            Operation::Neg(opr) => range(opr)?.neg(),
+            Operation::Assign(opr) => range(opr)?,
+            Operation::Binary(opr_type, opr1, opr2) => {
+                let (a, b) = (range(opr1)?, range(opr2)?);
+                match opr_type {
+                    OprBinary::Add => a.add(&b),
+                    OprBinary::Sub => a.sub(&b),
+                    OprBinary::Mul => a.mul(&b),
+                    _ => a.mul(&b.recip().with_context(|| format!("Divisor {} of {} may be zero", opr2, name))?),
+                }
+            }
+        };
+        ranges.insert(name.clone(), value);
+    }
+    Ok(ranges)
+}
+
+fn precision_of<'a>(precisions: &'a IndexMap<String, Precision>, name: &str) -> Result<&'a Precision> {
+    precisions
+        .get(name)
+        .ok_or_else(|| anyhow::anyhow!("Precision for variable {} not found in precisions map", name))
+}
+
+/// Worst-case roundoff of computing `name` in its precision.
+///
+/// Fixed-point values lose up to one ulp whenever they are shifted right into
+/// the variable, as the generated code does for the product of a
+/// multiplication and for each operand of an addition; inputs and quotients
+/// are truncated, and constants lose up to half an ulp. Floats lose a unit
+/// roundoff relative to the largest magnitude of the variable.
+fn roundoff(
+    name: &str,
+    operation: &Operation,
+    range: &Interval,
+    precisions: &IndexMap<String, Precision>,
+) -> Result<f64> {
+    let bits_of = |name: &str| -> Result<Option<i32>> {
+        Ok(match precision_of(precisions, name)? {
+            Precision::Fixed { fractional_bits, .. } => Some(*fractional_bits),
+            _ => None,
+        })
+    };
+
+    let unit = match precision_of(precisions, name)? {
+        Precision::Float32 => 2f64.powi(-24),
+        Precision::Float64 => 2f64.powi(-53),
+        Precision::Fixed { fractional_bits, .. } => {
+            if *fractional_bits < 0 {
+                anyhow::bail!("Fractional bits of {} are not decided yet", name);
+            }
+            let ulp = 2f64.powi(-fractional_bits);
+            // one ulp for every value shifted right into the variable
+            let truncated = |bits: Option<i32>| match bits {
+                Some(bits) if bits <= *fractional_bits => 0.0,
+                _ => ulp,
+            };
+            return Ok(match operation {
+                Operation::Input | Operation::Binary(OprBinary::Div, _, _) => ulp,
+                Operation::Constant(value) => {
+                    let scaled = value * 2f64.powi(*fractional_bits);
+                    if scaled == scaled.round() { 0.0 } else { ulp / 2.0 }
+                }
+                Operation::Neg(opr) | Operation::Assign(opr) => truncated(bits_of(opr)?),
+                Operation::Binary(OprBinary::Mul, opr1, opr2) => match (bits_of(opr1)?, bits_of(opr2)?) {
+                    (Some(f1), Some(f2)) => truncated(Some(f1 + f2)),
+                    _ => ulp,
+                },
+                // the generated code aligns both operands before adding them
+                Operation::Binary(_, opr1, opr2) => truncated(bits_of(opr1)?) + truncated(bits_of(opr2)?),
+            });
+        }
+    };
+    // negations and widening copies are exact in floating point
+    let exact = match operation {
+        Operation::Neg(_) => true,
+        Operation::Assign(opr) => !matches!((precision_of(precisions, opr)?, precision_of(precisions, name)?), (Precision::Float64, Precision::Float32)),
+        _ => false,
+    };
+    Ok(if exact { 0.0 } else { unit * range.magnitude() })
+}
+
+/// Attributes the error of every output element to the operations of the
+/// program that cause it.
+///
+/// Each variable `v` contributes `max |d output / d v| * roundoff(v)` to the
+/// first-order error of an output, so a contribution is what the error would
+/// shrink by if that one operation were exact. Derivatives are bounded over
+/// the whole input box by propagating intervals backwards through the program
+/// (reverse-mode differentiation), and roundoffs follow from the variable's
+/// precision and range. The sum of the contributions is a first-order estimate
+/// that neglects products of errors, so it can be slightly below Daisy's bound.
+///
+/// `program` must be the unrolled program; after `update_program_outputs` the
+/// attributions also carry the analysed bounds.
+pub fn attribute_errors(program: &Program, precisions: &IndexMap<String, Precision>) -> Result<Vec<ErrorAttribution>> {
+    info!("Attributing output errors to operations...");
+    let operations = operations(program)?;
+    let locations: IndexMap<&String, String> = program
+        .get_body()
+        .iter()
+        .filter_map(|expr| {
+            let Expr::Let { id, .. } = expr;
+            expr.location().map(|location| (id.name(), location))
+        })
+        .collect();
+    let ranges = ranges(program, &operations)?;
+    let roundoffs = operations
+        .iter()
+        .map(|(name, operation)| Ok((name.clone(), roundoff(name, operation, &ranges[name], precisions)?)))
+        .collect::<Result<IndexMap<String, f64>>>()?;
+
+    let mut attributions = vec![];
+    for (output, info) in output_fields(program, CInterface::Scalar) {
+        let mut adjoints: IndexMap<String, Interval> = IndexMap::new();
+        adjoints.insert(info.id.name().clone(), Interval::point(1.0));
+
+        let mut contributions = vec![];
+        for (name, operation) in operations.iter().rev() {
+            let Some(adjoint) = adjoints.get(name).copied() else {
+                continue;
+            };
+            let mut propagate = |opr: &String, derivative: Interval| {
+                let entry = adjoints.entry(opr.clone()).or_insert(Interval::point(0.0));
+                *entry = entry.add(&adjoint.mul(&derivative));
+            };
+            match operation {
+                Operation::Input | Operation::Constant(_) => (),
+                Operation::Neg(opr) => propagate(opr, Interval::point(-1.0)),
+                Operation::Assign(opr) => propagate(opr, Interval::point(1.0)),
+                Operation::Binary(opr_type, opr1, opr2) => match opr_type {
+                    OprBinary::Add => {
+                        propagate(opr1, Interval::point(1.0));
+                        propagate(opr2, Interval::point(1.0));
+                    }
+                    OprBinary::Sub => {
+                        propagate(opr1, Interval::point(1.0));
+                        propagate(opr2, Interval::point(-1.0));
+                    }
+                    OprBinary::Mul => {
+                        propagate(opr1, ranges[opr2]);
+                        propagate(opr2, ranges[opr1]);
+                    }
+                    _ => {
+                        // d(a/b)/da = 1/b, d(a/b)/db = -(a/b)/b; ranges checked b != 0
+                        let recip = ranges[opr2].recip().unwrap_or(Interval::point(0.0));
+                        propagate(opr1, recip);
+                        propagate(opr2, ranges[name].neg().mul(&recip));
+                    }
+                },
+            }
+
+            let sensitivity = adjoint.magnitude();
+            let contribution = sensitivity * roundoffs[name];
+            if contribution > 0.0 {
+                contributions.push(Contribution {
+                    variable: name.clone(),
+                    source: source_name(name),
+                    operation: operation.label(),
+                    location: locations.get(name).cloned(),
+                    roundoff: roundoffs[name],
+                    sensitivity,
+                    contribution,
+                });
+            }
+        }
+
+        contributions.sort_by(|a, b| b.contribution.total_cmp(&a.contribution));
+        attributions.push(ErrorAttribution {
+            output,
+            first_order_bound: contributions.iter().map(|contribution| contribution.contribution).sum(),
+            contributions,
+            bound: output_error_bound(&info),
+        });
+    }
+    Ok(attributions)
+}
+
+/// Writes the `top` largest contributions per output, then the same per source name
+pub fn write_attribution_to_file<P: AsRef<Path>>(attributions: &[ErrorAttribution], top: usize, path: P) -> Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    writeln!(file, "# variable (source, operation): contribution = sensitivity * roundoff, share of first-order error at location")?;
+    for attribution in attributions {
+        writeln!(
+            file,
+            "{}: first-order {:e}, bound {:e}",
+            attribution.output, attribution.first_order_bound, attribution.bound
+        )?;
+        for contribution in attribution.top(top) {
+            write!(
+                file,
+                "  {} ({}, {}): {:e} = {:e} * {:e}, {:.1}%",
+                contribution.variable,
+                contribution.source,
+                contribution.operation,
+                contribution.contribution,
+                contribution.sensitivity,
+                contribution.roundoff,
+                attribution.fraction(contribution) * 100.0
+            )?;
+            match &contribution.location {
+                Some(location) => writeln!(file, " at {}", location)?,
+                None => writeln!(file)?,
+            }
+        }
+        writeln!(file, "  by source:")?;
+        for (source, contribution) in attribution.by_source().iter().take(top) {
+            writeln!(
+                file,
+                "    {}: {:e}, {:.1}%",
+                source,
+                contribution,
+                contribution / attribution.first_order_bound * 100.0
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,244 @@
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Analysed range and error bound of one output element
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutputBounds {
+    pub range: (f64, f64),
+    pub error: f64,
+}
+
+/// One output element of two analysis runs; `None` where a run lacks it
+#[derive(Debug, Clone)]
+pub struct OutputChange {
+    pub name: String,
+    pub old: Option<OutputBounds>,
+    pub new: Option<OutputBounds>,
+}
+
+impl OutputChange {
+    /// Relative change of the error bound, `(new - old) / old`; infinite when
+    /// a zero bound became positive
+    pub fn relative_change(&self) -> Option<f64> {
+        let (old, new) = (self.old?.error, self.new?.error);
+        if old == new {
+            Some(0.0)
+        } else if old == 0.0 {
+            Some(f64::INFINITY)
+        } else {
+            Some((new - old) / old)
+        }
+    }
+}
+
+/// Output elements of two analysis runs, matched by name
+#[derive(Debug, Clone)]
+pub struct AnalysisDiff {
+    /// Elements of the old run in order, then those only in the new run
+    pub changes: Vec<OutputChange>,
+    /// Largest relative growth of an error bound that is not a regression
+    pub threshold: f64,
+}
+
+impl AnalysisDiff {
+    /// Elements whose error bound grew by more than the threshold
+    pub fn regressions(&self) -> Vec<&OutputChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.relative_change().is_some_and(|relative| relative > self.threshold))
+            .collect()
+    }
+
+    pub fn has_regressions(&self) -> bool {
+        !self.regressions().is_empty()
+    }
+}
+
+fn format_range(bounds: Option<OutputBounds>) -> String {
+    bounds.map_or_else(|| "-".to_string(), |bounds| format!("[{:.6e}, {:.6e}]", bounds.range.0, bounds.range.1))
+}
+
+fn format_error(bounds: Option<OutputBounds>) -> String {
+    bounds.map_or_else(|| "-".to_string(), |bounds| format!("{:.6e}", bounds.error))
+}
+
+impl fmt::Display for AnalysisDiff {
+    /// Side-by-side table of ranges and errors, regressions marked with `!`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = ["output", "old range", "new range", "old error", "new error", "change"];
+        let rows: Vec<[String; 6]> = self
+            .changes
+            .iter()
+            .map(|change| {
+                let relative = match (change.old, change.new, change.relative_change()) {
+                    (None, _, _) => "added".to_string(),
+                    (_, None, _) => "removed".to_string(),
+                    (_, _, Some(relative)) if relative > self.threshold => format!("{:+.2}% !", relative * 100.0),
+                    (_, _, Some(relative)) => format!("{:+.2}%", relative * 100.0),
+                    _ => unreachable!(),
+                };
+                [
+                    change.name.clone(),
+                    format_range(change.old),
+                    format_range(change.new),
+                    format_error(change.old),
+                    format_error(change.new),
+                    relative,
+                ]
+            })
+            .collect();
+
+        let mut widths = header.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        let header = header.map(str::to_string);
+        for row in std::iter::once(&header).chain(&rows) {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            writeln!(f, "{}", cells.join("  ").trim_end())?;
+        }
+
+        let regressions = self.regressions().len();
+        if regressions > 0 {
+            writeln!(
+                f,
+                "{} of {} outputs regressed by more than {}%",
+                regressions,
+                self.changes.len(),
+                self.threshold * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The JSON file to read for `path`: the file itself, or for a directory the
+/// analysis result in it, in its `analysis_data` folder or in the one kernel
+/// folder of that
+fn result_file(path: &Path) -> Result<PathBuf> {
+    if !path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+    let analysis_data = path.join("analysis_data");
+    let direct = [path.join("analysis_result.json"), analysis_data.join("analysis_result.json")];
+    if let Some(file) = direct.into_iter().find(|file| file.exists()) {
+        return Ok(file);
+    }
+
+    let mut kernels: Vec<PathBuf> = [path, analysis_data.as_path()]
+        .into_iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join("analysis_result.json"))
+        .filter(|file| file.exists())
+        .collect();
+    kernels.sort();
+    match kernels.len() {
+        0 => anyhow::bail!("No analysis_result.json found in {}", path.display()),
+        1 => Ok(kernels.remove(0)),
+        _ => {
+            let names: Vec<String> = kernels
+                .iter()
+                .filter_map(|file| file.parent()?.file_name()?.to_str().map(String::from))
+                .collect();
+            anyhow::bail!(
+                "Several kernels were analysed into {}: {}; pass the folder of one of them",
+                path.display(),
+                names.join(", ")
+            )
+        }
+    }
+}
+
+fn parse_range(value: &Value) -> Option<(f64, f64)> {
+    Some((value.get(0)?.as_f64()?, value.get(1)?.as_f64()?))
+}
+
+/// Output elements of one serialized [`crate::AnalysisResult`], named `{prefix}{element}`
+fn result_bounds(result: &Value, prefix: &str, bounds: &mut IndexMap<String, OutputBounds>) -> Result<()> {
+    let outputs = result["outputs"].as_object().context("Analysis result has no outputs")?;
+    for output in outputs.values() {
+        let elements = output["elements"].as_array().context("Output has no elements")?;
+        for element in elements {
+            let name = element["name"].as_str().context("Output element has no name")?;
+            let range = parse_range(&element["range"]).with_context(|| format!("Output {} has no range", name))?;
+            let error = element["error"].as_f64().with_context(|| format!("Output {} has no error", name))?;
+            bounds.insert(format!("{}{}", prefix, name), OutputBounds { range, error });
+        }
+    }
+    Ok(())
+}
+
+/// Loads the output bounds of an analysis run.
+///
+/// `path` is an output, `analysis_data` or kernel directory, an `analysis_result.json`
+/// written by [`crate::analysis`] or [`crate::analysis_library`], or a
+/// `report.json`. Elements are named like the fields of the scalar C
+/// interface; elements of a library are prefixed with `{kernel}.`.
+pub fn load_output_bounds(path: &Path) -> Result<IndexMap<String, OutputBounds>> {
+    let file = result_file(path)?;
+    let text = std::fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read analysis result {}", file.display()))?;
+    let json: Value = serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse analysis result {}", file.display()))?;
+
+    let mut bounds = IndexMap::new();
+    match &json {
+        Value::Array(results) => {
+            for result in results {
+                let kernel = result["kernel_name"].as_str().context("Analysis result has no kernel name")?;
+                result_bounds(result, &format!("{}.", kernel), &mut bounds)?;
+            }
+        }
+        // a report lists every output element with its range and error
+        Value::Object(report) if report.contains_key("program_hash") => {
+            let outputs = json["outputs"].as_array().context("Report has no outputs")?;
+            for output in outputs {
+                let name = output["output"].as_str().context("Report output has no name")?;
+                let (Some(range), Some(error)) = (parse_range(&output["range"]), output["error"].as_f64()) else {
+                    anyhow::bail!("Output {} was not analysed in {}", name, file.display());
+                };
+                bounds.insert(name.to_string(), OutputBounds { range, error });
+            }
+        }
+        _ => result_bounds(&json, "", &mut bounds)?,
+    }
+    Ok(bounds)
+}
+
+/// Compares the output bounds of two analysis runs, e.g. before and after a
+/// change to an algorithm or robot model.
+///
+/// Outputs are matched by name, see [`load_output_bounds`] for the accepted
+/// paths. An output regresses when its error bound grows by more than
+/// `threshold`, relative to the old bound: `0.01` allows 1% growth.
+pub fn diff_analyses(old: &Path, new: &Path, threshold: f64) -> Result<AnalysisDiff> {
+    let old = load_output_bounds(old)?;
+    let mut new = load_output_bounds(new)?;
+
+    let mut changes: Vec<OutputChange> = old
+        .into_iter()
+        .map(|(name, bounds)| OutputChange {
+            new: new.shift_remove(&name),
+            old: Some(bounds),
+            name,
+        })
+        .collect();
+    changes.extend(new.into_iter().map(|(name, bounds)| OutputChange {
+        name,
+        old: None,
+        new: Some(bounds),
+    }));
+
+    Ok(AnalysisDiff { changes, threshold })
+}
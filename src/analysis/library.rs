@@ -0,0 +1,228 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use log::info;
+
+use crate::{
+    analysis::{
+        analysis::{DaisyResults, analysis_data_dir, generated_files, run_daisy, write_analysis_data},
+        attribution::{attribute_errors, write_attribution_to_file},
+        monte_carlo::{MonteCarloReport, monte_carlo, write_monte_carlo_to_file},
+        report::AnalysisReport,
+        result::{AnalysisResult, StageTimings},
+    },
+    codegen::{
+        library::{LibraryKernel, check_library_names, generate_c_library},
+        test_harness::generate_c_test_harness,
+    },
+    config::Config,
+    ir::{
+        program::{Program, report_analysis_errors, report_analysis_ranges, report_worst_values, take_program, update_program_outputs},
+        unroll::unroll_ir,
+    },
+    logger::setup_logger,
+};
+
+/// A traced program that becomes one entry point of a generated library.
+#[derive(Debug, Clone)]
+pub struct Kernel {
+    pub name: String,
+    pub program: Program,
+}
+
+impl Kernel {
+    /// Takes the program traced so far, leaving an empty one to trace the next kernel into.
+    pub fn take(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            program: take_program(),
+        }
+    }
+}
+
+/// Analyses several kernels and generates them into one C library.
+///
+/// Every kernel goes through Daisy on its own (with its own name, so the
+/// Daisy and apfixed files do not overwrite each other); the C code is then
+/// written to `codegen/C/{config.kernel_name}.cpp` with one entry point per
+/// kernel and constants shared between them, next to one `test_{kernel}.cpp`
+/// harness per kernel. The analysis data of all kernels is combined into one
+/// report, with variables prefixed by their kernel name; the JSON and HTML
+/// reports are written per kernel as `{kernel}_report.json`/`.html`.
+///
+/// The analysis data is written to `analysis_data/{config.kernel_name}`. Returns
+/// one result per kernel, in input order; they are also written there to
+/// `analysis_result.json` as a JSON array.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use roboprec::*;
+///
+/// let x = add_input_scalar("x", (Real::from_f64(0.0), Real::from_f64(1.0)), 0.5);
+/// let mut square = &x * &x;
+/// register_scalar_output(&mut square, "square");
+/// let square_kernel = Kernel::take("square");
+///
+/// let x = add_input_scalar("x", (Real::from_f64(0.0), Real::from_f64(1.0)), 0.5);
+/// let mut cube = &(&x * &x) * &x;
+/// register_scalar_output(&mut cube, "cube");
+/// let cube_kernel = Kernel::take("cube");
+///
+/// let config = Config {
+///     kernel_name: String::from("powers"),
+///     ..Default::default()
+/// };
+/// analysis_library(vec![square_kernel, cube_kernel], config).unwrap();
+/// ```
+pub fn analysis_library(kernels: Vec<Kernel>, config: Config) -> Result<Vec<AnalysisResult>> {
+    let log_file_path = match setup_logger() {
+        Ok(path) => path,
+        Err(e) => anyhow::bail!("Failed to set up logger: {}", e),
+    };
+    let start_time = std::time::Instant::now();
+
+    let names: Vec<&str> = kernels.iter().map(|kernel| kernel.name.as_str()).collect();
+    check_library_names(&names, &config)?;
+    info!("Current precision: {:#?}", config.precision);
+    info!("Library name: {}", config.kernel_name);
+
+    let mut programs = vec![];
+    let mut all_results = vec![];
+    let mut all_timings = vec![];
+    for kernel in &kernels {
+        println!("Starting worst case analysis of {}...", kernel.name);
+        info!("Analysing kernel {}", kernel.name);
+        let kernel_config = Config {
+            kernel_name: kernel.name.clone(),
+            ..config.clone()
+        };
+        let mut timings = StageTimings::default();
+        let stage_start = std::time::Instant::now();
+        let mut program = unroll_ir(&kernel.program);
+        timings.unroll = stage_start.elapsed();
+        let stage_start = std::time::Instant::now();
+        let results = run_daisy(&program, &kernel_config)?;
+        timings.daisy = stage_start.elapsed();
+
+        update_program_outputs(&mut program, &results.ranges, &results.errors);
+        report_analysis_ranges(&program);
+        report_analysis_errors(&program);
+        report_worst_values(&results.ranges, &program);
+
+        programs.push(program);
+        all_results.push(results);
+        all_timings.push(timings);
+    }
+
+    let stage_start = std::time::Instant::now();
+
+    let library_kernels: Vec<LibraryKernel> = kernels
+        .iter()
+        .zip(programs.iter().zip(all_results.iter()))
+        .map(|(kernel, (program, results))| LibraryKernel {
+            name: &kernel.name,
+            program,
+            precisions: &results.precisions,
+        })
+        .collect();
+    generate_c_library(&library_kernels, &config)?;
+    for (kernel, program) in kernels.iter().zip(programs.iter()) {
+        let kernel_config = Config {
+            kernel_name: kernel.name.clone(),
+            ..config.clone()
+        };
+        generate_c_test_harness(
+            program,
+            &kernel_config,
+            &format!("{}_with_conversion.cpp", config.kernel_name),
+        )?;
+    }
+    let codegen_time = stage_start.elapsed();
+
+    let output_dir = analysis_data_dir(&config);
+    let combined = combine_results(&names, &all_results);
+    write_analysis_data(&combined, &output_dir)?;
+    for (kernel, (program, results)) in kernels.iter().zip(programs.iter().zip(all_results.iter())) {
+        let kernel_config = Config {
+            kernel_name: kernel.name.clone(),
+            ..config.clone()
+        };
+        AnalysisReport::new(program, results, &kernel_config)?.write(&output_dir, &format!("{}_", kernel.name))?;
+    }
+
+    if let Some(settings) = &config.monte_carlo {
+        let mut combined: Option<MonteCarloReport> = None;
+        for (kernel, (program, results)) in kernels.iter().zip(programs.iter().zip(all_results.iter())) {
+            let report = monte_carlo(program, &results.precisions, settings)?.prefixed(&kernel.name);
+            match &mut combined {
+                Some(combined) => combined.outputs.extend(report.outputs),
+                None => combined = Some(report),
+            }
+        }
+        if let Some(report) = combined {
+            write_monte_carlo_to_file(&report, output_dir.join("monte_carlo.txt"))?;
+        }
+    }
+
+    if let Some(top) = config.error_attribution {
+        let mut attributions = vec![];
+        for (kernel, (program, results)) in kernels.iter().zip(programs.iter().zip(all_results.iter())) {
+            let kernel_attributions = attribute_errors(program, &results.precisions)?;
+            attributions.extend(kernel_attributions.into_iter().map(|attribution| attribution.prefixed(&kernel.name)));
+        }
+        write_attribution_to_file(&attributions, top, output_dir.join("error_attribution.txt"))?;
+    }
+
+    let duration = start_time.elapsed();
+    let library_files = generated_files(&config);
+    let mut analysis_results = vec![];
+    for ((kernel, program), (results, mut timings)) in kernels
+        .iter()
+        .zip(programs)
+        .zip(all_results.iter().zip(all_timings))
+    {
+        let kernel_config = Config {
+            kernel_name: kernel.name.clone(),
+            ..config.clone()
+        };
+        // the C code and the analysis data live with the library, everything
+        // else is per kernel
+        let mut files = generated_files(&kernel_config);
+        files.c = library_files.c.clone();
+        files.c_with_conversion = library_files.c_with_conversion.clone();
+        files.analysis_data = library_files.analysis_data.clone();
+        timings.codegen = codegen_time;
+        timings.total = duration;
+        analysis_results.push(AnalysisResult::new(program, results, &kernel_config, files, timings));
+    }
+    let json = serde_json::to_string_pretty(&analysis_results)?;
+    std::fs::write(output_dir.join("analysis_result.json"), json)?;
+
+    println!("Total analysis time: {:?}", duration);
+    println!("Logs are saved in {}", log_file_path.display());
+    println!("Codegen output is saved in {}", config.output_dir.join("codegen/").display());
+    println!("Analysis data is saved in {}", output_dir.display());
+
+    Ok(analysis_results)
+}
+
+/// Merges per-kernel results into one report, naming variables `{kernel}.{variable}`
+pub fn combine_results(kernel_names: &[&str], results: &[DaisyResults]) -> DaisyResults {
+    fn prefixed<T: Clone>(kernel: &str, map: &IndexMap<String, T>, combined: &mut IndexMap<String, T>) {
+        for (name, value) in map {
+            combined.insert(format!("{}.{}", kernel, name), value.clone());
+        }
+    }
+
+    let mut combined = DaisyResults {
+        ranges: IndexMap::new(),
+        errors: IndexMap::new(),
+        precisions: IndexMap::new(),
+    };
+    for (kernel, result) in kernel_names.iter().zip(results) {
+        prefixed(kernel, &result.ranges, &mut combined.ranges);
+        prefixed(kernel, &result.errors, &mut combined.errors);
+        prefixed(kernel, &result.precisions, &mut combined.precisions);
+    }
+    combined
+}
@@ -1,3 +1,11 @@
 pub mod analysis;
 pub mod real;
 pub mod daisy;
+pub mod library;
+pub mod validate;
+pub mod monte_carlo;
+pub mod worst_case;
+pub mod result;
+pub mod report;
+pub mod diff;
+pub mod attribution;
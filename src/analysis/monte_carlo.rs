@@ -0,0 +1,199 @@
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use log::info;
+use std::io::Write;
+use std::path::Path;
+
+use crate::{
+    analysis::{real::Real, validate::SplitMix64},
+    codegen::{
+        interface::{output_error_bound, output_fields, scalar_input_range},
+        simulator::simulate_c,
+    },
+    config::{CInterface, MonteCarlo, Sampling},
+    ir::{
+        interpreter::{Exact, Value, interpret},
+        precision::Precision,
+        program::Program,
+    },
+};
+
+/// Observed error distribution of one output element next to its analysed bound
+#[derive(Debug, Clone)]
+pub struct OutputStatistics {
+    /// Output element, named like the fields of the generated output struct
+    pub name: String,
+    pub max_error: f64,
+    pub mean_error: f64,
+    pub p50_error: f64,
+    pub p90_error: f64,
+    pub p99_error: f64,
+    /// Worst-case error bound from the analysis
+    pub bound: f64,
+}
+
+impl OutputStatistics {
+    /// How many times larger the bound is than the largest observed error
+    pub fn tightness(&self) -> f64 {
+        self.bound / self.max_error
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MonteCarloReport {
+    pub samples: usize,
+    pub sampling: Sampling,
+    pub outputs: Vec<OutputStatistics>,
+}
+
+impl MonteCarloReport {
+    /// Prefixes every output with `{kernel}.`, as in the combined library report
+    pub fn prefixed(mut self, kernel: &str) -> Self {
+        for output in &mut self.outputs {
+            output.name = format!("{}.{}", kernel, output.name);
+        }
+        self
+    }
+}
+
+/// Draws the input samples, one value per unrolled scalar input
+fn sample_inputs(program: &Program, settings: &MonteCarlo) -> Result<Vec<IndexMap<String, Value<Real>>>> {
+    let mut ranges = vec![];
+    for id in program.get_inputs().keys() {
+        let (lo, hi) = scalar_input_range(program, id.name())?;
+        ranges.push((id.name().clone(), lo, hi));
+    }
+
+    let mut rng = SplitMix64::new(settings.seed);
+    let all_corners = ranges.len() < 64 && (1u64 << ranges.len()) <= settings.samples as u64;
+    let samples = match settings.sampling {
+        Sampling::Corners if all_corners => 1usize << ranges.len(),
+        _ => settings.samples,
+    };
+
+    let mut inputs = vec![];
+    for sample in 0..samples {
+        let mut values = IndexMap::new();
+        for (i, (name, lo, hi)) in ranges.iter().enumerate() {
+            let value = match settings.sampling {
+                Sampling::Uniform => Real::from_f64(rng.uniform(lo.to_f64(), hi.to_f64())),
+                Sampling::Corners => {
+                    // corner number `sample` picks the end of input i with its bit i
+                    let high = if all_corners { (sample >> i) & 1 == 1 } else { rng.next_u64() & 1 == 1 };
+                    if high { hi.clone() } else { lo.clone() }
+                }
+            };
+            values.insert(name.clone(), Value::Scalar(value));
+        }
+        inputs.push(values);
+    }
+    Ok(inputs)
+}
+
+/// Nearest-rank percentile of sorted errors
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Estimates how large the errors of the generated code actually get.
+///
+/// Every sample is evaluated in exact rational arithmetic and with the
+/// bit-accurate simulation of the generated C code for `precisions`. The
+/// observed errors are summarised per output element next to the bound the
+/// analysis proved. `program` must be the unrolled program after
+/// `update_program_outputs`, so its outputs carry the analysed errors.
+pub fn monte_carlo(
+    program: &Program,
+    precisions: &IndexMap<String, Precision>,
+    settings: &MonteCarlo,
+) -> Result<MonteCarloReport> {
+    info!(
+        "Running Monte Carlo error estimation with {} {:?} samples...",
+        settings.samples, settings.sampling
+    );
+    let fields = output_fields(program, CInterface::Scalar);
+    if fields.is_empty() {
+        anyhow::bail!("Program has no outputs, nothing to estimate");
+    }
+
+    let samples = sample_inputs(program, settings)?;
+    let mut errors = vec![Vec::with_capacity(samples.len()); fields.len()];
+    for inputs in &samples {
+        let exact = interpret(program, inputs, &Exact)?;
+        let simulated = simulate_c(program, precisions, inputs)?;
+        let exact = exact.values().flat_map(|value| value.elements());
+        let simulated = simulated.values().flat_map(|value| value.elements());
+        for (i, (exact, simulated)) in exact.zip(simulated).enumerate() {
+            let error = if simulated.is_finite() {
+                (&Real::from_f64(*simulated) - exact).abs().to_f64()
+            } else {
+                f64::INFINITY
+            };
+            errors[i].push(error);
+        }
+    }
+
+    let outputs = fields
+        .iter()
+        .zip(errors)
+        .map(|((name, output), mut errors)| {
+            errors.sort_by(|a, b| a.total_cmp(b));
+            let mean_error = errors.iter().sum::<f64>() / errors.len().max(1) as f64;
+            OutputStatistics {
+                name: name.clone(),
+                max_error: errors.last().copied().unwrap_or(0.0),
+                mean_error,
+                p50_error: percentile(&errors, 50.0),
+                p90_error: percentile(&errors, 90.0),
+                p99_error: percentile(&errors, 99.0),
+                bound: output_error_bound(output),
+            }
+        })
+        .collect();
+
+    Ok(MonteCarloReport {
+        samples: samples.len(),
+        sampling: settings.sampling,
+        outputs,
+    })
+}
+
+/// Writes one line per output: observed error statistics, the bound and how
+/// many times larger the bound is than the largest observed error
+pub fn write_monte_carlo_to_file<P: AsRef<Path>>(report: &MonteCarloReport, path: P) -> Result<()> {
+    let mut file = std::fs::File::create(&path).with_context(|| {
+        format!(
+            "Failed to create Monte Carlo output file at {}",
+            path.as_ref().display()
+        )
+    })?;
+
+    writeln!(file, "# {} {:?} samples", report.samples, report.sampling)?;
+    writeln!(file, "# output: max mean p50 p90 p99 bound bound/max")?;
+    for output in &report.outputs {
+        writeln!(
+            file,
+            "{}: {:e} {:e} {:e} {:e} {:e} {:e} {:.3}",
+            output.name,
+            output.max_error,
+            output.mean_error,
+            output.p50_error,
+            output.p90_error,
+            output.p99_error,
+            output.bound,
+            output.tightness()
+        )
+        .with_context(|| {
+            format!(
+                "Failed to write to Monte Carlo output file at {}",
+                path.as_ref().display()
+            )
+        })?;
+    }
+
+    Ok(())
+}
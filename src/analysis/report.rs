@@ -0,0 +1,347 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::{
+    analysis::analysis::{DAISY_ERROR_METHOD, DAISY_RANGE_METHOD, DaisyResults},
+    codegen::interface::{output_fields, scalar_input_range},
+    config::{CInterface, Config},
+    ir::{
+        expr::{Expr, Opr},
+        precision::Precision,
+        program::{Program, ProgramInput},
+    },
+};
+
+/// How Daisy was run
+#[derive(Debug, Clone, Serialize)]
+pub struct DaisySettings {
+    pub precision: Precision,
+    pub range_method: String,
+    pub error_method: String,
+}
+
+/// One unrolled input with its declared range and what Daisy found for it
+#[derive(Debug, Clone, Serialize)]
+pub struct InputReport {
+    pub name: String,
+    pub declared_range: (f64, f64),
+    pub range: Option<(f64, f64)>,
+    pub error: Option<f64>,
+    pub precision: Option<Precision>,
+}
+
+/// One unrolled variable of the body
+#[derive(Debug, Clone, Serialize)]
+pub struct VariableReport {
+    pub name: String,
+    /// The output element the variable is returned as, e.g. `tau_1`
+    pub output: Option<String>,
+    pub range: Option<(f64, f64)>,
+    pub error: Option<f64>,
+    pub precision: Option<Precision>,
+    /// Algorithm line the variable was traced at, `file:line:column`
+    pub location: Option<String>,
+}
+
+/// Complete analysis report of one kernel, written as JSON and HTML
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisReport {
+    pub kernel_name: String,
+    /// Hash of the unrolled program, to tell which trace a report belongs to
+    pub program_hash: String,
+    pub daisy: DaisySettings,
+    pub inputs: Vec<InputReport>,
+    pub outputs: Vec<VariableReport>,
+    pub intermediates: Vec<VariableReport>,
+}
+
+/// FNV-1a, stable across runs and platforms
+fn fnv1a(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Hash of the inputs, body and outputs of `program`.
+///
+/// Identifiers enter by name only, their numeric ids depend on what else was
+/// traced in the same process.
+pub fn program_hash(program: &Program) -> String {
+    let mut text = String::new();
+    for (id, input) in program.get_inputs() {
+        let ranges: Vec<String> = match input {
+            ProgramInput::Scalar { info } => vec![format!("{:?}", info.range)],
+            ProgramInput::Vector { info } => info.iter().map(|info| format!("{:?}", info.range)).collect(),
+            ProgramInput::Matrix { info } => info.iter().flatten().map(|info| format!("{:?}", info.range)).collect(),
+        };
+        text.push_str(&format!("input {} {}\n", id.name(), ranges.join(" ")));
+    }
+    for expr in program.get_body() {
+        match expr {
+            // locations are left out, so moving code keeps the hash
+            Expr::Let { id, opr, .. } => {
+                let opr = match opr {
+                    Opr::ConstantScalar { value } => format!("constant {:?}", value),
+                    Opr::ConstantVector { value } => format!("constant {:?}", value),
+                    Opr::ConstantMatrix { value } => format!("constant {:?}", value),
+                    Opr::Unary { opr1, opr_type } => format!("{:?} {}", opr_type, opr1.name()),
+                    Opr::Binary {
+                        opr1,
+                        opr2,
+                        opr_type,
+                    } => format!("{:?} {} {}", opr_type, opr1.name(), opr2.name()),
+                    Opr::ConstructScalar { id } => format!("construct {}", id.name()),
+                    Opr::ConstructVector { ids } => {
+                        let names: Vec<&str> = ids.iter().map(|id| id.name().as_str()).collect();
+                        format!("construct {}", names.join(" "))
+                    }
+                    Opr::ConstructMatrix { ids } => {
+                        let rows: Vec<String> = ids
+                            .iter()
+                            .map(|row| row.iter().map(|id| id.name().as_str()).collect::<Vec<_>>().join(" "))
+                            .collect();
+                        format!("construct {}", rows.join("; "))
+                    }
+                };
+                text.push_str(&format!("let {} = {}\n", id.name(), opr));
+            }
+        }
+    }
+    for id in program.get_outputs().keys() {
+        text.push_str(&format!("output {}\n", id.name()));
+    }
+    format!("{:016x}", fnv1a(&text))
+}
+
+impl AnalysisReport {
+    /// Collects the report of an unrolled program from its Daisy results
+    pub fn new(program: &Program, results: &DaisyResults, config: &Config) -> Result<Self> {
+        let range = |name: &str| results.ranges.get(name).map(|range| (range.lower, range.upper));
+        let error = |name: &str| results.errors.get(name).copied();
+        let precision = |name: &str| results.precisions.get(name).cloned();
+
+        let mut inputs = vec![];
+        for id in program.get_inputs().keys() {
+            let (lo, hi) = scalar_input_range(program, id.name())?;
+            inputs.push(InputReport {
+                name: id.name().clone(),
+                declared_range: (lo.to_f64(), hi.to_f64()),
+                range: range(id.name()),
+                error: error(id.name()),
+                precision: precision(id.name()),
+            });
+        }
+
+        let fields = output_fields(program, CInterface::Scalar);
+        let mut outputs = vec![];
+        let mut intermediates = vec![];
+        for expr in program.get_body() {
+            match expr {
+                Expr::Let { id, .. } => {
+                    let name = id.name();
+                    let location = expr.location();
+                    let output = fields
+                        .iter()
+                        .find(|(_, output)| output.id.name() == name)
+                        .map(|(field, _)| field.clone());
+                    let report = VariableReport {
+                        name: name.clone(),
+                        output: output.clone(),
+                        range: range(name),
+                        error: error(name),
+                        precision: precision(name),
+                        location,
+                    };
+                    match output {
+                        Some(_) => outputs.push(report),
+                        None => intermediates.push(report),
+                    }
+                }
+            }
+        }
+        // in the order of the output struct
+        outputs.sort_by_key(|report| fields.iter().position(|(field, _)| Some(field) == report.output.as_ref()));
+
+        Ok(Self {
+            kernel_name: config.kernel_name.clone(),
+            program_hash: program_hash(program),
+            daisy: DaisySettings {
+                precision: config.precision.clone(),
+                range_method: DAISY_RANGE_METHOD.to_string(),
+                error_method: DAISY_ERROR_METHOD.to_string(),
+            },
+            inputs,
+            outputs,
+            intermediates,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize analysis report")
+    }
+
+    /// A self-contained HTML page: sortable tables for inputs, outputs and
+    /// intermediates, and an error bar per output on a log scale
+    pub fn to_html(&self) -> String {
+        let errors: Vec<f64> = self
+            .outputs
+            .iter()
+            .filter_map(|output| output.error)
+            .filter(|error| *error > 0.0)
+            .collect();
+        let log_max = errors.iter().cloned().fold(f64::NEG_INFINITY, f64::max).log10();
+        let log_min = errors.iter().cloned().fold(f64::INFINITY, f64::min).log10();
+        let bar = |error: Option<f64>| -> String {
+            let width = match error {
+                Some(error) if error > 0.0 && log_max > log_min => {
+                    5.0 + 95.0 * (error.log10() - log_min) / (log_max - log_min)
+                }
+                Some(error) if error > 0.0 => 100.0,
+                _ => 0.0,
+            };
+            format!("<div class=\"bar\" style=\"width: {:.1}%\"></div>", width)
+        };
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>RoboPrec report: {}</title>\n", escape(&self.kernel_name)));
+        html.push_str(STYLE);
+        html.push_str("</head>\n<body>\n");
+        html.push_str(&format!("<h1>{}</h1>\n", escape(&self.kernel_name)));
+        html.push_str("<table class=\"summary\">\n");
+        html.push_str(&format!("<tr><th>Program hash</th><td>{}</td></tr>\n", self.program_hash));
+        html.push_str(&format!("<tr><th>Precision</th><td>{}</td></tr>\n", self.daisy.precision));
+        html.push_str(&format!(
+            "<tr><th>Daisy</th><td>range method {}, error method {}</td></tr>\n",
+            escape(&self.daisy.range_method),
+            escape(&self.daisy.error_method)
+        ));
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Outputs</h2>\n<p>Error bars use a log scale over all output errors.</p>\n");
+        html.push_str("<table class=\"sortable\">\n<thead><tr><th>Output</th><th>Variable</th><th>Lower</th><th>Upper</th><th>Error</th><th>Precision</th><th>Error bar</th><th>Location</th></tr></thead>\n<tbody>\n");
+        for output in &self.outputs {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td>{}{}<td>{}</td><td class=\"bar-cell\">{}</td><td>{}</td></tr>\n",
+                escape(output.output.as_deref().unwrap_or("")),
+                escape(&output.name),
+                range_cells(output.range),
+                number_cell(output.error),
+                precision_text(&output.precision),
+                bar(output.error),
+                escape(output.location.as_deref().unwrap_or(""))
+            ));
+        }
+        html.push_str("</tbody>\n</table>\n");
+
+        html.push_str("<h2>Inputs</h2>\n");
+        html.push_str("<table class=\"sortable\">\n<thead><tr><th>Input</th><th>Declared lower</th><th>Declared upper</th><th>Error</th><th>Precision</th></tr></thead>\n<tbody>\n");
+        for input in &self.inputs {
+            html.push_str(&format!(
+                "<tr><td>{}</td>{}{}<td>{}</td></tr>\n",
+                escape(&input.name),
+                range_cells(Some(input.declared_range)),
+                number_cell(input.error),
+                precision_text(&input.precision)
+            ));
+        }
+        html.push_str("</tbody>\n</table>\n");
+
+        html.push_str("<h2>Intermediates</h2>\n");
+        html.push_str("<table class=\"sortable\">\n<thead><tr><th>Variable</th><th>Lower</th><th>Upper</th><th>Error</th><th>Precision</th><th>Location</th></tr></thead>\n<tbody>\n");
+        for variable in &self.intermediates {
+            html.push_str(&format!(
+                "<tr><td>{}</td>{}{}<td>{}</td><td>{}</td></tr>\n",
+                escape(&variable.name),
+                range_cells(variable.range),
+                number_cell(variable.error),
+                precision_text(&variable.precision),
+                escape(variable.location.as_deref().unwrap_or(""))
+            ));
+        }
+        html.push_str("</tbody>\n</table>\n");
+
+        html.push_str(SCRIPT);
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// Writes `{prefix}report.json` and `{prefix}report.html` to `output_dir`
+    pub fn write(&self, output_dir: &Path, prefix: &str) -> Result<()> {
+        std::fs::create_dir_all(output_dir)?;
+        let json_file = output_dir.join(format!("{}report.json", prefix));
+        let html_file = output_dir.join(format!("{}report.html", prefix));
+        std::fs::write(&json_file, self.to_json()?)
+            .with_context(|| format!("Failed to write report to {}", json_file.display()))?;
+        std::fs::write(&html_file, self.to_html())
+            .with_context(|| format!("Failed to write report to {}", html_file.display()))?;
+        Ok(())
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// A numeric cell; `data-value` keeps the full value for sorting
+fn number_cell(value: Option<f64>) -> String {
+    match value {
+        Some(value) => format!("<td data-value=\"{:e}\">{:.6e}</td>", value, value),
+        None => "<td></td>".to_string(),
+    }
+}
+
+fn range_cells(range: Option<(f64, f64)>) -> String {
+    match range {
+        Some((lower, upper)) => format!("{}{}", number_cell(Some(lower)), number_cell(Some(upper))),
+        None => "<td></td><td></td>".to_string(),
+    }
+}
+
+fn precision_text(precision: &Option<Precision>) -> String {
+    precision.as_ref().map(|precision| precision.to_string()).unwrap_or_default()
+}
+
+const STYLE: &str = r#"<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.6em; text-align: left; }
+td[data-value] { text-align: right; font-family: monospace; }
+table.sortable th { cursor: pointer; background: #f0f0f0; }
+table.sortable th.asc::after { content: " \25B2"; }
+table.sortable th.desc::after { content: " \25BC"; }
+.bar-cell { width: 12em; }
+.bar { height: 0.8em; background: #d9534f; }
+</style>
+"#;
+
+// clicking a header sorts by that column, numerically where possible
+const SCRIPT: &str = r#"<script>
+document.querySelectorAll("table.sortable th").forEach(function (th) {
+    th.addEventListener("click", function () {
+        var table = th.closest("table");
+        var body = table.tBodies[0];
+        var column = Array.prototype.indexOf.call(th.parentNode.children, th);
+        var ascending = !th.classList.contains("asc");
+        table.querySelectorAll("th").forEach(function (other) { other.classList.remove("asc", "desc"); });
+        th.classList.add(ascending ? "asc" : "desc");
+        var key = function (row) {
+            var cell = row.children[column];
+            var value = cell.getAttribute("data-value");
+            return value === null ? cell.textContent : parseFloat(value);
+        };
+        var rows = Array.prototype.slice.call(body.rows);
+        rows.sort(function (a, b) {
+            var x = key(a), y = key(b);
+            var order = (typeof x === "number" && typeof y === "number") ? x - y : String(x).localeCompare(String(y));
+            return ascending ? order : -order;
+        });
+        rows.forEach(function (row) { body.appendChild(row); });
+    });
+});
+</script>
+"#;
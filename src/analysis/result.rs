@@ -0,0 +1,208 @@
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::{Serialize, Serializer};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::{
+    analysis::analysis::DaisyResults,
+    config::Config,
+    ir::{
+        identifier::Identifier,
+        precision::Precision,
+        program::{Output, Program, ProgramOutput},
+    },
+};
+
+fn seconds<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+/// Analysis result of one unrolled element of an output
+#[derive(Debug, Clone, Serialize)]
+pub struct ElementResult {
+    /// Element as named in the scalar C interface, e.g. `tau_1` or `rotation_0_2`
+    pub name: String,
+    /// Unrolled variable computing the element, as named in the Daisy results
+    pub variable: String,
+    /// Analysed range `(lower, upper)`
+    pub range: (f64, f64),
+    /// Absolute worst-case roundoff error
+    pub error: f64,
+    /// Precision chosen for the variable
+    pub precision: Option<Precision>,
+}
+
+/// Analysis result of one registered output
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputResult {
+    /// `[]` for scalars, `[n]` for vectors and `[rows, cols]` for matrices
+    pub shape: Vec<usize>,
+    /// Elements, matrices in row-major order
+    pub elements: Vec<ElementResult>,
+}
+
+impl OutputResult {
+    /// The largest error over all elements
+    pub fn max_error(&self) -> f64 {
+        self.elements.iter().map(|element| element.error).fold(0.0, f64::max)
+    }
+}
+
+/// Files written by the analysis; `None` where a stage did not run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GeneratedFiles {
+    pub c: Option<PathBuf>,
+    pub c_with_conversion: Option<PathBuf>,
+    pub test_harness: Option<PathBuf>,
+    pub daisy: Option<PathBuf>,
+    pub apfixed: Option<PathBuf>,
+    pub analysis_data: Option<PathBuf>,
+}
+
+/// Wall-clock time of each stage, serialized in seconds
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StageTimings {
+    #[serde(serialize_with = "seconds")]
+    pub unroll: Duration,
+    #[serde(serialize_with = "seconds")]
+    pub daisy: Duration,
+    #[serde(serialize_with = "seconds")]
+    pub codegen: Duration,
+    #[serde(serialize_with = "seconds")]
+    pub total: Duration,
+}
+
+/// Everything one analysis run found and produced.
+///
+/// Outputs are indexed by the names passed to `register_*_output`; single
+/// elements can also be looked up by their unrolled names with
+/// [`AnalysisResult::element`]. Serializes to JSON with
+/// [`AnalysisResult::to_json`].
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisResult {
+    pub kernel_name: String,
+    /// Precision requested in the configuration
+    pub precision: Precision,
+    pub outputs: IndexMap<String, OutputResult>,
+    /// Precision of every unrolled variable
+    pub precisions: IndexMap<String, Precision>,
+    pub files: GeneratedFiles,
+    pub timings: StageTimings,
+    /// The unrolled program, its outputs carrying the analysed ranges and
+    /// errors as expected by [`crate::validate_generated_c`]
+    #[serde(skip)]
+    pub program: Program,
+}
+
+impl AnalysisResult {
+    /// Collects the results of `program`, whose outputs have already been
+    /// updated with the Daisy results.
+    pub fn new(
+        program: Program,
+        results: &DaisyResults,
+        config: &Config,
+        files: GeneratedFiles,
+        timings: StageTimings,
+    ) -> Self {
+        let element = |name: String, output: &Output| ElementResult {
+            name,
+            variable: output.id.name().clone(),
+            range: (output.range.0.to_f64(), output.range.1.to_f64()),
+            error: output.error.0.to_f64().abs().max(output.error.1.to_f64().abs()),
+            precision: results.precisions.get(output.id.name()).cloned(),
+        };
+
+        let mut outputs = IndexMap::new();
+        for (id, output) in program.get_outputs() {
+            let name = id.name();
+            let result = match output {
+                ProgramOutput::Scalar { info } => OutputResult {
+                    shape: vec![],
+                    elements: vec![element(name.clone(), info)],
+                },
+                ProgramOutput::Vector { info } => OutputResult {
+                    shape: vec![info.len()],
+                    elements: info
+                        .iter()
+                        .enumerate()
+                        .map(|(i, info)| element(format!("{}_{}", name, i), info))
+                        .collect(),
+                },
+                ProgramOutput::Matrix { info } => OutputResult {
+                    shape: vec![info.len(), info.first().map_or(0, |row| row.len())],
+                    elements: info
+                        .iter()
+                        .enumerate()
+                        .flat_map(|(i, row)| {
+                            row.iter()
+                                .enumerate()
+                                .map(move |(j, info)| element(format!("{}_{}_{}", name, i, j), info))
+                        })
+                        .collect(),
+                },
+            };
+            outputs.insert(name.clone(), result);
+        }
+
+        Self {
+            kernel_name: config.kernel_name.clone(),
+            precision: config.precision.clone(),
+            outputs,
+            precisions: results.precisions.clone(),
+            files,
+            timings,
+            program,
+        }
+    }
+
+    /// An output element by its interface name (`tau_1`) or unrolled variable name
+    pub fn element(&self, name: &str) -> Option<&ElementResult> {
+        self.outputs
+            .values()
+            .flat_map(|output| output.elements.iter())
+            .find(|element| element.name == name || element.variable == name)
+    }
+
+    /// The output registered as `id`, checked to have `dimensions` dimensions
+    fn registered_output(&self, id: &Identifier, dimensions: usize) -> Result<&OutputResult> {
+        let output = self.outputs.get(id.name()).ok_or_else(|| {
+            anyhow::anyhow!(
+                "{} is not an output of kernel {}, register it before running the analysis",
+                id.name(),
+                self.kernel_name
+            )
+        })?;
+        if output.shape.len() != dimensions {
+            anyhow::bail!("Output {} has shape {:?}", id.name(), output.shape);
+        }
+        Ok(output)
+    }
+
+    pub(crate) fn scalar_output(&self, id: &Identifier) -> Result<&ElementResult> {
+        Ok(&self.registered_output(id, 0)?.elements[0])
+    }
+
+    pub(crate) fn vector_output(&self, id: &Identifier) -> Result<&[ElementResult]> {
+        Ok(&self.registered_output(id, 1)?.elements)
+    }
+
+    /// Rows of the matrix output `id`
+    pub(crate) fn matrix_output(&self, id: &Identifier) -> Result<Vec<&[ElementResult]>> {
+        let output = self.registered_output(id, 2)?;
+        Ok(output.elements.chunks(output.shape[1].max(1)).collect())
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize analysis result")
+    }
+
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(&path, self.to_json()?).with_context(|| {
+            format!(
+                "Failed to write analysis result to {}",
+                path.as_ref().display()
+            )
+        })
+    }
+}
@@ -0,0 +1,307 @@
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use log::info;
+use std::io::Write;
+
+use crate::{
+    analysis::real::Real,
+    codegen::{
+        interface::{call_vars, output_error_bound, output_fields, scalar_input_range},
+        simulator::simulate_c,
+    },
+    config::{CInterface, Config},
+    ir::{
+        interpreter::{Exact, Value, interpret},
+        precision::Precision,
+        program::{Output, Program},
+    },
+};
+
+/// Observed error of one output element next to its analysed bound
+#[derive(Debug, Clone)]
+pub struct OutputValidation {
+    /// Field of the output struct, e.g. `tau_1` or `tau[1]`
+    pub name: String,
+    /// Largest absolute difference to the exact result over all samples
+    pub max_error: f64,
+    /// Error bound from the analysis
+    pub bound: f64,
+}
+
+impl OutputValidation {
+    pub fn within_bound(&self) -> bool {
+        self.max_error <= self.bound
+    }
+}
+
+/// Result of running the generated C code on sampled inputs
+#[derive(Debug, Clone)]
+pub struct ValidationReport {
+    pub samples: usize,
+    pub outputs: Vec<OutputValidation>,
+}
+
+impl ValidationReport {
+    pub fn all_within_bounds(&self) -> bool {
+        self.outputs.iter().all(|output| output.within_bound())
+    }
+
+    /// The output with the smallest margin between bound and observed error
+    pub fn worst(&self) -> Option<&OutputValidation> {
+        self.outputs.iter().min_by(|a, b| {
+            (a.bound - a.max_error)
+                .partial_cmp(&(b.bound - b.max_error))
+                .unwrap_or(std::cmp::Ordering::Less)
+        })
+    }
+}
+
+/// SplitMix64, enough to spread samples over the input ranges reproducibly
+/// without pulling in a random number crate.
+pub(crate) struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform double in `[lo, hi]`
+    pub(crate) fn uniform(&mut self, lo: f64, hi: f64) -> f64 {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        (lo + (hi - lo) * unit).clamp(lo, hi)
+    }
+}
+
+/// Compiles the generated `{kernel_name}_with_conversion.cpp`, runs it on
+/// `samples` random inputs drawn from the declared input ranges and compares
+/// every output with the exact rational evaluation of the unrolled program.
+///
+/// `program` must be the unrolled program of the [`crate::analysis`] result, so
+/// its outputs carry the analysed error bounds, and the C file must already be
+/// in `config.output_dir`. The driver is compiled with `$CC` (default `cc`)
+/// as C++, since the generated code uses brace-initialised returns.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use roboprec::*;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let config = Config::default();
+/// let result = analysis(config.clone())?;
+/// let report = validate_generated_c(&result.program, &config, 1000)?;
+/// assert!(report.all_within_bounds());
+/// # Ok(())
+/// # }
+/// ```
+pub fn validate_generated_c(program: &Program, config: &Config, samples: usize) -> Result<ValidationReport> {
+    info!("Validating generated C code with {} samples...", samples);
+    config.check_kernel_name()?;
+    let func_name = config.kernel_name.as_str();
+    let kernel_file = config
+        .output_dir
+        .join("codegen/C")
+        .join(format!("{}_with_conversion.cpp", func_name));
+    let kernel_file = std::fs::canonicalize(&kernel_file)
+        .with_context(|| format!("Generated C code not found at {}", kernel_file.display()))?;
+
+    let vars = call_vars(program, config.c_interface)?;
+    let fields = output_fields(program, config.c_interface);
+    if fields.is_empty() {
+        anyhow::bail!("Program has no outputs, nothing to validate");
+    }
+
+    // the driver reads one sample per line and prints the outputs of each
+    let mut driver = String::new();
+    driver.push_str("#include <math.h>\n");
+    driver.push_str("#include <stdint.h>\n");
+    driver.push_str("#include <stdio.h>\n");
+    driver.push_str(format!("\n#include \"{}\"\n", kernel_file.display()).as_str());
+    driver.push_str("\nint main(void) {\n");
+    for var in &vars {
+        driver.push_str(format!("    double in_{};\n", var.declarator()).as_str());
+    }
+    driver.push_str("    while (1) {\n");
+    for var in &vars {
+        for (subscript, _) in &var.elements {
+            driver.push_str(
+                format!("        if (scanf(\"%lf\", &in_{}{}) != 1) return 0;\n", var.name, subscript).as_str(),
+            );
+        }
+    }
+    let args: Vec<String> = vars.iter().map(|var| format!("in_{}", var.name)).collect();
+    driver.push_str(
+        format!("        {}_output_t out = {}({});\n", func_name, func_name, args.join(", ")).as_str(),
+    );
+    for (field, _) in &fields {
+        driver.push_str(format!("        printf(\"%.17g \", out.{});\n", field).as_str());
+    }
+    driver.push_str("        printf(\"\\n\");\n    }\n}\n");
+
+    let folder = config.output_dir.join("validation");
+    std::fs::create_dir_all(&folder)?;
+    let driver_file = folder.join(format!("validate_{}.cpp", func_name));
+    let executable = folder.join(format!("validate_{}", func_name));
+    std::fs::write(&driver_file, driver)?;
+
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let compile = std::process::Command::new(&compiler)
+        .args(["-x", "c++", "-O1", "-o"])
+        .arg(&executable)
+        .arg(&driver_file)
+        .arg("-lm")
+        .output()
+        .with_context(|| format!("Failed to run C compiler '{}'", compiler))?;
+    if !compile.status.success() {
+        anyhow::bail!(
+            "Compiling {} failed:\n{}",
+            driver_file.display(),
+            String::from_utf8_lossy(&compile.stderr)
+        );
+    }
+
+    // sample the inputs; the exact evaluation uses the very same doubles
+    let mut rng = SplitMix64::new(0x853c49e6748fea9b);
+    let mut ranges = IndexMap::new();
+    for var in &vars {
+        for (_, element) in &var.elements {
+            let (lo, hi) = scalar_input_range(program, element.name())?;
+            ranges.insert(element.name().clone(), (lo.to_f64(), hi.to_f64()));
+        }
+    }
+    let mut sampled_inputs = vec![];
+    let mut input_text = String::new();
+    for _ in 0..samples {
+        let mut values = IndexMap::new();
+        for (name, (lo, hi)) in &ranges {
+            let value = rng.uniform(*lo, *hi);
+            input_text.push_str(format!("{:e}\n", value).as_str());
+            values.insert(name.clone(), Value::Scalar(Real::from_f64(value)));
+        }
+        sampled_inputs.push(values);
+    }
+    let input_file = folder.join(format!("validate_{}_inputs.txt", func_name));
+    std::fs::File::create(&input_file)?.write_all(input_text.as_bytes())?;
+
+    let run = std::process::Command::new(&executable)
+        .stdin(std::fs::File::open(&input_file)?)
+        .output()
+        .with_context(|| format!("Failed to run {}", executable.display()))?;
+    if !run.status.success() {
+        anyhow::bail!("{} exited with {}", executable.display(), run.status);
+    }
+    let stdout = String::from_utf8_lossy(&run.stdout);
+
+    let mut max_errors = vec![0.0f64; fields.len()];
+    let mut lines = stdout.lines();
+    for inputs in &sampled_inputs {
+        let line = lines
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Generated code produced fewer results than samples"))?;
+        let values: Vec<f64> = line
+            .split_whitespace()
+            .map(|value| value.parse::<f64>())
+            .collect::<std::result::Result<_, _>>()
+            .with_context(|| format!("Unexpected output line '{}'", line))?;
+        if values.len() != fields.len() {
+            anyhow::bail!("Expected {} outputs, got '{}'", fields.len(), line);
+        }
+
+        update_max_errors(program, inputs, &values, &mut max_errors)?;
+    }
+
+    Ok(report(&fields, max_errors, samples))
+}
+
+/// Like [`validate_generated_c`], but runs the program in the bit-accurate
+/// [`crate::codegen::simulator::CSimulator`] instead of compiling the C code,
+/// so no compiler is needed and `precisions` can be tried before generating
+/// any code.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use roboprec::*;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let result = analysis(Config::default())?;
+/// let report = validate_simulated(&result.program, &result.precisions, 10000)?;
+/// assert!(report.all_within_bounds());
+/// # Ok(())
+/// # }
+/// ```
+pub fn validate_simulated(
+    program: &Program,
+    precisions: &IndexMap<String, Precision>,
+    samples: usize,
+) -> Result<ValidationReport> {
+    info!("Validating simulated fixed-point code with {} samples...", samples);
+    let fields = output_fields(program, CInterface::Scalar);
+    if fields.is_empty() {
+        anyhow::bail!("Program has no outputs, nothing to validate");
+    }
+
+    let mut ranges = IndexMap::new();
+    for id in program.get_inputs().keys() {
+        let (lo, hi) = scalar_input_range(program, id.name())?;
+        ranges.insert(id.name().clone(), (lo.to_f64(), hi.to_f64()));
+    }
+
+    let mut rng = SplitMix64::new(0x853c49e6748fea9b);
+    let mut max_errors = vec![0.0f64; fields.len()];
+    for _ in 0..samples {
+        let mut inputs = IndexMap::new();
+        for (name, (lo, hi)) in &ranges {
+            inputs.insert(name.clone(), Value::Scalar(Real::from_f64(rng.uniform(*lo, *hi))));
+        }
+        let simulated = simulate_c(program, precisions, &inputs)?;
+        let values: Vec<f64> = simulated.values().flat_map(|value| value.elements()).copied().collect();
+        update_max_errors(program, &inputs, &values, &mut max_errors)?;
+    }
+
+    Ok(report(&fields, max_errors, samples))
+}
+
+/// Compares one sample of output elements with the exact evaluation
+fn update_max_errors(
+    program: &Program,
+    inputs: &IndexMap<String, Value<Real>>,
+    values: &[f64],
+    max_errors: &mut [f64],
+) -> Result<()> {
+    // output elements in the same order as the output struct fields
+    let exact = interpret(program, inputs, &Exact)?;
+    let expected: Vec<&Real> = exact.values().flat_map(|value| value.elements()).collect();
+    for (i, expected) in expected.into_iter().enumerate() {
+        let error = if values[i].is_finite() {
+            (&Real::from_f64(values[i]) - expected).abs().to_f64()
+        } else {
+            f64::INFINITY
+        };
+        max_errors[i] = max_errors[i].max(error);
+    }
+    Ok(())
+}
+
+fn report(fields: &[(String, Output)], max_errors: Vec<f64>, samples: usize) -> ValidationReport {
+    let outputs = fields
+        .iter()
+        .zip(max_errors)
+        .map(|((field, output), max_error)| OutputValidation {
+            name: field.clone(),
+            max_error,
+            bound: output_error_bound(output),
+        })
+        .collect();
+    ValidationReport { samples, outputs }
+}
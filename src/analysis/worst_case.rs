@@ -0,0 +1,184 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use log::info;
+
+use crate::{
+    analysis::{real::Real, validate::SplitMix64},
+    codegen::{
+        interface::{output_error_bound, output_fields, scalar_input_range},
+        simulator::simulate_c,
+    },
+    config::CInterface,
+    ir::{
+        interpreter::{Exact, Value, interpret},
+        precision::Precision,
+        program::Program,
+    },
+};
+
+/// Budget of [`search_worst_case`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorstCaseSearch {
+    /// Number of starting points: the centre of the input box, then random
+    /// corners and random points inside it, alternating
+    pub restarts: usize,
+    /// Program evaluations per start
+    pub evaluations: usize,
+    pub seed: u64,
+}
+
+impl Default for WorstCaseSearch {
+    fn default() -> Self {
+        Self {
+            restarts: 8,
+            evaluations: 2000,
+            seed: 0x853c49e6748fea9b,
+        }
+    }
+}
+
+/// Largest roundoff error found for one output element
+#[derive(Debug, Clone)]
+pub struct WorstCase {
+    /// Output element, named like the fields of the generated output struct
+    pub output: String,
+    /// The input that produced the error, keyed by unrolled input name
+    pub inputs: IndexMap<String, f64>,
+    pub error: f64,
+    /// Worst-case error bound from the analysis
+    pub bound: f64,
+    /// Number of program evaluations spent
+    pub evaluations: usize,
+}
+
+impl WorstCase {
+    /// Observed error over analysed bound; 1 means the bound is attained
+    pub fn ratio(&self) -> f64 {
+        self.error / self.bound
+    }
+}
+
+/// Error of output element `index` between exact and generated arithmetic at `point`
+fn error_at(
+    program: &Program,
+    precisions: &IndexMap<String, Precision>,
+    names: &[String],
+    point: &[f64],
+    index: usize,
+) -> Result<f64> {
+    let inputs: IndexMap<String, Value<Real>> = names
+        .iter()
+        .zip(point)
+        .map(|(name, value)| (name.clone(), Value::Scalar(Real::from_f64(*value))))
+        .collect();
+    let exact = interpret(program, &inputs, &Exact)?;
+    let simulated = simulate_c(program, precisions, &inputs)?;
+    let exact = exact.values().flat_map(|value| value.elements()).nth(index);
+    let simulated = simulated.values().flat_map(|value| value.elements()).nth(index);
+    match (exact, simulated) {
+        (Some(exact), Some(simulated)) if simulated.is_finite() => {
+            Ok((&Real::from_f64(*simulated) - exact).abs().to_f64())
+        }
+        (Some(_), Some(_)) => Ok(f64::INFINITY),
+        _ => anyhow::bail!("Output element {} was not computed", index),
+    }
+}
+
+/// Searches the input box for the input that maximises the roundoff error of
+/// one output element of the generated code.
+///
+/// Runs a coordinate search from several starting points: every input is
+/// moved up and down by a step, improvements are kept, and the step is halved
+/// once no move helps, down to a millionth of the input range. Errors are
+/// measured between exact rational evaluation and the bit-accurate
+/// simulation of the generated C code for `precisions`, so no external solver
+/// is needed.
+///
+/// `output` names an element like the scalar interface struct fields, e.g.
+/// `tau_1` or `rotation_0_2`. `program` must be the unrolled program after
+/// `update_program_outputs`, so its outputs carry the analysed errors.
+pub fn search_worst_case(
+    program: &Program,
+    precisions: &IndexMap<String, Precision>,
+    output: &str,
+    settings: &WorstCaseSearch,
+) -> Result<WorstCase> {
+    info!("Searching worst-case input for output {}...", output);
+    let fields = output_fields(program, CInterface::Scalar);
+    let index = fields.iter().position(|(name, _)| name == output).ok_or_else(|| {
+        let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+        anyhow::anyhow!("Output {} not found, expected one of {}", output, names.join(", "))
+    })?;
+    let bound = output_error_bound(&fields[index].1);
+
+    let mut names = vec![];
+    let mut ranges = vec![];
+    for id in program.get_inputs().keys() {
+        let (lo, hi) = scalar_input_range(program, id.name())?;
+        names.push(id.name().clone());
+        ranges.push((lo.to_f64(), hi.to_f64()));
+    }
+
+    let mut rng = SplitMix64::new(settings.seed);
+    let mut best_point: Vec<f64> = ranges.iter().map(|(lo, hi)| lo + (hi - lo) / 2.0).collect();
+    let mut best_error = f64::NEG_INFINITY;
+    let mut evaluations = 0;
+
+    for restart in 0..settings.restarts.max(1) {
+        let mut point: Vec<f64> = ranges
+            .iter()
+            .map(|(lo, hi)| match restart {
+                0 => lo + (hi - lo) / 2.0,
+                _ if restart % 2 == 1 => {
+                    if rng.next_u64() & 1 == 0 { *lo } else { *hi }
+                }
+                _ => rng.uniform(*lo, *hi),
+            })
+            .collect();
+        let mut error = error_at(program, precisions, &names, &point, index)?;
+        let mut used = 1;
+
+        let mut step = 0.5;
+        'search: while step > 1e-6 {
+            let mut improved = false;
+            for i in 0..point.len() {
+                let (lo, hi) = ranges[i];
+                for direction in [1.0, -1.0] {
+                    if used >= settings.evaluations {
+                        break 'search;
+                    }
+                    let mut candidate = point.clone();
+                    candidate[i] = (point[i] + direction * step * (hi - lo)).clamp(lo, hi);
+                    if candidate[i] == point[i] {
+                        continue;
+                    }
+                    let candidate_error = error_at(program, precisions, &names, &candidate, index)?;
+                    used += 1;
+                    if candidate_error > error {
+                        point = candidate;
+                        error = candidate_error;
+                        improved = true;
+                        break;
+                    }
+                }
+            }
+            if !improved {
+                step /= 2.0;
+            }
+        }
+
+        evaluations += used;
+        if error > best_error {
+            best_error = error;
+            best_point = point;
+        }
+    }
+
+    Ok(WorstCase {
+        output: output.to_string(),
+        inputs: names.into_iter().zip(best_point).collect(),
+        error: best_error,
+        bound,
+        evaluations,
+    })
+}
@@ -6,7 +6,11 @@ use std::io::Write;
 
 use crate::{
     analysis::real::Real,
-    config::Config,
+    codegen::{
+        constants::ConstantPool,
+        interface::{common_type, input_vars, output_vars},
+    },
+    config::{CInterface, Config},
     ir::{
         expr::{Expr, Opr, OprBinary, OprUnary},
         precision::Precision,
@@ -86,62 +90,53 @@ pub fn generate_c(
     config: &Config,
 ) -> Result<()> {
     info!("Generating C code...");
+    config.check_kernel_name()?;
     // create a string for the file, so we can write to it at once
+    let mut generated_code = String::new();
+
+    // first prints
+    generated_code.push_str("#include <math.h>\n");
+    generated_code.push_str(&emit_c_kernel(program, precisions, config, None)?);
+
+    let folder = config.output_dir.join("codegen/C");
+    std::fs::create_dir_all(&folder).expect("Failed to create codegen directory for C");
+    let filename = folder.join(format!("{}.cpp", config.kernel_name));
+    let mut file = match std::fs::File::create(filename.clone()) {
+        Ok(f) => f,
+        Err(e) => anyhow::bail!("Unable to create file {}: {}", filename.display(), e),
+    };
+
+    file.write_all(generated_code.as_bytes())
+        .expect("Unable to write to file");
+
+    Ok(())
+}
+
+/// Output struct and function of the kernel, named after `config.kernel_name`.
+///
+/// With a constant pool, literals are replaced by references to the pool's
+/// shared constants, which the caller has to declare before this code.
+pub fn emit_c_kernel(
+    program: &Program,
+    precisions: &IndexMap<String, Precision>,
+    config: &Config,
+    mut constants: Option<&mut ConstantPool>,
+) -> Result<String> {
     let inputs = program.get_inputs();
     let body = program.get_body();
     let outputs = program.get_outputs();
-    let func_name = "codegen";
+    let func_name = config.kernel_name.as_str();
 
     let mut generated_code = String::new();
 
-    // first prints
-    generated_code.push_str("#include <math.h>\n");
-
     // first print the function return type
     generated_code.push_str("\ntypedef struct {\n");
-    for (id, output) in outputs {
-        match output {
-            ProgramOutput::Scalar { .. } => {
-                let name = id.name();
-                let precision = precisions.get(&name.clone()).ok_or_else(|| {
-                    anyhow::anyhow!(
-                        "Precision for output variable {} not found in precisions map",
-                        name
-                    )
-                })?;
-                generated_code.push_str(
-                    format!(
-                        "    {} {};\n",
-                        precision_to_type(&precision),
-                        id.name() // in this case we use id.name, in the function body we'll use info.id.name
-                    )
-                    .as_str(),
-                );
-            }
-            ProgramOutput::Vector { info: infos } => {
-                for (i, info) in infos.iter().enumerate() {
-                    let name = info.id.name();
-                    let precision = precisions.get(&name.clone()).ok_or_else(|| {
-                        anyhow::anyhow!(
-                            "Precision for output variable {} not found in precisions map",
-                            name
-                        )
-                    })?;
-                    generated_code.push_str(
-                        format!(
-                            "    {} {}_{};\n",
-                            precision_to_type(&precision),
-                            id.name(),
-                            i
-                        )
-                        .as_str(),
-                    );
-                }
-            }
-            ProgramOutput::Matrix { info: infos } => {
-                for (i, row) in infos.iter().enumerate() {
-                    for (j, info) in row.iter().enumerate() {
-                        let name = info.id.name();
+    match config.c_interface {
+        CInterface::Scalar => {
+            for (id, output) in outputs {
+                match output {
+                    ProgramOutput::Scalar { .. } => {
+                        let name = id.name();
                         let precision = precisions.get(&name.clone()).ok_or_else(|| {
                             anyhow::anyhow!(
                                 "Precision for output variable {} not found in precisions map",
@@ -150,54 +145,140 @@ pub fn generate_c(
                         })?;
                         generated_code.push_str(
                             format!(
-                                "    {} {}_{}_{};\n",
+                                "    {} {};\n",
                                 precision_to_type(&precision),
-                                id.name(),
-                                i,
-                                j
+                                id.name() // in this case we use id.name, in the function body we'll use info.id.name
                             )
                             .as_str(),
                         );
                     }
+                    ProgramOutput::Vector { info: infos } => {
+                        for (i, info) in infos.iter().enumerate() {
+                            let name = info.id.name();
+                            let precision = precisions.get(&name.clone()).ok_or_else(|| {
+                                anyhow::anyhow!(
+                                    "Precision for output variable {} not found in precisions map",
+                                    name
+                                )
+                            })?;
+                            generated_code.push_str(
+                                format!(
+                                    "    {} {}_{};\n",
+                                    precision_to_type(&precision),
+                                    id.name(),
+                                    i
+                                )
+                                .as_str(),
+                            );
+                        }
+                    }
+                    ProgramOutput::Matrix { info: infos } => {
+                        for (i, row) in infos.iter().enumerate() {
+                            for (j, info) in row.iter().enumerate() {
+                                let name = info.id.name();
+                                let precision = precisions.get(&name.clone()).ok_or_else(|| {
+                                    anyhow::anyhow!(
+                                        "Precision for output variable {} not found in precisions map",
+                                        name
+                                    )
+                                })?;
+                                generated_code.push_str(
+                                    format!(
+                                        "    {} {}_{}_{};\n",
+                                        precision_to_type(&precision),
+                                        id.name(),
+                                        i,
+                                        j
+                                    )
+                                    .as_str(),
+                                );
+                            }
+                        }
+                    }
                 }
             }
         }
+        CInterface::Array => {
+            for var in output_vars(program) {
+                let c_type = common_type(&var, precisions, precision_to_type)?;
+                generated_code.push_str(format!("    {} {};\n", c_type, var.declarator()).as_str());
+            }
+        }
     }
     generated_code.push_str(format!("}} {}_output_t;\n", func_name).as_str());
 
     // then print the function signature
     generated_code.push_str(format!("\n{}_output_t {}(\n", func_name, func_name).as_str());
 
-    for (i, (id, input)) in inputs.iter().enumerate() {
-        match input {
-            ProgramInput::Scalar { .. } => {
-                let name = id.name();
-                let precision = precisions.get(&name.clone()).ok_or_else(|| {
-                    anyhow::anyhow!(
-                        "Precision for input variable {} not found in precisions map",
-                        name
-                    )
-                })?;
+    match config.c_interface {
+        CInterface::Scalar => {
+            for (i, (id, input)) in inputs.iter().enumerate() {
+                match input {
+                    ProgramInput::Scalar { .. } => {
+                        let name = id.name();
+                        let precision = precisions.get(&name.clone()).ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "Precision for input variable {} not found in precisions map",
+                                name
+                            )
+                        })?;
+                        generated_code.push_str(
+                            format!("    {} {}", precision_to_type(&precision), id.name()).as_str(),
+                        );
+                        if i != inputs.len() - 1 {
+                            generated_code.push_str(",\n");
+                        }
+                    }
+                    ProgramInput::Vector { .. } | ProgramInput::Matrix { .. } => {
+                        panic!("Vector and Matrix should have been unrolled before codegen")
+                    }
+                }
+            }
+        }
+        CInterface::Array => {
+            let params = input_vars(program)?;
+            for (i, var) in params.iter().enumerate() {
+                let c_type = common_type(var, precisions, precision_to_type)?;
+                // arrays are only read by the kernel
+                let qualifier = if var.dims.is_empty() { "" } else { "const " };
                 generated_code.push_str(
-                    format!("    {} {}", precision_to_type(&precision), id.name()).as_str(),
+                    format!("    {}{} {}", qualifier, c_type, var.declarator()).as_str(),
                 );
-                if i != inputs.len() - 1 {
+                if i != params.len() - 1 {
                     generated_code.push_str(",\n");
                 }
             }
-            ProgramInput::Vector { .. } | ProgramInput::Matrix { .. } => {
-                panic!("Vector and Matrix should have been unrolled before codegen")
-            }
         }
     }
     generated_code.push_str("\n) {\n");
 
-    // now write the body
+    // unpack the input arrays into the scalar names the body refers to
+    if config.c_interface == CInterface::Array {
+        for var in input_vars(program)? {
+            if var.dims.is_empty() {
+                continue;
+            }
+            let c_type = common_type(&var, precisions, precision_to_type)?;
+            for (subscript, element) in &var.elements {
+                generated_code.push_str(
+                    format!("    {} {} = {}{};\n", c_type, element.name(), var.name, subscript)
+                        .as_str(),
+                );
+            }
+        }
+    }
+
+    // now write the body, with the algorithm line each part was traced at
+    let mut last_location = None;
     for expr in body {
+        if let Some(comment) = expr.location_comment("    ", &mut last_location) {
+            generated_code.push_str(&comment);
+        }
         match expr {
             Expr::Let {
                 id: var_id,
                 opr: rhs,
+                ..
             } => {
                 let name = var_id.name();
                 let precision = precisions.get(&name.clone()).ok_or_else(|| {
@@ -207,7 +288,13 @@ pub fn generate_c(
                     )
                 })?;
                 let rhs = match rhs {
-                    Opr::ConstantScalar { value } => value_precision_to_str(value, precision),
+                    Opr::ConstantScalar { value } => {
+                        let literal = value_precision_to_str(value, precision);
+                        match constants.as_deref_mut() {
+                            Some(pool) => pool.name_for(&precision_to_type(precision), literal),
+                            None => literal,
+                        }
+                    }
                     Opr::ConstructScalar { id } => id.name().to_string(),
                     Opr::Unary { opr1, opr_type } => match opr_type {
                         OprUnary::Neg => {
@@ -478,48 +565,52 @@ pub fn generate_c(
         };
     }
 
-    // now, print the return statement
-    // it is a struct containing all the outputs
-    generated_code.push_str("\n    return {\n");
-    for (_, output) in outputs {
-        match output {
-            ProgramOutput::Scalar { info } => {
-                generated_code.push_str(
-                    format!(
-                        "        {},\n",
-                        info.id.name() // in this case we use id.name, in the function body we'll use info.id.name
-                    )
-                    .as_str(),
-                );
-            }
-            ProgramOutput::Vector { info: infos } => {
-                for info in infos.iter() {
-                    generated_code.push_str(format!("        {},\n", info.id.name()).as_str());
+    match config.c_interface {
+        CInterface::Scalar => {
+            // now, print the return statement
+            // it is a struct containing all the outputs
+            generated_code.push_str("\n    return {\n");
+            for (_, output) in outputs {
+                match output {
+                    ProgramOutput::Scalar { info } => {
+                        generated_code.push_str(
+                            format!(
+                                "        {},\n",
+                                info.id.name() // in this case we use id.name, in the function body we'll use info.id.name
+                            )
+                            .as_str(),
+                        );
+                    }
+                    ProgramOutput::Vector { info: infos } => {
+                        for info in infos.iter() {
+                            generated_code.push_str(format!("        {},\n", info.id.name()).as_str());
+                        }
+                    }
+                    ProgramOutput::Matrix { info: infos } => {
+                        for row in infos.iter() {
+                            for info in row.iter() {
+                                generated_code.push_str(format!("        {},\n", info.id.name()).as_str());
+                            }
+                        }
+                    }
                 }
             }
-            ProgramOutput::Matrix { info: infos } => {
-                for row in infos.iter() {
-                    for info in row.iter() {
-                        generated_code.push_str(format!("        {},\n", info.id.name()).as_str());
-                    }
+            generated_code.push_str("    };\n");
+        }
+        CInterface::Array => {
+            generated_code.push_str(format!("\n    {}_output_t _output;\n", func_name).as_str());
+            for var in output_vars(program) {
+                for (subscript, element) in &var.elements {
+                    generated_code.push_str(
+                        format!("    _output.{}{} = {};\n", var.name, subscript, element.name()).as_str(),
+                    );
                 }
             }
+            generated_code.push_str("    return _output;\n");
         }
     }
-    generated_code.push_str("    };\n");
 
     generated_code.push_str("}\n");
 
-    let folder = config.output_dir.join("codegen/C");
-    std::fs::create_dir_all(&folder).expect("Failed to create codegen directory for C");
-    let filename = folder.join("codegen.cpp");
-    let mut file = match std::fs::File::create(filename.clone()) {
-        Ok(f) => f,
-        Err(e) => anyhow::bail!("Unable to create file {}: {}", filename.display(), e),
-    };
-
-    file.write_all(generated_code.as_bytes())
-        .expect("Unable to write to file");
-
-    Ok(())
+    Ok(generated_code)
 }
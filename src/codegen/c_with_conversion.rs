@@ -6,7 +6,11 @@ use std::io::Write;
 
 use crate::{
     analysis::real::Real,
-    config::Config,
+    codegen::{
+        constants::ConstantPool,
+        interface::{input_vars, output_vars},
+    },
+    config::{CInterface, Config},
     ir::{
         expr::{Expr, Opr, OprBinary, OprUnary},
         precision::Precision,
@@ -80,64 +84,136 @@ fn value_precision_to_str(value: &Real, precision: &Precision) -> String {
     }
 }
 
+/// Declaration of `name` converted from the double expression `source`.
+fn input_conversion(name: &str, source: &str, precision: &Precision) -> String {
+    match precision {
+        Precision::Fixed {
+            total_bits: _,
+            fractional_bits,
+        } => format!(
+            "    {} {} = ({})({} * (1 << {}));\n",
+            precision_to_type(precision),
+            name,
+            precision_to_type(precision),
+            source,
+            fractional_bits
+        ),
+        Precision::Float32 | Precision::Float64 => format!(
+            "    {} {} = ({}){};\n",
+            precision_to_type(precision),
+            name,
+            precision_to_type(precision),
+            source
+        ),
+    }
+}
+
+/// Expression converting the variable `name` back to double.
+fn output_conversion(name: &str, precision: &Precision) -> String {
+    match precision {
+        Precision::Fixed {
+            total_bits: _,
+            fractional_bits,
+        } => format!("((double){}) / (1 << {})", name, fractional_bits),
+        Precision::Float32 | Precision::Float64 => name.to_string(),
+    }
+}
+
 pub fn generate_c_with_conversion(
     program: &Program,
     precisions: &IndexMap<String, Precision>,
     config: &Config,
 ) -> Result<()> {
     info!("Generating C code...");
+    config.check_kernel_name()?;
     // create a string for the file, so we can write to it at once
+    let mut generated_code = String::new();
+
+    // first prints
+    generated_code.push_str("#include <math.h>\n");
+    generated_code.push_str(&emit_c_with_conversion_kernel(program, precisions, config, None)?);
+
+    let folder = config.output_dir.join("codegen/C");
+    std::fs::create_dir_all(&folder).expect("Failed to create codegen directory for C");
+    let filename = folder.join(format!("{}_with_conversion.cpp", config.kernel_name));
+    let mut file = match std::fs::File::create(filename.clone()) {
+        Ok(f) => f,
+        Err(e) => anyhow::bail!("Unable to create file {}: {}", filename.display(), e),
+    };
+
+    file.write_all(generated_code.as_bytes())
+        .expect("Unable to write to file");
+
+    Ok(())
+}
+
+/// Output struct and function of the kernel with its double interface, named after `config.kernel_name`.
+///
+/// With a constant pool, literals are replaced by references to the pool's
+/// shared constants, which the caller has to declare before this code.
+pub fn emit_c_with_conversion_kernel(
+    program: &Program,
+    precisions: &IndexMap<String, Precision>,
+    config: &Config,
+    mut constants: Option<&mut ConstantPool>,
+) -> Result<String> {
     let inputs = program.get_inputs();
     let body = program.get_body();
     let outputs = program.get_outputs();
-    let func_name = "codegen";
+    let func_name = config.kernel_name.as_str();
 
     let mut generated_code = String::new();
 
-    // first prints
-    generated_code.push_str("#include <math.h>\n");
-
     // first print the function return type
     generated_code.push_str("\ntypedef struct {\n");
-    for (id, output) in outputs {
-        match output {
-            ProgramOutput::Scalar { .. } => {
-                generated_code.push_str(
-                    format!(
-                        "    double {};\n",
-                        id.name() // in this case we use id.name, in the function body we'll use info.id.name
-                    )
-                    .as_str(),
-                );
-            }
-            ProgramOutput::Vector { info: infos } => {
-                for (i, _) in infos.iter().enumerate() {
-                    generated_code.push_str(
-                        format!(
-                            "    double {}_{};\n",
-                            id.name(),
-                            i
-                        )
-                        .as_str(),
-                    );
-                }
-            }
-            ProgramOutput::Matrix { info: infos } => {
-                for (i, row) in infos.iter().enumerate() {
-                    for (j, _) in row.iter().enumerate() {
+    match config.c_interface {
+        CInterface::Scalar => {
+            for (id, output) in outputs {
+                match output {
+                    ProgramOutput::Scalar { .. } => {
                         generated_code.push_str(
                             format!(
-                                "    double {}_{}_{};\n",
-                                id.name(),
-                                i,
-                                j
+                                "    double {};\n",
+                                id.name() // in this case we use id.name, in the function body we'll use info.id.name
                             )
                             .as_str(),
                         );
                     }
+                    ProgramOutput::Vector { info: infos } => {
+                        for (i, _) in infos.iter().enumerate() {
+                            generated_code.push_str(
+                                format!(
+                                    "    double {}_{};\n",
+                                    id.name(),
+                                    i
+                                )
+                                .as_str(),
+                            );
+                        }
+                    }
+                    ProgramOutput::Matrix { info: infos } => {
+                        for (i, row) in infos.iter().enumerate() {
+                            for (j, _) in row.iter().enumerate() {
+                                generated_code.push_str(
+                                    format!(
+                                        "    double {}_{}_{};\n",
+                                        id.name(),
+                                        i,
+                                        j
+                                    )
+                                    .as_str(),
+                                );
+                            }
+                        }
+                    }
                 }
             }
         }
+        CInterface::Array => {
+            for var in output_vars(program) {
+                generated_code.push_str(format!("    double {};\n", var.declarator()).as_str());
+            }
+        }
     }
     generated_code.push_str(format!("}} {}_output_t;\n", func_name).as_str());
 
@@ -145,54 +221,85 @@ pub fn generate_c_with_conversion(
     generated_code.push_str(format!("\n{}_output_t {}(\n", func_name, func_name).as_str());
 
     let mut input_conversion_str = String::new();
-    for (i, (id, input)) in inputs.iter().enumerate() {
-        match input {
-            ProgramInput::Scalar { .. } => {
-                let name = id.name();
-                let precision = precisions.get(&name.clone()).ok_or_else(|| {
-                    anyhow::anyhow!(
-                        "Precision for input variable {} not found in precisions map",
-                        name
-                    )
-                })?;
-                generated_code.push_str(
-                    format!("    double _double_{}", id.name()).as_str(),
-                );
-                if i != inputs.len() - 1 {
-                    generated_code.push_str(",\n");
-                }
-
-                // now add conversion from double to fixed
-                match precision {
-                    Precision::Fixed{ total_bits: _, fractional_bits } => {
-                        input_conversion_str.push_str(
-                            format!(
-                                "    {} {} = ({})(_double_{} * (1 << {}));\n",
-                                precision_to_type(precision),
-                                id.name(),
-                                precision_to_type(precision),
-                                id.name(),
-                                fractional_bits
+    match config.c_interface {
+        CInterface::Scalar => {
+            for (i, (id, input)) in inputs.iter().enumerate() {
+                match input {
+                    ProgramInput::Scalar { .. } => {
+                        let name = id.name();
+                        let precision = precisions.get(&name.clone()).ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "Precision for input variable {} not found in precisions map",
+                                name
                             )
-                            .as_str(),
+                        })?;
+                        generated_code.push_str(
+                            format!("    double _double_{}", id.name()).as_str(),
                         );
+                        if i != inputs.len() - 1 {
+                            generated_code.push_str(",\n");
+                        }
+
+                        // now add conversion from double to fixed
+                        match precision {
+                            Precision::Fixed{ total_bits: _, fractional_bits } => {
+                                input_conversion_str.push_str(
+                                    format!(
+                                        "    {} {} = ({})(_double_{} * (1 << {}));\n",
+                                        precision_to_type(precision),
+                                        id.name(),
+                                        precision_to_type(precision),
+                                        id.name(),
+                                        fractional_bits
+                                    )
+                                    .as_str(),
+                                );
+                            }
+                            Precision::Float32 | Precision::Float64 => {
+                                input_conversion_str.push_str(
+                                    format!(
+                                        "    {} {} = ({})_double_{};\n",
+                                        precision_to_type(precision),
+                                        id.name(),
+                                        precision_to_type(precision),
+                                        id.name()
+                                    )
+                                    .as_str(),
+                                );
+                            }
+                        }
                     }
-                    Precision::Float32 | Precision::Float64 => {
-                        input_conversion_str.push_str(
-                            format!(
-                                "    {} {} = ({})_double_{};\n",
-                                precision_to_type(precision),
-                                id.name(),
-                                precision_to_type(precision),
-                                id.name()
-                            )
-                            .as_str(),
-                        );
+                    ProgramInput::Vector { .. } | ProgramInput::Matrix { .. } => {
+                        panic!("Vector and Matrix should have been unrolled before codegen")
                     }
                 }
             }
-            ProgramInput::Vector { .. } | ProgramInput::Matrix { .. } => {
-                panic!("Vector and Matrix should have been unrolled before codegen")
+        }
+        CInterface::Array => {
+            let params = input_vars(program)?;
+            for (i, var) in params.iter().enumerate() {
+                // arrays are only read by the kernel
+                let qualifier = if var.dims.is_empty() { "" } else { "const " };
+                generated_code.push_str(
+                    format!("    {}double _double_{}", qualifier, var.declarator()).as_str(),
+                );
+                if i != params.len() - 1 {
+                    generated_code.push_str(",\n");
+                }
+
+                for (subscript, element) in &var.elements {
+                    let precision = precisions.get(element.name()).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Precision for input variable {} not found in precisions map",
+                            element.name()
+                        )
+                    })?;
+                    input_conversion_str.push_str(&input_conversion(
+                        element.name(),
+                        &format!("_double_{}{}", var.name, subscript),
+                        precision,
+                    ));
+                }
             }
         }
     }
@@ -200,12 +307,17 @@ pub fn generate_c_with_conversion(
 
     generated_code.push_str(&input_conversion_str);
 
-    // now write the body
+    // now write the body, with the algorithm line each part was traced at
+    let mut last_location = None;
     for expr in body {
+        if let Some(comment) = expr.location_comment("    ", &mut last_location) {
+            generated_code.push_str(&comment);
+        }
         match expr {
             Expr::Let {
                 id: var_id,
                 opr: rhs,
+                ..
             } => {
                 let name = var_id.name();
                 let precision = precisions.get(&name.clone()).ok_or_else(|| {
@@ -215,7 +327,13 @@ pub fn generate_c_with_conversion(
                     )
                 })?;
                 let rhs = match rhs {
-                    Opr::ConstantScalar { value } => value_precision_to_str(value, precision),
+                    Opr::ConstantScalar { value } => {
+                        let literal = value_precision_to_str(value, precision);
+                        match constants.as_deref_mut() {
+                            Some(pool) => pool.name_for(&precision_to_type(precision), literal),
+                            None => literal,
+                        }
+                    }
                     Opr::ConstructScalar { id } => id.name().to_string(),
                     Opr::Unary { opr1, opr_type } => match opr_type {
                         OprUnary::Neg => {
@@ -489,82 +607,12 @@ pub fn generate_c_with_conversion(
     // before the return statement, 
     // now, print the return statement
     // it is a struct containing all the outputs
-    generated_code.push_str("\n    return {\n");
-    for (_, output) in outputs {
-        match output {
-            ProgramOutput::Scalar { info } => {
-                let name = info.id.name();
-                let precision = precisions.get(&name.clone()).ok_or_else(|| {
-                    anyhow::anyhow!(
-                        "Precision for output variable {} not found in precisions map",
-                        name
-                    )
-                })?;
-                match precision {
-                    Precision::Fixed {
-                        total_bits: _,
-                        fractional_bits,
-                    } => {
-                        generated_code.push_str(
-                            format!(
-                                "        ((double){}) / (1 << {}),\n",
-                                info.id.name(), // in this case we use id.name, in the function body we'll use info.id.name
-                                fractional_bits
-                            )
-                            .as_str(),
-                        );
-                    }
-                    Precision::Float32 | Precision::Float64 => {
-                        generated_code.push_str(
-                            format!(
-                                "        {},\n",
-                                info.id.name(), // in this case we use id.name, in the function body we'll use info.id.name
-                            )
-                            .as_str(),
-                        );
-                    }
-                }
-            }
-            ProgramOutput::Vector { info: infos } => {
-                for info in infos.iter() {
-                    //generated_code.push_str(format!("        {},\n", info.id.name()).as_str());
-                    let name = info.id.name();
-                    let precision = precisions.get(&name.clone()).ok_or_else(|| {
-                        anyhow::anyhow!(
-                            "Precision for output variable {} not found in precisions map",
-                            name
-                        )
-                    })?;
-                        match precision {
-                        Precision::Fixed {
-                            total_bits: _,
-                            fractional_bits,
-                        } => {
-                            generated_code.push_str(
-                                format!(
-                                    "        ((double){}) / (1 << {}),\n",
-                                    info.id.name(), // in this case we use id.name, in the function body we'll use info.id.name
-                                    fractional_bits
-                                )
-                                .as_str(),
-                            );
-                        }
-                        Precision::Float32 | Precision::Float64 => {
-                            generated_code.push_str(
-                                format!(
-                                    "        {},\n",
-                                    info.id.name(), // in this case we use id.name, in the function body we'll use info.id.name
-                                )
-                                .as_str(),
-                            );
-                        }
-                    }
-                }
-            }
-            ProgramOutput::Matrix { info: infos } => {
-                for row in infos.iter() {
-                    for info in row.iter() {
-                        //generated_code.push_str(format!("        {},\n", info.id.name()).as_str());
+    match config.c_interface {
+        CInterface::Scalar => {
+            generated_code.push_str("\n    return {\n");
+            for (_, output) in outputs {
+                match output {
+                    ProgramOutput::Scalar { info } => {
                         let name = info.id.name();
                         let precision = precisions.get(&name.clone()).ok_or_else(|| {
                             anyhow::anyhow!(
@@ -597,24 +645,110 @@ pub fn generate_c_with_conversion(
                             }
                         }
                     }
+                    ProgramOutput::Vector { info: infos } => {
+                        for info in infos.iter() {
+                            //generated_code.push_str(format!("        {},\n", info.id.name()).as_str());
+                            let name = info.id.name();
+                            let precision = precisions.get(&name.clone()).ok_or_else(|| {
+                                anyhow::anyhow!(
+                                    "Precision for output variable {} not found in precisions map",
+                                    name
+                                )
+                            })?;
+                                match precision {
+                                Precision::Fixed {
+                                    total_bits: _,
+                                    fractional_bits,
+                                } => {
+                                    generated_code.push_str(
+                                        format!(
+                                            "        ((double){}) / (1 << {}),\n",
+                                            info.id.name(), // in this case we use id.name, in the function body we'll use info.id.name
+                                            fractional_bits
+                                        )
+                                        .as_str(),
+                                    );
+                                }
+                                Precision::Float32 | Precision::Float64 => {
+                                    generated_code.push_str(
+                                        format!(
+                                            "        {},\n",
+                                            info.id.name(), // in this case we use id.name, in the function body we'll use info.id.name
+                                        )
+                                        .as_str(),
+                                    );
+                                }
+                            }
+                        }
+                    }
+                    ProgramOutput::Matrix { info: infos } => {
+                        for row in infos.iter() {
+                            for info in row.iter() {
+                                //generated_code.push_str(format!("        {},\n", info.id.name()).as_str());
+                                let name = info.id.name();
+                                let precision = precisions.get(&name.clone()).ok_or_else(|| {
+                                    anyhow::anyhow!(
+                                        "Precision for output variable {} not found in precisions map",
+                                        name
+                                    )
+                                })?;
+                                match precision {
+                                    Precision::Fixed {
+                                        total_bits: _,
+                                        fractional_bits,
+                                    } => {
+                                        generated_code.push_str(
+                                            format!(
+                                                "        ((double){}) / (1 << {}),\n",
+                                                info.id.name(), // in this case we use id.name, in the function body we'll use info.id.name
+                                                fractional_bits
+                                            )
+                                            .as_str(),
+                                        );
+                                    }
+                                    Precision::Float32 | Precision::Float64 => {
+                                        generated_code.push_str(
+                                            format!(
+                                                "        {},\n",
+                                                info.id.name(), // in this case we use id.name, in the function body we'll use info.id.name
+                                            )
+                                            .as_str(),
+                                        );
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            generated_code.push_str("    };\n");
+        }
+        CInterface::Array => {
+            generated_code.push_str(format!("\n    {}_output_t _output;\n", func_name).as_str());
+            for var in output_vars(program) {
+                for (subscript, element) in &var.elements {
+                    let precision = precisions.get(element.name()).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Precision for output variable {} not found in precisions map",
+                            element.name()
+                        )
+                    })?;
+                    generated_code.push_str(
+                        format!(
+                            "    _output.{}{} = {};\n",
+                            var.name,
+                            subscript,
+                            output_conversion(element.name(), precision)
+                        )
+                        .as_str(),
+                    );
                 }
             }
+            generated_code.push_str("    return _output;\n");
         }
     }
-    generated_code.push_str("    };\n");
 
     generated_code.push_str("}\n");
 
-    let folder = config.output_dir.join("codegen/C");
-    std::fs::create_dir_all(&folder).expect("Failed to create codegen directory for C");
-    let filename = folder.join("codegen_with_conversion.cpp");
-    let mut file = match std::fs::File::create(filename.clone()) {
-        Ok(f) => f,
-        Err(e) => anyhow::bail!("Unable to create file {}: {}", filename.display(), e),
-    };
-
-    file.write_all(generated_code.as_bytes())
-        .expect("Unable to write to file");
-
-    Ok(())
+    Ok(generated_code)
 }
@@ -0,0 +1,52 @@
+use indexmap::IndexMap;
+
+/// Deduplicated constants shared by all kernels of a generated library.
+///
+/// Every distinct (C type, literal) pair becomes one `static const` at file
+/// scope; kernel bodies refer to it by name instead of repeating the literal.
+#[derive(Debug, Clone)]
+pub struct ConstantPool {
+    library: String,
+    constants: IndexMap<(String, String), String>,
+}
+
+impl ConstantPool {
+    /// An empty pool for the library `library`
+    pub fn new(library: &str) -> Self {
+        Self {
+            library: library.to_string(),
+            constants: IndexMap::new(),
+        }
+    }
+
+    /// Name of the constant holding `literal` as `c_type`, adding it if needed.
+    ///
+    /// Names are `{library}_const_{n}`: identifiers starting with `_` are
+    /// reserved in C, and the library prefix keeps the constants of several
+    /// generated libraries apart when they are linked together.
+    pub fn name_for(&mut self, c_type: &str, literal: String) -> String {
+        let next_index = self.constants.len();
+        let library = &self.library;
+        self.constants
+            .entry((c_type.to_string(), literal))
+            .or_insert_with(|| format!("{}_const_{}", library, next_index))
+            .clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.constants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.constants.is_empty()
+    }
+
+    /// File-scope declarations of all constants, in insertion order
+    pub fn declarations(&self) -> String {
+        let mut code = String::new();
+        for ((c_type, literal), name) in &self.constants {
+            code.push_str(format!("static const {} {} = {};\n", c_type, name, literal).as_str());
+        }
+        code
+    }
+}
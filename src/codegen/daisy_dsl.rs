@@ -14,7 +14,8 @@ pub fn generate_daisy_dsl(program: &Program, config: &Config) -> Result<()> {
     info!("Generating Daisy DSL code...");
     let inputs = program.get_inputs();
     let body = program.get_body();
-    let func_name = "codegen";
+    config.check_kernel_name()?;
+    let func_name = config.kernel_name.as_str();
 
     let mut generated_code = String::new();
 
@@ -111,12 +112,17 @@ pub fn generate_daisy_dsl(program: &Program, config: &Config) -> Result<()> {
     }
     generated_code.push_str(")\n");
 
-    // print body
+    // print body, with the algorithm line each part was traced at
+    let mut last_location = None;
     for expr in body.iter() {
+        if let Some(comment) = expr.location_comment("", &mut last_location) {
+            generated_code.push_str(&comment);
+        }
         let expr_str: String = match expr {
             Expr::Let {
                 id: var_id,
                 opr: rhs,
+                ..
             } => match rhs {
                 Opr::ConstantScalar { value } => {
                     format!("val {}: Real = {}\n", var_id.name(), value.to_f64())
@@ -171,7 +177,7 @@ pub fn generate_daisy_dsl(program: &Program, config: &Config) -> Result<()> {
     // Create directory and write to file
     let folder = config.output_dir.join("codegen/daisy");
     std::fs::create_dir_all(&folder).expect("Failed to create codegen directory for Daisy DSL");
-    let filename = folder.join("codegen.scala");
+    let filename = folder.join(format!("{}.scala", func_name));
 
     let mut file = match std::fs::File::create(filename.clone()) {
         Ok(f) => f,
@@ -0,0 +1,212 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+
+use crate::{
+    analysis::real::Real,
+    config::CInterface,
+    ir::{
+        identifier::Identifier,
+        precision::Precision,
+        program::{InputLayout, Output, Program, ProgramInput, ProgramOutput},
+    },
+};
+
+/// A kernel parameter or output field in the array calling convention.
+///
+/// Built from the shape the variable had before unrolling; every element keeps
+/// the unrolled scalar identifier that the kernel body refers to.
+#[derive(Debug, Clone)]
+pub struct InterfaceVar {
+    pub name: String,
+    /// Empty for scalars, `[len]` for vectors, `[rows, cols]` for matrices
+    pub dims: Vec<usize>,
+    /// C subscript of each element (e.g. `[1][2]`) and its unrolled identifier
+    pub elements: Vec<(String, Identifier)>,
+}
+
+impl InterfaceVar {
+    /// The name with its array dimensions, e.g. `qsin[7]`
+    pub fn declarator(&self) -> String {
+        let dims: String = self.dims.iter().map(|d| format!("[{}]", d)).collect();
+        format!("{}{}", self.name, dims)
+    }
+}
+
+/// Kernel parameters in the array calling convention, one per original input.
+pub fn input_vars(program: &Program) -> Result<Vec<InterfaceVar>> {
+    if program.get_input_layouts().is_empty() && !program.get_inputs().is_empty() {
+        anyhow::bail!("Array calling convention needs the input layouts recorded by unroll_ir");
+    }
+    let vars = program
+        .get_input_layouts()
+        .iter()
+        .map(|(id, layout)| match layout {
+            InputLayout::Scalar { id: element } => InterfaceVar {
+                name: id.name().clone(),
+                dims: vec![],
+                elements: vec![(String::new(), element.clone())],
+            },
+            InputLayout::Vector { ids } => InterfaceVar {
+                name: id.name().clone(),
+                dims: vec![ids.len()],
+                elements: ids
+                    .iter()
+                    .enumerate()
+                    .map(|(i, element)| (format!("[{}]", i), element.clone()))
+                    .collect(),
+            },
+            InputLayout::Matrix { ids } => InterfaceVar {
+                name: id.name().clone(),
+                dims: vec![ids.len(), ids.first().map_or(0, |row| row.len())],
+                elements: matrix_elements(ids.iter().cloned()),
+            },
+        })
+        .collect();
+    Ok(vars)
+}
+
+/// Kernel parameters in call order for the given calling convention.
+///
+/// With the scalar convention every unrolled input is its own parameter.
+pub fn call_vars(program: &Program, c_interface: CInterface) -> Result<Vec<InterfaceVar>> {
+    match c_interface {
+        CInterface::Scalar => Ok(program
+            .get_inputs()
+            .keys()
+            .map(|id| InterfaceVar {
+                name: id.name().clone(),
+                dims: vec![],
+                elements: vec![(String::new(), id.clone())],
+            })
+            .collect()),
+        CInterface::Array => input_vars(program),
+    }
+}
+
+/// Output struct fields in the array calling convention, one per registered output.
+pub fn output_vars(program: &Program) -> Vec<InterfaceVar> {
+    program
+        .get_outputs()
+        .iter()
+        .map(|(id, output)| match output {
+            ProgramOutput::Scalar { info } => InterfaceVar {
+                name: id.name().clone(),
+                dims: vec![],
+                elements: vec![(String::new(), info.id.clone())],
+            },
+            ProgramOutput::Vector { info: infos } => InterfaceVar {
+                name: id.name().clone(),
+                dims: vec![infos.len()],
+                elements: infos
+                    .iter()
+                    .enumerate()
+                    .map(|(i, info)| (format!("[{}]", i), info.id.clone()))
+                    .collect(),
+            },
+            ProgramOutput::Matrix { info: infos } => InterfaceVar {
+                name: id.name().clone(),
+                dims: vec![infos.len(), infos.first().map_or(0, |row| row.len())],
+                elements: matrix_elements(
+                    infos
+                        .iter()
+                        .map(|row| row.iter().map(|info| info.id.clone()).collect()),
+                ),
+            },
+        })
+        .collect()
+}
+
+/// Every output element with the way it is reached in the returned struct,
+/// e.g. `tau_1` with the scalar convention and `tau[1]` with the array one.
+pub fn output_fields(program: &Program, c_interface: CInterface) -> Vec<(String, Output)> {
+    let mut fields = vec![];
+    for (id, output) in program.get_outputs() {
+        match output {
+            ProgramOutput::Scalar { info } => fields.push((id.name().clone(), info.clone())),
+            ProgramOutput::Vector { info: infos } => {
+                for (i, info) in infos.iter().enumerate() {
+                    let field = match c_interface {
+                        CInterface::Scalar => format!("{}_{}", id.name(), i),
+                        CInterface::Array => format!("{}[{}]", id.name(), i),
+                    };
+                    fields.push((field, info.clone()));
+                }
+            }
+            ProgramOutput::Matrix { info: infos } => {
+                for (i, row) in infos.iter().enumerate() {
+                    for (j, info) in row.iter().enumerate() {
+                        let field = match c_interface {
+                            CInterface::Scalar => format!("{}_{}_{}", id.name(), i, j),
+                            CInterface::Array => format!("{}[{}][{}]", id.name(), i, j),
+                        };
+                        fields.push((field, info.clone()));
+                    }
+                }
+            }
+        }
+    }
+    fields
+}
+
+/// Declared range of the unrolled scalar input `name`
+pub fn scalar_input_range(program: &Program, name: &str) -> Result<(Real, Real)> {
+    let input = program
+        .get_inputs()
+        .iter()
+        .find(|(id, _)| id.name() == name)
+        .map(|(_, input)| input)
+        .ok_or_else(|| anyhow::anyhow!("Input {} not found in program", name))?;
+    match input {
+        ProgramInput::Scalar { info } => Ok(info.range.clone()),
+        ProgramInput::Vector { .. } | ProgramInput::Matrix { .. } => {
+            panic!("Vector and Matrix should have been unrolled before codegen")
+        }
+    }
+}
+
+/// Analysed absolute error bound of an output element
+pub fn output_error_bound(output: &Output) -> f64 {
+    output.error.0.to_f64().abs().max(output.error.1.to_f64().abs())
+}
+
+fn matrix_elements(rows: impl Iterator<Item = Vec<Identifier>>) -> Vec<(String, Identifier)> {
+    rows.enumerate()
+        .flat_map(|(i, row)| {
+            row.into_iter()
+                .enumerate()
+                .map(move |(j, element)| (format!("[{}][{}]", i, j), element))
+        })
+        .collect()
+}
+
+/// The C type shared by all elements of `var`.
+///
+/// Arrays need a single element type, so this fails if the chosen precisions
+/// map the elements to different C types.
+pub fn common_type(
+    var: &InterfaceVar,
+    precisions: &IndexMap<String, Precision>,
+    to_type: fn(&Precision) -> String,
+) -> Result<String> {
+    let mut common: Option<String> = None;
+    for (_, element) in &var.elements {
+        let precision = precisions.get(element.name()).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Precision for variable {} not found in precisions map",
+                element.name()
+            )
+        })?;
+        let c_type = to_type(precision);
+        match &common {
+            Some(existing) if *existing != c_type => anyhow::bail!(
+                "Elements of {} have different C types ({} and {}), cannot pack them into an array",
+                var.name,
+                existing,
+                c_type
+            ),
+            Some(_) => (),
+            None => common = Some(c_type),
+        }
+    }
+    common.ok_or_else(|| anyhow::anyhow!("{} has no elements", var.name))
+}
@@ -0,0 +1,112 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use log::info;
+use std::io::Write;
+
+use crate::{
+    codegen::{
+        c::emit_c_kernel, c_with_conversion::emit_c_with_conversion_kernel,
+        constants::ConstantPool,
+    },
+    config::Config,
+    ir::{precision::Precision, program::Program},
+};
+
+/// One entry point of a generated library: an unrolled program and the
+/// precisions chosen for it.
+#[derive(Debug, Clone, Copy)]
+pub struct LibraryKernel<'a> {
+    pub name: &'a str,
+    pub program: &'a Program,
+    pub precisions: &'a IndexMap<String, Precision>,
+}
+
+type KernelEmitter = fn(
+    &Program,
+    &IndexMap<String, Precision>,
+    &Config,
+    Option<&mut ConstantPool>,
+) -> Result<String>;
+
+/// Checks that the library and kernel names are valid and that no two entry
+/// points would end up with the same symbol.
+pub fn check_library_names(kernel_names: &[&str], config: &Config) -> Result<()> {
+    config.check_kernel_name()?;
+    let mut seen = vec![config.kernel_name.as_str()];
+    for name in kernel_names {
+        Config {
+            kernel_name: name.to_string(),
+            ..config.clone()
+        }
+        .check_kernel_name()?;
+        if seen.contains(name) {
+            anyhow::bail!("Kernel name '{}' is used more than once in library '{}'", name, config.kernel_name);
+        }
+        seen.push(name);
+    }
+    Ok(())
+}
+
+/// Writes all kernels into one translation unit, `{kernel_name}.cpp`, and its
+/// double-interface twin, `{kernel_name}_with_conversion.cpp`, where
+/// `kernel_name` is the library name from `config`.
+///
+/// Each kernel keeps its own entry point and output struct; constants are
+/// emitted once at file scope and shared by all kernels.
+pub fn generate_c_library(kernels: &[LibraryKernel], config: &Config) -> Result<()> {
+    info!("Generating C library {}...", config.kernel_name);
+    let names: Vec<&str> = kernels.iter().map(|kernel| kernel.name).collect();
+    check_library_names(&names, config)?;
+
+    let folder = config.output_dir.join("codegen/C");
+    std::fs::create_dir_all(&folder).expect("Failed to create codegen directory for C");
+
+    let backends: [(KernelEmitter, &str); 2] = [
+        (emit_c_kernel, ""),
+        (emit_c_with_conversion_kernel, "_with_conversion"),
+    ];
+    for (emit, suffix) in backends {
+        // every file is self-contained, so each one declares its own constants
+        let mut pool = ConstantPool::new(&config.kernel_name);
+        let mut kernels_code = String::new();
+        for kernel in kernels {
+            let kernel_config = Config {
+                kernel_name: kernel.name.to_string(),
+                ..config.clone()
+            };
+            kernels_code.push_str(&emit(
+                kernel.program,
+                kernel.precisions,
+                &kernel_config,
+                Some(&mut pool),
+            )?);
+        }
+        write_library_file(
+            &folder.join(format!("{}{}.cpp", config.kernel_name, suffix)),
+            &pool,
+            &kernels_code,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_library_file(filename: &std::path::Path, pool: &ConstantPool, kernels_code: &str) -> Result<()> {
+    let mut generated_code = String::new();
+    generated_code.push_str("#include <math.h>\n");
+    if !pool.is_empty() {
+        generated_code.push('\n');
+        generated_code.push_str(&pool.declarations());
+    }
+    generated_code.push_str(kernels_code);
+
+    let mut file = match std::fs::File::create(filename) {
+        Ok(f) => f,
+        Err(e) => anyhow::bail!("Unable to create file {}: {}", filename.display(), e),
+    };
+
+    file.write_all(generated_code.as_bytes())
+        .expect("Unable to write to file");
+
+    Ok(())
+}
@@ -1,3 +1,8 @@
 pub mod daisy_dsl;
 pub mod c;
-pub mod c_with_conversion;
\ No newline at end of file
+pub mod c_with_conversion;
+pub mod interface;
+pub mod constants;
+pub mod library;
+pub mod test_harness;
+pub mod simulator;
@@ -0,0 +1,340 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+
+use crate::{
+    analysis::real::Real,
+    ir::{
+        expr::OprBinary,
+        interpreter::{Domain, Value, interpret},
+        precision::Precision,
+        program::Program,
+    },
+};
+
+/// A variable of the generated C code, with the type it is declared with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CValue {
+    /// Raw integer of an `intN_t` variable with `fractional_bits` fractional bits
+    Fixed {
+        raw: i64,
+        bits: u32,
+        fractional_bits: i32,
+    },
+    Float(f32),
+    Double(f64),
+}
+
+impl CValue {
+    /// The value as returned by the double interface, `((double)raw) / (1 << f)`
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            CValue::Fixed {
+                raw,
+                fractional_bits,
+                ..
+            } => (*raw as f64) / 2f64.powi(*fractional_bits),
+            CValue::Float(value) => *value as f64,
+            CValue::Double(value) => *value,
+        }
+    }
+
+    /// The value as the raw C arithmetic sees it, integers without their scale
+    fn raw_f64(&self) -> f64 {
+        match self {
+            CValue::Fixed { raw, .. } => *raw as f64,
+            CValue::Float(value) => *value as f64,
+            CValue::Double(value) => *value,
+        }
+    }
+}
+
+/// Width of the `intN_t` type `precision_to_type` picks
+fn type_bits(total_bits: i32) -> Result<u32> {
+    Ok(match total_bits {
+        ..=8 => 8,
+        9..=16 => 16,
+        17..=32 => 32,
+        33..=64 => 64,
+        _ => anyhow::bail!("Fixed precision with more than 64 bits is not supported in C codegen"),
+    })
+}
+
+/// Integer promotion: arithmetic on narrower types happens in `int`
+fn promoted(bits: u32) -> u32 {
+    bits.max(32)
+}
+
+/// Two's complement conversion to a `bits` wide signed integer
+fn wrap(value: i128, bits: u32) -> i128 {
+    let shift = 128 - bits;
+    (value << shift) >> shift
+}
+
+fn shift_left(value: i128, shift: i32, bits: u32) -> Result<i128> {
+    if shift < 0 || shift as u32 >= bits {
+        anyhow::bail!("Shift by {} is undefined for a {} bit integer", shift, bits);
+    }
+    Ok(wrap(value << shift, bits))
+}
+
+fn shift_right(value: i128, shift: i32, bits: u32) -> Result<i128> {
+    if shift < 0 || shift as u32 >= bits {
+        anyhow::bail!("Shift by {} is undefined for a {} bit integer", shift, bits);
+    }
+    Ok(value >> shift)
+}
+
+/// Runs an unrolled program with the exact integer and float semantics of the
+/// code `generate_c_with_conversion` emits for the given precisions.
+///
+/// Fixed-point operands are aligned with the same shifts as the generated
+/// code, multiplications and divisions go through the same wider types, and
+/// every result is truncated to the C type of its variable with two's
+/// complement wraparound, as gcc and clang do. Assignments between variables
+/// copy the raw integer without rescaling, like the generated `T a = b;`.
+/// Operations C leaves undefined (negative or too wide shifts, division by
+/// zero) are reported as errors; signed overflow wraps.
+#[derive(Debug, Clone)]
+pub struct CSimulator {
+    /// Precision of each variable, keyed by name as in the Daisy results
+    pub precisions: IndexMap<String, Precision>,
+}
+
+impl CSimulator {
+    pub fn new(precisions: IndexMap<String, Precision>) -> Self {
+        Self { precisions }
+    }
+
+    fn precision_of(&self, name: &str) -> Result<&Precision> {
+        self.precisions
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Precision for variable {} not found in precisions map", name))
+    }
+
+    /// The C type of `name` as (width, fractional bits), `None` for floats
+    fn fixed_type(&self, name: &str) -> Result<Option<(u32, i32)>> {
+        match self.precision_of(name)? {
+            Precision::Fixed {
+                total_bits,
+                fractional_bits,
+            } => {
+                if *fractional_bits < 0 {
+                    anyhow::bail!("Fractional bits of {} are not decided yet", name);
+                }
+                Ok(Some((type_bits(*total_bits)?, *fractional_bits)))
+            }
+            Precision::Float32 | Precision::Float64 => Ok(None),
+        }
+    }
+
+    /// Implicit conversion of an integer expression to the type of `name`
+    fn store_integer(&self, name: &str, value: i128) -> Result<CValue> {
+        Ok(match self.precision_of(name)? {
+            Precision::Float32 => CValue::Float(value as f32),
+            Precision::Float64 => CValue::Double(value as f64),
+            Precision::Fixed { .. } => {
+                let (bits, fractional_bits) = self.fixed_type(name)?.unwrap();
+                CValue::Fixed {
+                    raw: wrap(value, bits) as i64,
+                    bits,
+                    fractional_bits,
+                }
+            }
+        })
+    }
+
+    /// Implicit conversion of a floating-point expression to the type of `name`
+    fn store_float(&self, name: &str, value: f64) -> Result<CValue> {
+        Ok(match self.precision_of(name)? {
+            Precision::Float32 => CValue::Float(value as f32),
+            Precision::Float64 => CValue::Double(value),
+            Precision::Fixed { .. } => {
+                // truncates towards zero, out of range values are wrapped
+                // where C would be undefined
+                if !value.is_finite() {
+                    anyhow::bail!("Cannot convert {} to a fixed-point value for {}", value, name);
+                }
+                return self.store_integer(name, value.trunc() as i128);
+            }
+        })
+    }
+
+    fn mixed_types(&self, name: &str, opr1: &CValue, opr2: &CValue) -> Result<CValue> {
+        anyhow::bail!(
+            "Mixed types are not supported in C codegen, {:?} and {:?} are mixed computing {}",
+            opr1,
+            opr2,
+            name
+        )
+    }
+}
+
+impl Domain for CSimulator {
+    type Value = CValue;
+
+    fn input(&self, name: &str, value: &Real) -> Result<CValue> {
+        // `(T)(_double_x * (1 << f))` and `(T)_double_x`
+        let value = value.to_f64();
+        match self.fixed_type(name)? {
+            Some((_, fractional_bits)) => self.store_float(name, value * 2f64.powi(fractional_bits)),
+            None => self.store_float(name, value),
+        }
+    }
+
+    fn constant(&self, name: &str, value: &Real) -> Result<CValue> {
+        // the literal `value_precision_to_str` prints
+        match self.fixed_type(name)? {
+            Some((_, fractional_bits)) => {
+                let scaled = value * &Real::from_i64(2).pow(fractional_bits);
+                self.store_integer(name, scaled.nearest_integer() as i128)
+            }
+            None => self.store_float(name, value.to_f64()),
+        }
+    }
+
+    fn neg(&self, name: &str, opr: &CValue) -> Result<CValue> {
+        match opr {
+            CValue::Fixed { raw, bits, .. } => self.store_integer(name, wrap(-(*raw as i128), promoted(*bits))),
+            CValue::Float(value) => self.store_float(name, -value as f64),
+            CValue::Double(value) => self.store_float(name, -value),
+        }
+    }
+
+    fn assign(&self, name: &str, opr: &CValue) -> Result<CValue> {
+        match opr {
+            CValue::Fixed { raw, .. } => self.store_integer(name, *raw as i128),
+            _ => self.store_float(name, opr.raw_f64()),
+        }
+    }
+
+    fn binary(&self, name: &str, opr_type: &OprBinary, opr1: &CValue, opr2: &CValue) -> Result<CValue> {
+        let goal = self.fixed_type(name)?;
+        match (goal, opr1, opr2) {
+            (
+                Some((_, fbgoal)),
+                CValue::Fixed {
+                    raw: raw1,
+                    bits: bits1,
+                    fractional_bits: fb1,
+                },
+                CValue::Fixed {
+                    raw: raw2,
+                    bits: bits2,
+                    fractional_bits: fb2,
+                },
+            ) => {
+                let (raw1, raw2) = (*raw1 as i128, *raw2 as i128);
+                let value = match opr_type {
+                    OprBinary::Add | OprBinary::Sub => {
+                        // each operand is shifted to the goal in its promoted type
+                        let align = |raw: i128, fb: i32, bits: u32| -> Result<i128> {
+                            if fb < fbgoal {
+                                shift_left(raw, fbgoal - fb, promoted(bits))
+                            } else if fb > fbgoal {
+                                shift_right(raw, fb - fbgoal, promoted(bits))
+                            } else {
+                                Ok(raw)
+                            }
+                        };
+                        let (a, b) = (align(raw1, *fb1, *bits1)?, align(raw2, *fb2, *bits2)?);
+                        let bits = promoted(*bits1).max(promoted(*bits2));
+                        match opr_type {
+                            OprBinary::Add => wrap(a + b, bits),
+                            _ => wrap(a - b, bits),
+                        }
+                    }
+                    OprBinary::Mul => {
+                        // (T) ((((N1) (a) * (N2) (b)) >> (f1 + f2 - f)))
+                        let bits = promoted(next_bits(*bits1)?).max(promoted(next_bits(*bits2)?));
+                        shift_right(wrap(raw1 * raw2, bits), fb1 + fb2 - fbgoal, bits)?
+                    }
+                    OprBinary::Div => {
+                        // (((N1) (a) << (f + f2 - f1)) / b)
+                        let bits1 = promoted(next_bits(*bits1)?);
+                        let dividend = shift_left(raw1, fbgoal + fb2 - fb1, bits1)?;
+                        if raw2 == 0 {
+                            anyhow::bail!("Division by zero computing {}", name);
+                        }
+                        wrap(dividend / raw2, bits1.max(promoted(*bits2)))
+                    }
+                    _ => anyhow::bail!("The operation {:?} should have been unrolled", opr_type),
+                };
+                self.store_integer(name, value)
+            }
+            (None, CValue::Float(a), CValue::Float(b)) if *self.precision_of(name)? == Precision::Float32 => {
+                let value = match opr_type {
+                    OprBinary::Add => a + b,
+                    OprBinary::Sub => a - b,
+                    OprBinary::Mul => a * b,
+                    OprBinary::Div => a / b,
+                    _ => anyhow::bail!("The operation {:?} should have been unrolled", opr_type),
+                };
+                Ok(CValue::Float(value))
+            }
+            (None, CValue::Double(a), CValue::Double(b)) if *self.precision_of(name)? == Precision::Float64 => {
+                let value = match opr_type {
+                    OprBinary::Add => a + b,
+                    OprBinary::Sub => a - b,
+                    OprBinary::Mul => a * b,
+                    OprBinary::Div => a / b,
+                    _ => anyhow::bail!("The operation {:?} should have been unrolled", opr_type),
+                };
+                Ok(CValue::Double(value))
+            }
+            _ => self.mixed_types(name, opr1, opr2),
+        }
+    }
+}
+
+/// Width of the type `fixed_precision_to_next_type` picks
+fn next_bits(bits: u32) -> Result<u32> {
+    if bits >= 64 {
+        anyhow::bail!("Next precision beyond 64 bits is not supported in C codegen");
+    }
+    Ok(bits * 2)
+}
+
+/// The outputs the generated `{kernel_name}_with_conversion.cpp` returns for
+/// `inputs`, computed on the host.
+///
+/// `program` must be unrolled and `precisions` the map the C code was
+/// generated with, e.g. the one returned by the analysis. Outputs are keyed by
+/// their registered names, like [`interpret`].
+///
+/// # Examples
+///
+/// ```rust
+/// use indexmap::IndexMap;
+/// use roboprec::*;
+/// use roboprec::codegen::simulator::simulate_c;
+/// use roboprec::ir::{interpreter::Value, program::get_program, unroll::unroll_ir};
+///
+/// let x = add_input_scalar("x", (Real::from_f64(0.0), Real::from_f64(1.0)), 0.5);
+/// let mut square = &x * &x;
+/// register_scalar_output(&mut square, "square");
+/// let program = unroll_ir(&get_program());
+///
+/// let q8 = Precision::Fixed { total_bits: 16, fractional_bits: 8 };
+/// // keyed by the unrolled variable names, as in the Daisy results
+/// let precisions: IndexMap<String, Precision> = ["x", "x_mul_x", "square"]
+///     .into_iter()
+///     .map(|name| (name.to_string(), q8.clone()))
+///     .collect();
+/// let mut inputs = IndexMap::new();
+/// inputs.insert("x".to_string(), Value::Scalar(Real::from_f64(0.3)));
+///
+/// // 0.3 truncates to 76/256, and 76 * 76 >> 8 = 22
+/// let outputs = simulate_c(&program, &precisions, &inputs).unwrap();
+/// assert_eq!(outputs["square"], Value::Scalar(22.0 / 256.0));
+/// ```
+pub fn simulate_c(
+    program: &Program,
+    precisions: &IndexMap<String, Precision>,
+    inputs: &IndexMap<String, Value<Real>>,
+) -> Result<IndexMap<String, Value<f64>>> {
+    let outputs = interpret(program, inputs, &CSimulator::new(precisions.clone()))?;
+    Ok(outputs
+        .into_iter()
+        .map(|(name, value)| (name, value.map(CValue::to_f64)))
+        .collect())
+}
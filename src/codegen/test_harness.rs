@@ -0,0 +1,203 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use log::info;
+use std::io::Write;
+
+use crate::{
+    codegen::{
+        c_with_conversion::emit_c_with_conversion_kernel,
+        interface::{call_vars, output_error_bound, output_fields, scalar_input_range},
+    },
+    config::Config,
+    ir::{
+        expr::Expr,
+        precision::Precision,
+        program::Program,
+    },
+};
+
+/// Every variable of the program in double precision, for the reference build
+fn double_precisions(program: &Program) -> IndexMap<String, Precision> {
+    let mut precisions = IndexMap::new();
+    for id in program.get_inputs().keys() {
+        precisions.insert(id.name().clone(), Precision::Float64);
+    }
+    for expr in program.get_body() {
+        match expr {
+            Expr::Let { id, .. } => {
+                precisions.insert(id.name().clone(), Precision::Float64);
+            }
+        }
+    }
+    precisions
+}
+
+/// Writes `codegen/C/test_{kernel_name}.cpp`, a standalone end-to-end check
+/// of the generated kernel.
+///
+/// The harness includes `kernel_file` (the double-interface kernel), builds
+/// the same program once more in double precision as `{kernel_name}_double`,
+/// draws random inputs from the declared input ranges and checks that the
+/// difference between both stays within the analysed error bound of every
+/// output. It prints the worst margin (bound minus observed error) and exits
+/// with 1 if any bound is exceeded.
+///
+/// `program` must be the unrolled program after `update_program_outputs`, so
+/// its outputs carry the analysed errors.
+///
+/// Usage: `./test_{kernel_name} [samples] [seed]`.
+pub fn generate_c_test_harness(program: &Program, config: &Config, kernel_file: &str) -> Result<()> {
+    info!("Generating C test harness...");
+    config.check_kernel_name()?;
+    let func_name = config.kernel_name.as_str();
+    let reference_config = Config {
+        kernel_name: format!("{}_double", func_name),
+        ..config.clone()
+    };
+
+    let fields = output_fields(program, config.c_interface);
+    if fields.is_empty() {
+        anyhow::bail!("Program has no outputs, nothing to test");
+    }
+
+    let mut generated_code = String::new();
+    generated_code.push_str("#include <math.h>\n");
+    generated_code.push_str("#include <stdint.h>\n");
+    generated_code.push_str("#include <stdio.h>\n");
+    generated_code.push_str("#include <stdlib.h>\n");
+    generated_code.push_str(format!("\n#include \"{}\"\n", kernel_file).as_str());
+
+    // the same program in double precision serves as reference
+    generated_code.push_str("\n// double precision reference\n");
+    generated_code.push_str(&emit_c_with_conversion_kernel(
+        program,
+        &double_precisions(program),
+        &reference_config,
+        None,
+    )?);
+
+    // analysed bounds, one per output element
+    generated_code.push_str(format!("\nstatic const int {}_n_outputs = {};\n", func_name, fields.len()).as_str());
+    generated_code.push_str(format!("static const char *{}_output_names[] = {{\n", func_name).as_str());
+    for (field, _) in &fields {
+        generated_code.push_str(format!("    \"{}\",\n", field).as_str());
+    }
+    generated_code.push_str("};\n");
+    generated_code.push_str(format!("static const double {}_error_bounds[] = {{\n", func_name).as_str());
+    for (_, output) in &fields {
+        generated_code.push_str(format!("    {:e},\n", output_error_bound(output)).as_str());
+    }
+    generated_code.push_str("};\n");
+
+    generated_code.push_str(
+        r#"
+static uint64_t {kernel}_rng_state = 0x853c49e6748fea9bull;
+
+// splitmix64, so every platform draws the same inputs for the same seed
+static double {kernel}_uniform(double lo, double hi) {
+    uint64_t z = ({kernel}_rng_state += 0x9e3779b97f4a7c15ull);
+    z = (z ^ (z >> 30)) * 0xbf58476d1ce4e5b9ull;
+    z = (z ^ (z >> 27)) * 0x94d049bb133111ebull;
+    z = z ^ (z >> 31);
+    return lo + (hi - lo) * ((double)(z >> 11) / 9007199254740992.0);
+}
+
+static double {kernel}_worst_margin = INFINITY;
+static double {kernel}_worst_error = 0.0;
+static int {kernel}_worst_output = 0;
+static long {kernel}_violations = 0;
+
+static void {kernel}_check(int output, double value, double reference) {
+    double error = fabs(value - reference);
+    double margin = {kernel}_error_bounds[output] - error;
+    if (!(margin >= 0.0)) {
+        {kernel}_violations++;
+    }
+    if (!(margin >= {kernel}_worst_margin)) {
+        {kernel}_worst_margin = margin;
+        {kernel}_worst_error = error;
+        {kernel}_worst_output = output;
+    }
+}
+
+int main(int argc, char **argv) {
+    long samples = argc > 1 ? atol(argv[1]) : 10000;
+    if (argc > 2) {
+        {kernel}_rng_state = strtoull(argv[2], NULL, 10);
+    }
+
+    for (long sample = 0; sample < samples; sample++) {
+"#
+        .replace("{kernel}", func_name)
+        .as_str(),
+    );
+
+    // draw the inputs
+    let mut args = vec![];
+    for var in call_vars(program, config.c_interface)? {
+        generated_code.push_str(format!("        double in_{};\n", var.declarator()).as_str());
+        for (subscript, element) in &var.elements {
+            let (lo, hi) = scalar_input_range(program, element.name())?;
+            generated_code.push_str(
+                format!(
+                    "        in_{}{} = {}_uniform({:e}, {:e});\n",
+                    var.name,
+                    subscript,
+                    func_name,
+                    lo.to_f64(),
+                    hi.to_f64()
+                )
+                .as_str(),
+            );
+        }
+        args.push(format!("in_{}", var.name));
+    }
+
+    // run both builds and compare every output element
+    let args = args.join(", ");
+    generated_code.push_str(
+        format!("\n        {}_output_t out = {}({});\n", func_name, func_name, args).as_str(),
+    );
+    generated_code.push_str(
+        format!(
+            "        {}_output_t ref = {}({});\n",
+            reference_config.kernel_name, reference_config.kernel_name, args
+        )
+        .as_str(),
+    );
+    for (i, (field, _)) in fields.iter().enumerate() {
+        generated_code.push_str(format!("        {}_check({}, out.{}, ref.{});\n", func_name, i, field, field).as_str());
+    }
+
+    generated_code.push_str(
+        r#"    }
+
+    printf("Samples: %ld\n", samples);
+    printf("Worst margin: %g (output %s, observed error %g, bound %g)\n",
+           {kernel}_worst_margin, {kernel}_output_names[{kernel}_worst_output],
+           {kernel}_worst_error, {kernel}_error_bounds[{kernel}_worst_output]);
+    if ({kernel}_violations > 0) {
+        printf("FAILED: %ld error bound violations\n", {kernel}_violations);
+        return 1;
+    }
+    printf("OK: all %d outputs within their error bounds\n", {kernel}_n_outputs);
+    return 0;
+}
+"#
+        .replace("{kernel}", func_name)
+        .as_str(),
+    );
+
+    let folder = config.output_dir.join("codegen/C");
+    std::fs::create_dir_all(&folder).expect("Failed to create codegen directory for C");
+    let filename = folder.join(format!("test_{}.cpp", func_name));
+    let mut file = match std::fs::File::create(filename.clone()) {
+        Ok(f) => f,
+        Err(e) => anyhow::bail!("Unable to create file {}: {}", filename.display(), e),
+    };
+
+    file.write_all(generated_code.as_bytes())
+        .expect("Unable to write to file");
+
+    Ok(())
+}
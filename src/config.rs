@@ -1,6 +1,60 @@
+use anyhow::Result;
+
 use crate::ir::precision::Precision;
 use std::path::PathBuf;
 
+/// Calling convention of the generated C kernels.
+///
+/// # Variants
+///
+/// * `Scalar` - One parameter per unrolled scalar input (`qsin_0, qsin_1, ...`)
+///   and one struct field per output element
+/// * `Array` - One array parameter per input and one array field per output,
+///   following the shape the input or output had before unrolling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CInterface {
+    #[default]
+    Scalar,
+    Array,
+}
+
+/// How the Monte Carlo estimate draws input values from the input ranges.
+///
+/// # Variants
+///
+/// * `Uniform` - Every input uniformly in its range
+/// * `Corners` - Every input at one end of its range; all corners are tried
+///   if there are no more than `samples` of them, random ones otherwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Sampling {
+    #[default]
+    Uniform,
+    Corners,
+}
+
+/// Empirical error estimation next to the worst-case analysis.
+///
+/// The program is evaluated on sampled inputs both exactly and with the
+/// exact arithmetic of the generated code, and the observed errors are
+/// reported per output in `analysis_data/{kernel_name}/monte_carlo.txt`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonteCarlo {
+    pub samples: usize,
+    pub sampling: Sampling,
+    /// Seed of the input generator, runs with the same seed draw the same inputs
+    pub seed: u64,
+}
+
+impl Default for MonteCarlo {
+    fn default() -> Self {
+        Self {
+            samples: 10000,
+            sampling: Sampling::Uniform,
+            seed: 0x853c49e6748fea9b,
+        }
+    }
+}
+
 /// Configuration for RoboPrec analysis and code generation.
 ///
 /// Specifies the target numerical precision and output directory for
@@ -22,6 +76,10 @@ use std::path::PathBuf;
 ///         fractional_bits: -1,  // Auto-optimize
 ///     },
 ///     output_dir: PathBuf::from("my_output/"),
+///     c_interface: CInterface::Array,
+///     kernel_name: String::from("fk_7dof"),
+///     monte_carlo: Some(MonteCarlo::default()),
+///     error_attribution: Some(10),
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -30,6 +88,18 @@ pub struct Config {
     pub precision: Precision,
     /// Directory for generated code and analysis files
     pub output_dir: PathBuf,
+    /// Calling convention of the generated C kernels
+    pub c_interface: CInterface,
+    /// Name of the generated kernel; used for the C function (`{kernel_name}`),
+    /// its output struct (`{kernel_name}_output_t`), the Daisy object and
+    /// every generated file name, and the folder of its analysis data
+    pub kernel_name: String,
+    /// Empirical error estimation after the analysis, disabled by default
+    pub monte_carlo: Option<MonteCarlo>,
+    /// Number of operations reported per output as the largest contributors
+    /// to its error, in `analysis_data/{kernel_name}/error_attribution.txt`;
+    /// disabled by default
+    pub error_attribution: Option<usize>,
 }
 
 impl Default for Config {
@@ -37,7 +107,33 @@ impl Default for Config {
         Self {
             precision: Precision::Float64,
             output_dir: PathBuf::from("output/"),
+            c_interface: CInterface::default(),
+            kernel_name: String::from("codegen"),
+            monte_carlo: None,
+            error_attribution: None,
         }
     }
 }
 
+impl Config {
+    /// Checks that `kernel_name` can be used as a C identifier, a Scala object
+    /// name and a file name at the same time.
+    pub fn check_kernel_name(&self) -> Result<()> {
+        let name = &self.kernel_name;
+        let mut chars = name.chars();
+        let valid = match chars.next() {
+            Some(first) => {
+                (first.is_ascii_alphabetic() || first == '_')
+                    && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+            }
+            None => false,
+        };
+        if !valid {
+            anyhow::bail!(
+                "Invalid kernel name '{}': expected a C identifier ([A-Za-z_][A-Za-z0-9_]*)",
+                name
+            );
+        }
+        Ok(())
+    }
+}
@@ -1,50 +1,76 @@
-use anyhow::{Context, Result};
 use std::vec;
-use roboprec::{add_input_vector, register_matrix_output, register_vector_output, analysis, Real, Config};
+use roboprec::{add_input_vector, register_matrix_output, register_vector_output, Real, Vector};
 
 use crate::{
     algorithms::{
         forward_kinematics::{FKResult, forward_kinematics},
+        jacobian::{ReferenceFrame, jacobian},
+        rnea::rnea,
         rnea_derivatives::rneaderivatives,
-        robots::{
-            panda::{panda, panda_get_bounds},
-            roarm_m2::{roarm_m2, roarm_m2_get_bounds},
-        },
+        robots::robot_info::{JointType, RobotInfo},
     },
     helpers::{cos_extremes, sin_extremes},
 };
 
-#[allow(dead_code)]
-pub fn fk_7dof(config: Config) -> Result<()> {
-    const DOF: usize = 7;
-
-    let joint_bounds = panda_get_bounds();
-    let v_ranges = vec![(Real::from_f64(-0.5), Real::from_f64(0.5)); DOF];
-    let a_ranges = vec![(Real::from_f64(-1.0), Real::from_f64(1.0)); DOF];
-
+/// Range of every joint velocity
+pub const V_RANGE: (f64, f64) = (-0.5, 0.5);
+/// Range of every joint acceleration
+pub const A_RANGE: (f64, f64) = (-1.0, 1.0);
+
+/// Adds the inputs `qsin` and `qcos` of a robot whose joints move as
+/// `joint_types`. A revolute joint's entries range over the extremes of the
+/// sine and cosine over its bounds. A prismatic joint's `qsin` is its
+/// displacement and ranges over its bounds as they are, while its `qcos` is
+/// unused and pinned to 1.
+pub fn angle_inputs(joint_bounds: &[(Real, Real)], joint_types: &[JointType]) -> (Vector, Vector) {
+    let dof = joint_bounds.len();
     let minmax_sin = joint_bounds
         .iter()
-        .map(|min_max| {
-            let (sin_min, sin_max) = sin_extremes(min_max.0.to_f64(), min_max.1.to_f64());
-            (Real::from_f64(sin_min), Real::from_f64(sin_max))
+        .zip(joint_types)
+        .map(|(min_max, joint_type)| match joint_type {
+            JointType::Prismatic(_) => min_max.clone(),
+            _ => {
+                let (sin_min, sin_max) = sin_extremes(min_max.0.to_f64(), min_max.1.to_f64());
+                (Real::from_f64(sin_min), Real::from_f64(sin_max))
+            }
         })
         .collect::<Vec<(Real, Real)>>();
     let minmax_cos = joint_bounds
         .iter()
-        .map(|min_max| {
-            let (cos_min, cos_max) = cos_extremes(min_max.0.to_f64(), min_max.1.to_f64());
-            (Real::from_f64(cos_min), Real::from_f64(cos_max))
+        .zip(joint_types)
+        .map(|(min_max, joint_type)| match joint_type {
+            JointType::Prismatic(_) => (Real::from_f64(1.0), Real::from_f64(1.0)),
+            _ => {
+                let (cos_min, cos_max) = cos_extremes(min_max.0.to_f64(), min_max.1.to_f64());
+                (Real::from_f64(cos_min), Real::from_f64(cos_max))
+            }
         })
         .collect::<Vec<(Real, Real)>>();
 
-    let qsin =
-        add_input_vector("qsin", minmax_sin.clone(), vec![0.0; DOF]);
-    let qcos =
-        add_input_vector("qcos", minmax_cos.clone(), vec![0.0; DOF]);
-    let v = add_input_vector("v", v_ranges.clone(), vec![0.0; DOF]);
-    let a = add_input_vector("a", a_ranges.clone(), vec![0.0; DOF]);
+    let qsin = add_input_vector("qsin", minmax_sin, vec![0.0; dof]);
+    let qcos = add_input_vector("qcos", minmax_cos, vec![0.0; dof]);
+
+    (qcos, qsin)
+}
+
+/// Adds the inputs `qsin`, `qcos`, `v` and `a` of a robot whose joints move as
+/// `joint_types`, see `angle_inputs`
+pub fn joint_inputs(joint_bounds: &[(Real, Real)], joint_types: &[JointType]) -> (Vector, Vector, Vector, Vector) {
+    let dof = joint_bounds.len();
+    let v_ranges = vec![(Real::from_f64(V_RANGE.0), Real::from_f64(V_RANGE.1)); dof];
+    let a_ranges = vec![(Real::from_f64(A_RANGE.0), Real::from_f64(A_RANGE.1)); dof];
 
-    let result = forward_kinematics(qcos, qsin, v, a, &panda());
+    let (qcos, qsin) = angle_inputs(joint_bounds, joint_types);
+    let v = add_input_vector("v", v_ranges, vec![0.0; dof]);
+    let a = add_input_vector("a", a_ranges, vec![0.0; dof]);
+
+    (qcos, qsin, v, a)
+}
+
+/// Forward kinematics, with the placement, velocity and acceleration of every
+/// joint as outputs
+pub fn fk(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot_info: &RobotInfo) {
+    let result = forward_kinematics(qcos, qsin, v, a, robot_info);
 
     let FKResult {
         mut omi_translations,
@@ -66,89 +92,27 @@ pub fn fk_7dof(config: Config) -> Result<()> {
     all_a.iter_mut().enumerate().for_each(|(i, a)| {
         register_vector_output(a, &format!("all_a_{}", i));
     });
-
-    analysis(config).with_context(|| "Failed to analyze program")?;
-
-    Ok(())
 }
 
-#[allow(dead_code)]
-pub(super) fn rnea_deriv_4dof(config: Config) -> Result<()> {
-    const DOF: usize = 4;
-
-    let joint_bounds = roarm_m2_get_bounds();
-    let v_ranges = vec![(Real::from_f64(-0.5), Real::from_f64(0.5)); DOF];
-    let a_ranges = vec![(Real::from_f64(-1.0), Real::from_f64(1.0)); DOF];
-
-    let minmax_sin = joint_bounds
-        .iter()
-        .map(|min_max| {
-            let (sin_min, sin_max) = sin_extremes(min_max.0.to_f64(), min_max.1.to_f64());
-            (Real::from_f64(sin_min), Real::from_f64(sin_max))
-        })
-        .collect::<Vec<(Real, Real)>>();
-    let minmax_cos = joint_bounds
-        .iter()
-        .map(|min_max| {
-            let (cos_min, cos_max) = cos_extremes(min_max.0.to_f64(), min_max.1.to_f64());
-            (Real::from_f64(cos_min), Real::from_f64(cos_max))
-        })
-        .collect::<Vec<(Real, Real)>>();
+/// Jacobian of the last link in the base frame
+pub fn jacobian_world(qcos: Vector, qsin: Vector, robot_info: &RobotInfo) {
+    let last = robot_info.n_joints - 1;
+    let mut jacobian = jacobian(qcos, qsin, robot_info, last, ReferenceFrame::World);
+    register_matrix_output(&mut jacobian, "jacobian");
+}
 
-    let qsin =
-        add_input_vector("qsin", minmax_sin.clone(), vec![0.0; DOF]);
-    let qcos =
-        add_input_vector("qcos", minmax_cos.clone(), vec![0.0; DOF]);
-    let v = add_input_vector("v", v_ranges.clone(), vec![0.0; DOF]);
-    let a = add_input_vector("a", a_ranges.clone(), vec![0.0; DOF]);
+/// Inverse dynamics, with the joint torques as output
+pub fn rnea_tau(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot_info: &RobotInfo) {
+    let mut tau = rnea(qcos, qsin, v, a, robot_info);
+    register_vector_output(&mut tau, "tau");
+}
 
-    let result = rneaderivatives(qcos, qsin, v, a, &roarm_m2());
+/// Derivatives of inverse dynamics with respect to `a`, `v` and `q`
+pub fn rnea_deriv(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot_info: &RobotInfo) {
+    let result = rneaderivatives(qcos, qsin, v, a, robot_info);
 
     let (mut rnea_partial_da, mut rnea_partial_dv, mut rnea_partial_dq, _) = result;
     register_matrix_output(&mut rnea_partial_da, "rnea_partial_da");
     register_matrix_output(&mut rnea_partial_dv, "rnea_partial_dv");
     register_matrix_output(&mut rnea_partial_dq, "rnea_partial_dq");
-
-    analysis(config).with_context(|| "Failed to analyze program")?;
-
-    Ok(())
-}
-
-#[allow(dead_code)]
-pub(super) fn rnea_deriv_7dof(config: Config) -> Result<()> {
-    const DOF: usize = 7;
-
-    let v_ranges = vec![(Real::from_f64(-0.5), Real::from_f64(0.5)); DOF];
-    let a_ranges = vec![(Real::from_f64(-1.0), Real::from_f64(1.0)); DOF];
-
-    let joint_bounds = panda_get_bounds();
-    let minmax_sin = joint_bounds
-        .iter()
-        .map(|min_max| {
-            let (sin_min, sin_max) = sin_extremes(min_max.0.to_f64(), min_max.1.to_f64());
-            (Real::from_f64(sin_min), Real::from_f64(sin_max))
-        })
-        .collect::<Vec<(Real, Real)>>();
-    let minmax_cos = joint_bounds
-        .iter()
-        .map(|min_max| {
-            let (cos_min, cos_max) = cos_extremes(min_max.0.to_f64(), min_max.1.to_f64());
-            (Real::from_f64(cos_min), Real::from_f64(cos_max))
-        })
-        .collect::<Vec<(Real, Real)>>();
-
-    let qsin =
-        add_input_vector("qsin", minmax_sin.clone(), vec![0.0; DOF]);
-    let qcos =
-        add_input_vector("qcos", minmax_cos.clone(), vec![0.0; DOF]);
-    let v = add_input_vector("v", v_ranges.clone(), vec![0.0; DOF]);
-    let a = add_input_vector("a", a_ranges.clone(), vec![0.0; DOF]);
-
-    let result = rneaderivatives(qcos, qsin, v, a, &panda());
-
-    let (_rnea_partial_da, _rnea_partial_dv, _rnea_partial_dq, _) = result;
-
-    analysis(config).with_context(|| "Failed to analyze program")?;
-
-    Ok(())
 }
@@ -1,4 +1,5 @@
 use log::warn;
+use std::panic::Location;
 
 use crate::{analysis::real::Real, ir::identifier::IdSize};
 
@@ -60,30 +61,62 @@ pub enum Opr {
 /// This expression is only used as an input for the analysis stages.
 /// It should be later replaced by a more complex AST, specifically for analysis stages.
 pub enum Expr {
-    Let { id: Identifier, opr: Opr },
+    /// `location` is the line of the algorithm that traced the expression,
+    /// `None` for expressions built by hand
+    Let {
+        id: Identifier,
+        opr: Opr,
+        location: Option<&'static Location<'static>>,
+    },
+}
+
+impl Expr {
+    /// Where the expression was traced, as `file:line:column`
+    pub fn location(&self) -> Option<String> {
+        match self {
+            Expr::Let { location, .. } => location.map(|location| location.to_string()),
+        }
+    }
+
+    /// A `// file:line:column` line for the first of several expressions
+    /// traced at the same place, `None` for the others. `last` keeps the
+    /// location of the previous expression.
+    pub fn location_comment(&self, indent: &str, last: &mut Option<String>) -> Option<String> {
+        let location = self.location();
+        if location.is_none() || location == *last {
+            return None;
+        }
+        *last = location.clone();
+        location.map(|location| format!("{}// {}\n", indent, location))
+    }
 }
 
+#[track_caller]
 pub fn create_scalar_constant(name: &str, value: Real) -> Identifier {
     let new_id = Identifier::new_scalar(name);
     let expr = Expr::Let {
         id: new_id.clone(),
         opr: Opr::ConstantScalar { value },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_vector_constant(name: &str, value: Vec<Real>) -> Identifier {
     let size = value.len();
     let new_id = Identifier::new_vector(name, size);
     let expr = Expr::Let {
         id: new_id.clone(),
         opr: Opr::ConstantVector { value },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_matrix_constant(name: &str, value: Vec<Vec<Real>>) -> Identifier {
     let size0 = value.len();
     let size1 = if size0 == 0 { 0 } else { value[0].len() };
@@ -91,21 +124,25 @@ pub fn create_matrix_constant(name: &str, value: Vec<Vec<Real>>) -> Identifier {
     let expr = Expr::Let {
         id: new_id.clone(),
         opr: Opr::ConstantMatrix { value },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_unary_scalar_expr(name: &str, opr1: Identifier, opr_type: OprUnary) -> Identifier {
     let new_id = Identifier::new_scalar(name);
     let expr = Expr::Let {
         id: new_id.clone(),
         opr: Opr::Unary { opr1, opr_type },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_unary_vector_expr(name: &str, opr1: Identifier, opr_type: OprUnary) -> Identifier {
     let size = match opr1.size {
         IdSize::Vector { len } => len,
@@ -115,11 +152,13 @@ pub fn create_unary_vector_expr(name: &str, opr1: Identifier, opr_type: OprUnary
     let expr = Expr::Let {
         id: new_id.clone(),
         opr: Opr::Unary { opr1, opr_type },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_unary_matrix_expr(name: &str, opr1: Identifier, opr_type: OprUnary) -> Identifier {
     let (size0, size1) = match opr1.size {
         IdSize::Matrix { row_size, col_size } => (row_size, col_size),
@@ -129,11 +168,13 @@ pub fn create_unary_matrix_expr(name: &str, opr1: Identifier, opr_type: OprUnary
     let expr = Expr::Let {
         id: new_id.clone(),
         opr: Opr::Unary { opr1, opr_type },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_binary_scalar_expr(
     name: &str,
     opr1: Identifier,
@@ -148,11 +189,13 @@ pub fn create_binary_scalar_expr(
             opr2,
             opr_type,
         },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_binary_vector_expr(
     name: &str,
     opr1: Identifier,
@@ -168,11 +211,13 @@ pub fn create_binary_vector_expr(
             opr2,
             opr_type,
         },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_binary_matrix_expr(
     name: &str,
     opr1: Identifier,
@@ -189,32 +234,38 @@ pub fn create_binary_matrix_expr(
             opr2,
             opr_type,
         },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_construct_scalar_expr(name: &str, id: Identifier) -> Identifier {
     let new_id = Identifier::new_scalar(name);
     let expr = Expr::Let {
         id: new_id.clone(),
         opr: Opr::ConstructScalar { id },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_construct_vector_expr(name: &str, ids: Vec<Identifier>) -> Identifier {
     let size = ids.len();
     let new_id = Identifier::new_vector(name, size);
     let expr = Expr::Let {
         id: new_id.clone(),
         opr: Opr::ConstructVector { ids },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_construct_matrix_expr(name: &str, ids: Vec<Vec<Identifier>>) -> Identifier {
     let size0 = ids.len();
     let size1 = ids[0].len();
@@ -222,11 +273,13 @@ pub fn create_construct_matrix_expr(name: &str, ids: Vec<Vec<Identifier>>) -> Id
     let expr = Expr::Let {
         id: new_id.clone(),
         opr: Opr::ConstructMatrix { ids },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_index_vector_expr(name: &str, opr1: Identifier, index: usize) -> Identifier {
     let new_id = Identifier::new_scalar(name);
     let expr = Expr::Let {
@@ -235,11 +288,13 @@ pub fn create_index_vector_expr(name: &str, opr1: Identifier, index: usize) -> I
             opr1,
             opr_type: OprUnary::Index { index: vec![index] },
         },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
 }
 
+#[track_caller]
 pub fn create_index_matrix_row_expr(
     _name: &str,
     _opr1: Identifier,
@@ -266,6 +321,7 @@ pub fn create_index_matrix_row_expr(
     create_construct_vector_expr(_name, element_ids)
 }
 
+#[track_caller]
 pub fn create_index_matrix_element_expr(
     name: &str,
     opr1: Identifier,
@@ -281,6 +337,7 @@ pub fn create_index_matrix_element_expr(
                 index: vec![row_index, col_index],
             },
         },
+        location: Some(Location::caller()),
     };
     get_program().add_expr(expr);
     new_id
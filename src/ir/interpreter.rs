@@ -0,0 +1,522 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+
+use crate::{
+    analysis::real::Real,
+    ir::{
+        expr::{Expr, Opr, OprBinary, OprUnary},
+        identifier::Identifier,
+        precision::Precision,
+        program::{InputLayout, Program, ProgramInput, ProgramOutput},
+    },
+};
+
+/// A value of the interpreter, shaped like the variable it belongs to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<T> {
+    Scalar(T),
+    Vector(Vec<T>),
+    Matrix(Vec<Vec<T>>),
+}
+
+impl<T> Value<T> {
+    /// All elements, matrices in row-major order
+    pub fn elements(&self) -> Vec<&T> {
+        match self {
+            Value::Scalar(value) => vec![value],
+            Value::Vector(values) => values.iter().collect(),
+            Value::Matrix(rows) => rows.iter().flatten().collect(),
+        }
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Value<U> {
+        match self {
+            Value::Scalar(value) => Value::Scalar(f(value)),
+            Value::Vector(values) => Value::Vector(values.iter().map(f).collect()),
+            Value::Matrix(rows) => Value::Matrix(rows.iter().map(|row| row.iter().map(&f).collect()).collect()),
+        }
+    }
+
+    fn shape(&self) -> String {
+        match self {
+            Value::Scalar(_) => "scalar".to_string(),
+            Value::Vector(values) => format!("vector of {}", values.len()),
+            Value::Matrix(rows) => format!("{}x{} matrix", rows.len(), rows.first().map_or(0, |row| row.len())),
+        }
+    }
+}
+
+/// The number system a program is interpreted in.
+///
+/// Every operation gets the name of the variable it defines, so a domain can
+/// look up per-variable information such as the precision Daisy chose.
+pub trait Domain {
+    type Value: Clone + std::fmt::Debug;
+
+    /// Converts an input assignment into the domain
+    fn input(&self, name: &str, value: &Real) -> Result<Self::Value>;
+    fn constant(&self, name: &str, value: &Real) -> Result<Self::Value>;
+    fn neg(&self, name: &str, opr: &Self::Value) -> Result<Self::Value>;
+    /// Copies a value into the variable `name`
+    fn assign(&self, name: &str, opr: &Self::Value) -> Result<Self::Value>;
+    /// One of `Add`, `Sub`, `Mul` and `Div`
+    fn binary(&self, name: &str, opr_type: &OprBinary, opr1: &Self::Value, opr2: &Self::Value) -> Result<Self::Value>;
+}
+
+/// Exact rational arithmetic
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Exact;
+
+impl Domain for Exact {
+    type Value = Real;
+
+    fn input(&self, _name: &str, value: &Real) -> Result<Real> {
+        Ok(value.clone())
+    }
+
+    fn constant(&self, _name: &str, value: &Real) -> Result<Real> {
+        Ok(value.clone())
+    }
+
+    fn neg(&self, _name: &str, opr: &Real) -> Result<Real> {
+        Ok(-opr)
+    }
+
+    fn assign(&self, _name: &str, opr: &Real) -> Result<Real> {
+        Ok(opr.clone())
+    }
+
+    fn binary(&self, name: &str, opr_type: &OprBinary, opr1: &Real, opr2: &Real) -> Result<Real> {
+        Ok(match opr_type {
+            OprBinary::Add => opr1 + opr2,
+            OprBinary::Sub => opr1 - opr2,
+            OprBinary::Mul => opr1 * opr2,
+            OprBinary::Div => {
+                if *opr2 == Real::zero() {
+                    anyhow::bail!("Division by zero computing {}", name);
+                }
+                opr1 / opr2
+            }
+            _ => anyhow::bail!("{:?} is not an element-wise operation", opr_type),
+        })
+    }
+}
+
+/// IEEE double arithmetic
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Double;
+
+impl Domain for Double {
+    type Value = f64;
+
+    fn input(&self, _name: &str, value: &Real) -> Result<f64> {
+        Ok(value.to_f64())
+    }
+
+    fn constant(&self, _name: &str, value: &Real) -> Result<f64> {
+        Ok(value.to_f64())
+    }
+
+    fn neg(&self, _name: &str, opr: &f64) -> Result<f64> {
+        Ok(-opr)
+    }
+
+    fn assign(&self, _name: &str, opr: &f64) -> Result<f64> {
+        Ok(*opr)
+    }
+
+    fn binary(&self, _name: &str, opr_type: &OprBinary, opr1: &f64, opr2: &f64) -> Result<f64> {
+        Ok(match opr_type {
+            OprBinary::Add => opr1 + opr2,
+            OprBinary::Sub => opr1 - opr2,
+            OprBinary::Mul => opr1 * opr2,
+            OprBinary::Div => opr1 / opr2,
+            _ => anyhow::bail!("{:?} is not an element-wise operation", opr_type),
+        })
+    }
+}
+
+/// Arithmetic in a target [`Precision`], rounding every result into the
+/// precision of the variable it is stored in.
+///
+/// Results are computed exactly and rounded once: fixed-point values are
+/// floored onto their grid and wrapped to their word size (like the shifts
+/// of the generated code), inputs are truncated towards zero (like the C
+/// conversion) and constants are rounded to nearest (like the generated
+/// literals). Floats round to nearest. It does not model the intermediate
+/// operand shifts of the generated C code.
+#[derive(Debug, Clone, Default)]
+pub struct Simulated {
+    /// Precision of each variable, keyed by name as in the Daisy results
+    pub precisions: IndexMap<String, Precision>,
+    /// Precision of variables missing from `precisions`
+    pub default: Option<Precision>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Rounding {
+    Floor,
+    TowardZero,
+    Nearest,
+}
+
+impl Simulated {
+    /// The same precision for every variable
+    pub fn uniform(precision: Precision) -> Self {
+        Self {
+            precisions: IndexMap::new(),
+            default: Some(precision),
+        }
+    }
+
+    /// The precisions chosen for an unrolled program, e.g. Daisy's
+    pub fn per_variable(precisions: IndexMap<String, Precision>) -> Self {
+        Self {
+            precisions,
+            default: None,
+        }
+    }
+
+    fn precision_of(&self, name: &str) -> Result<&Precision> {
+        self.precisions
+            .get(name)
+            .or(self.default.as_ref())
+            .ok_or_else(|| anyhow::anyhow!("Precision for variable {} not found in precisions map", name))
+    }
+
+    fn round(&self, name: &str, value: Real, rounding: Rounding) -> Result<Real> {
+        match self.precision_of(name)? {
+            Precision::Fixed {
+                total_bits,
+                fractional_bits,
+            } => {
+                if *fractional_bits < 0 {
+                    anyhow::bail!("Fractional bits of {} are not decided yet", name);
+                }
+                let scale = Real::from_i64(2).pow(*fractional_bits);
+                let scaled = &value * &scale;
+                let mut integer = match rounding {
+                    Rounding::Floor => scaled.floor(),
+                    Rounding::Nearest => Real::from_i64(scaled.nearest_integer()),
+                    Rounding::TowardZero if scaled.is_positive() => scaled.floor(),
+                    Rounding::TowardZero => -(-&scaled).floor(),
+                };
+                // two's complement wraparound
+                let modulus = Real::from_i64(2).pow(*total_bits);
+                let half = Real::from_i64(2).pow(*total_bits - 1);
+                integer = &integer + &half;
+                integer = &integer - &(&(&integer / &modulus).floor() * &modulus);
+                integer = &integer - &half;
+                Ok(&integer / &scale)
+            }
+            Precision::Float32 => Ok(Real::from_f64(value.to_f64() as f32 as f64)),
+            Precision::Float64 => Ok(Real::from_f64(value.to_f64())),
+        }
+    }
+}
+
+impl Domain for Simulated {
+    type Value = Real;
+
+    fn input(&self, name: &str, value: &Real) -> Result<Real> {
+        self.round(name, value.clone(), Rounding::TowardZero)
+    }
+
+    fn constant(&self, name: &str, value: &Real) -> Result<Real> {
+        self.round(name, value.clone(), Rounding::Nearest)
+    }
+
+    fn neg(&self, name: &str, opr: &Real) -> Result<Real> {
+        self.round(name, -opr, Rounding::Floor)
+    }
+
+    fn assign(&self, name: &str, opr: &Real) -> Result<Real> {
+        self.round(name, opr.clone(), Rounding::Floor)
+    }
+
+    fn binary(&self, name: &str, opr_type: &OprBinary, opr1: &Real, opr2: &Real) -> Result<Real> {
+        let exact = Exact.binary(name, opr_type, opr1, opr2)?;
+        self.round(name, exact, Rounding::Floor)
+    }
+}
+
+/// Evaluates every registered output of `program` on the given inputs.
+///
+/// Works on programs before and after [`crate::ir::unroll::unroll_ir`].
+/// `inputs` is keyed by the names passed to `add_input_*`; for unrolled
+/// programs the unrolled element names (e.g. `q_1`) are accepted as well.
+/// Outputs are keyed by the names passed to `register_*_output`.
+///
+/// # Examples
+///
+/// ```rust
+/// use indexmap::IndexMap;
+/// use roboprec::*;
+/// use roboprec::ir::{interpreter::{interpret, Exact, Value}, program::get_program};
+///
+/// let x = add_input_scalar("x", (Real::from_f64(0.0), Real::from_f64(1.0)), 0.5);
+/// let mut square = &x * &x;
+/// register_scalar_output(&mut square, "square");
+///
+/// let mut inputs = IndexMap::new();
+/// inputs.insert("x".to_string(), Value::Scalar(Real::from_f64(0.25)));
+/// let outputs = interpret(&get_program(), &inputs, &Exact).unwrap();
+/// assert_eq!(outputs["square"], Value::Scalar(Real::from_f64(0.0625)));
+/// ```
+pub fn interpret<D: Domain>(
+    program: &Program,
+    inputs: &IndexMap<String, Value<Real>>,
+    domain: &D,
+) -> Result<IndexMap<String, Value<D::Value>>> {
+    let env = evaluate(program, inputs, domain)?;
+
+    let mut outputs = IndexMap::new();
+    for (id, output) in program.get_outputs() {
+        // unrolled programs only keep the elements of vector and matrix outputs
+        let value = match env.get(id.name()) {
+            Some(value) => value.clone(),
+            None => {
+                let element = |element_id: &Identifier| lookup_scalar(&env, element_id);
+                match output {
+                    ProgramOutput::Scalar { info } => Value::Scalar(element(&info.id)?),
+                    ProgramOutput::Vector { info } => {
+                        Value::Vector(info.iter().map(|info| element(&info.id)).collect::<Result<_>>()?)
+                    }
+                    ProgramOutput::Matrix { info } => Value::Matrix(
+                        info.iter()
+                            .map(|row| row.iter().map(|info| element(&info.id)).collect::<Result<_>>())
+                            .collect::<Result<_>>()?,
+                    ),
+                }
+            }
+        };
+        outputs.insert(id.name().clone(), value);
+    }
+    Ok(outputs)
+}
+
+/// Evaluates `program` and returns the value of every variable, keyed by name.
+pub fn evaluate<D: Domain>(
+    program: &Program,
+    inputs: &IndexMap<String, Value<Real>>,
+    domain: &D,
+) -> Result<IndexMap<String, Value<D::Value>>> {
+    let inputs = expand_inputs(program, inputs);
+    let mut env: IndexMap<String, Value<D::Value>> = IndexMap::new();
+
+    for (id, input) in program.get_inputs() {
+        let value = inputs
+            .get(id.name())
+            .ok_or_else(|| anyhow::anyhow!("No value given for input {}", id.name()))?;
+        let name = id.name();
+        let matches = matches!(
+            (input, value),
+            (ProgramInput::Scalar { .. }, Value::Scalar(_))
+                | (ProgramInput::Vector { .. }, Value::Vector(_))
+                | (ProgramInput::Matrix { .. }, Value::Matrix(_))
+        );
+        let expected_len = match input {
+            ProgramInput::Scalar { .. } => 1,
+            ProgramInput::Vector { info } => info.len(),
+            ProgramInput::Matrix { info } => info.iter().map(|row| row.len()).sum(),
+        };
+        if !matches || value.elements().len() != expected_len {
+            anyhow::bail!("Input {} does not have the traced shape, got a {}", name, value.shape());
+        }
+        env.insert(name.clone(), map_result(value, |element| domain.input(name, element))?);
+    }
+
+    for expr in program.get_body() {
+        match expr {
+            Expr::Let { id, opr, .. } => {
+                let value = evaluate_opr(id, opr, &env, domain)?;
+                env.insert(id.name().clone(), value);
+            }
+        }
+    }
+    Ok(env)
+}
+
+/// Splits inputs given by their original name into the unrolled elements
+fn expand_inputs(program: &Program, inputs: &IndexMap<String, Value<Real>>) -> IndexMap<String, Value<Real>> {
+    let mut expanded = inputs.clone();
+    for (id, layout) in program.get_input_layouts() {
+        let Some(value) = inputs.get(id.name()) else {
+            continue;
+        };
+        match (layout, value) {
+            (InputLayout::Vector { ids }, Value::Vector(values)) if ids.len() == values.len() => {
+                for (element, value) in ids.iter().zip(values) {
+                    expanded.insert(element.name().clone(), Value::Scalar(value.clone()));
+                }
+            }
+            (InputLayout::Matrix { ids }, Value::Matrix(values)) => {
+                for (id_row, value_row) in ids.iter().zip(values) {
+                    for (element, value) in id_row.iter().zip(value_row) {
+                        expanded.insert(element.name().clone(), Value::Scalar(value.clone()));
+                    }
+                }
+            }
+            // scalars keep their name, shape mismatches are reported when binding
+            _ => (),
+        }
+    }
+    expanded
+}
+
+fn map_result<T, U>(value: &Value<T>, f: impl Fn(&T) -> Result<U>) -> Result<Value<U>> {
+    Ok(match value {
+        Value::Scalar(value) => Value::Scalar(f(value)?),
+        Value::Vector(values) => Value::Vector(values.iter().map(&f).collect::<Result<_>>()?),
+        Value::Matrix(rows) => Value::Matrix(
+            rows.iter()
+                .map(|row| row.iter().map(&f).collect::<Result<_>>())
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
+
+fn lookup<'a, T>(env: &'a IndexMap<String, Value<T>>, id: &Identifier) -> Result<&'a Value<T>> {
+    env.get(id.name())
+        .ok_or_else(|| anyhow::anyhow!("Variable {} used before definition", id.name()))
+}
+
+fn lookup_scalar<T: Clone>(env: &IndexMap<String, Value<T>>, id: &Identifier) -> Result<T> {
+    match lookup(env, id)? {
+        Value::Scalar(value) => Ok(value.clone()),
+        value => anyhow::bail!("Expected {} to be a scalar, got a {}", id.name(), value.shape()),
+    }
+}
+
+fn evaluate_opr<D: Domain>(
+    id: &Identifier,
+    opr: &Opr,
+    env: &IndexMap<String, Value<D::Value>>,
+    domain: &D,
+) -> Result<Value<D::Value>> {
+    let name = id.name().as_str();
+    Ok(match opr {
+        Opr::ConstantScalar { value } => Value::Scalar(domain.constant(name, value)?),
+        Opr::ConstantVector { value } => Value::Vector(
+            value
+                .iter()
+                .map(|element| domain.constant(name, element))
+                .collect::<Result<_>>()?,
+        ),
+        Opr::ConstantMatrix { value } => Value::Matrix(
+            value
+                .iter()
+                .map(|row| row.iter().map(|element| domain.constant(name, element)).collect::<Result<_>>())
+                .collect::<Result<_>>()?,
+        ),
+        Opr::ConstructScalar { id } => Value::Scalar(domain.assign(name, &lookup_scalar(env, id)?)?),
+        Opr::ConstructVector { ids } => Value::Vector(
+            ids.iter()
+                .map(|element| domain.assign(name, &lookup_scalar(env, element)?))
+                .collect::<Result<_>>()?,
+        ),
+        Opr::ConstructMatrix { ids } => Value::Matrix(
+            ids.iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|element| domain.assign(name, &lookup_scalar(env, element)?))
+                        .collect::<Result<_>>()
+                })
+                .collect::<Result<_>>()?,
+        ),
+        Opr::Unary { opr1, opr_type } => {
+            let value = lookup(env, opr1)?;
+            match opr_type {
+                OprUnary::Neg => map_result(value, |element| domain.neg(name, element))?,
+                OprUnary::Assign | OprUnary::AssignNoOpt => {
+                    map_result(value, |element| domain.assign(name, element))?
+                }
+                OprUnary::Index { index } => {
+                    let element = match (value, index.as_slice()) {
+                        (Value::Vector(values), [i]) => values.get(*i),
+                        (Value::Matrix(rows), [i, j]) => rows.get(*i).and_then(|row| row.get(*j)),
+                        _ => None,
+                    }
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Index {:?} is not valid for the {} {}", index, value.shape(), opr1.name())
+                    })?;
+                    Value::Scalar(domain.assign(name, element)?)
+                }
+                OprUnary::Transpose => match value {
+                    Value::Matrix(rows) => {
+                        let cols = rows.first().map_or(0, |row| row.len());
+                        Value::Matrix(
+                            (0..cols)
+                                .map(|j| rows.iter().map(|row| domain.assign(name, &row[j])).collect::<Result<_>>())
+                                .collect::<Result<_>>()?,
+                        )
+                    }
+                    _ => anyhow::bail!("Transpose is only supported for matrices, {} is a {}", opr1.name(), value.shape()),
+                },
+            }
+        }
+        Opr::Binary {
+            opr1,
+            opr2,
+            opr_type,
+        } => {
+            let (value1, value2) = (lookup(env, opr1)?, lookup(env, opr2)?);
+            match opr_type {
+                OprBinary::Cross => match (value1, value2) {
+                    (Value::Vector(a), Value::Vector(b)) if a.len() == 3 && b.len() == 3 => {
+                        let mul = |x: &D::Value, y: &D::Value| domain.binary(name, &OprBinary::Mul, x, y);
+                        let component = |i: usize, j: usize| -> Result<D::Value> {
+                            domain.binary(name, &OprBinary::Sub, &mul(&a[i], &b[j])?, &mul(&a[j], &b[i])?)
+                        };
+                        Value::Vector(vec![component(1, 2)?, component(2, 0)?, component(0, 1)?])
+                    }
+                    _ => anyhow::bail!("Cross product needs two 3D vectors, got {} and {}", value1.shape(), value2.shape()),
+                },
+                OprBinary::Dot => match (value1, value2) {
+                    (Value::Vector(a), Value::Vector(b)) if a.len() == b.len() && !a.is_empty() => {
+                        // same summation order as the unrolled program
+                        let mut sum = domain.binary(name, &OprBinary::Mul, &a[0], &b[0])?;
+                        for (x, y) in a.iter().zip(b).skip(1) {
+                            let product = domain.binary(name, &OprBinary::Mul, x, y)?;
+                            sum = domain.binary(name, &OprBinary::Add, &sum, &product)?;
+                        }
+                        Value::Scalar(sum)
+                    }
+                    _ => anyhow::bail!("Dot product needs two vectors of the same length, got {} and {}", value1.shape(), value2.shape()),
+                },
+                OprBinary::Add | OprBinary::Sub | OprBinary::Mul | OprBinary::Div => {
+                    let op = |x: &D::Value, y: &D::Value| domain.binary(name, opr_type, x, y);
+                    match (value1, value2) {
+                        (Value::Scalar(a), Value::Scalar(b)) => Value::Scalar(op(a, b)?),
+                        (Value::Vector(a), Value::Vector(b)) if a.len() == b.len() => {
+                            Value::Vector(a.iter().zip(b).map(|(x, y)| op(x, y)).collect::<Result<_>>()?)
+                        }
+                        (Value::Matrix(a), Value::Matrix(b))
+                            if a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.len() == y.len()) =>
+                        {
+                            Value::Matrix(
+                                a.iter()
+                                    .zip(b)
+                                    .map(|(x, y)| x.iter().zip(y).map(|(x, y)| op(x, y)).collect::<Result<_>>())
+                                    .collect::<Result<_>>()?,
+                            )
+                        }
+                        (Value::Vector(_) | Value::Matrix(_), Value::Scalar(b)) => map_result(value1, |x| op(x, b))?,
+                        // scalar - vector and scalar / vector are rejected by unrolling, too
+                        (Value::Scalar(a), Value::Vector(_) | Value::Matrix(_))
+                            if matches!(opr_type, OprBinary::Add | OprBinary::Mul) =>
+                        {
+                            map_result(value2, |y| op(a, y))?
+                        }
+                        _ => anyhow::bail!(
+                            "{:?} between a {} and a {} is not supported",
+                            opr_type,
+                            value1.shape(),
+                            value2.shape()
+                        ),
+                    }
+                }
+            }
+        }
+    })
+}
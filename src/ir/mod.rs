@@ -3,4 +3,4 @@ pub mod helper;
 pub mod identifier;
 pub mod program;
 pub mod unroll;
-pub mod precision;
\ No newline at end of file
+pub mod precision;pub mod interpreter;
@@ -6,7 +6,7 @@ use std::sync::Mutex;
 use crate::{
     Matrix, Scalar, Vector,
     analysis::{daisy::DaisyRange, real::Real},
-    ir::{expr::Expr, identifier::Identifier},
+    ir::{expr::Expr, helper::clear_all_names, identifier::Identifier},
 };
 
 // TODO: add error
@@ -36,14 +36,25 @@ pub enum ProgramOutput {
     Matrix { info: Vec<Vec<Output>> },
 }
 
+/// Shape of an input before unrolling, with the scalar identifiers that replaced its elements.
+#[derive(Debug, Clone)]
+pub enum InputLayout {
+    Scalar { id: Identifier },
+    Vector { ids: Vec<Identifier> },
+    Matrix { ids: Vec<Vec<Identifier>> },
+}
+
 #[derive(Debug, Clone)]
 pub struct Program {
     inputs: IndexMap<Identifier, ProgramInput>,
     outputs: IndexMap<Identifier, ProgramOutput>,
     body: Vec<Expr>,
+    /// Filled by `unroll_ir`, keyed by the original input identifiers
+    input_layouts: IndexMap<Identifier, InputLayout>,
 }
 
 #[allow(dead_code)]
+#[track_caller]
 pub fn register_scalar_output(output: &mut Scalar, name: &str) {
     *output = output.define(name.to_string());
     get_program().outputs.insert(
@@ -60,6 +71,7 @@ pub fn register_scalar_output(output: &mut Scalar, name: &str) {
 }
 
 #[allow(dead_code)]
+#[track_caller]
 pub fn register_vector_output(output: &mut Vector, name: &str) {
     *output = output.define(name.to_string());
     get_program().outputs.insert(
@@ -79,6 +91,7 @@ pub fn register_vector_output(output: &mut Vector, name: &str) {
     );
 }
 #[allow(dead_code)]
+#[track_caller]
 pub fn register_matrix_output(output: &mut Matrix, name: &str) {
     *output = output.define(name.to_string());
     get_program().outputs.insert(
@@ -166,6 +179,7 @@ impl Program {
             inputs: IndexMap::new(),
             outputs: IndexMap::new(),
             body: Vec::new(),
+            input_layouts: IndexMap::new(),
         }
     }
 
@@ -185,6 +199,10 @@ impl Program {
         &self.body
     }
 
+    pub fn get_input_layouts(&self) -> &IndexMap<Identifier, InputLayout> {
+        &self.input_layouts
+    }
+
     pub fn set_inputs(&mut self, inputs: &IndexMap<Identifier, ProgramInput>) {
         self.inputs = inputs.clone();
     }
@@ -196,6 +214,10 @@ impl Program {
     pub fn set_body(&mut self, body: &[Expr]) {
         self.body = body.to_vec();
     }
+
+    pub fn set_input_layouts(&mut self, input_layouts: &IndexMap<Identifier, InputLayout>) {
+        self.input_layouts = input_layouts.clone();
+    }
 }
 
 static PROGRAM: Lazy<Mutex<Program>> = Lazy::new(|| Mutex::new(Program::new()));
@@ -209,11 +231,22 @@ pub fn set_program(program: Program) {
     *prog = program;
 }
 
+/// Returns the traced program and starts a fresh one.
+///
+/// Used to trace several kernels one after the other; names are reset too, so
+/// every kernel gets the input and output names it asked for.
+pub fn take_program() -> Program {
+    let program = std::mem::replace(&mut *get_program(), Program::new());
+    clear_all_names();
+    program
+}
+
 pub fn clear_program() {
     let mut program = get_program();
     program.inputs.clear();
     program.outputs.clear();
     program.body.clear();
+    program.input_layouts.clear();
 }
 
 pub fn update_program_outputs(
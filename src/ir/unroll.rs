@@ -5,7 +5,7 @@ use crate::{
     ir::{
         expr::{Expr, Opr, OprBinary, OprUnary},
         identifier::{IdSize, Identifier, VarType},
-        program::{Output, Program, ProgramInput, ProgramOutput},
+        program::{InputLayout, Output, Program, ProgramInput, ProgramOutput},
     },
 };
 
@@ -17,6 +17,7 @@ pub fn unroll_ir(program: &Program) -> Program {
     let mut new_inputs: IndexMap<Identifier, ProgramInput> = IndexMap::new();
     let mut new_outputs: IndexMap<Identifier, ProgramOutput> = IndexMap::new();
     let mut new_body: Vec<Expr> = vec![];
+    let mut input_layouts: IndexMap<Identifier, InputLayout> = IndexMap::new();
 
     // the map to convert variables
     // TODO: Make this a normal map
@@ -28,6 +29,7 @@ pub fn unroll_ir(program: &Program) -> Program {
         match input {
             ProgramInput::Scalar { .. } => {
                 new_inputs.insert(id.clone(), input.clone());
+                input_layouts.insert(id.clone(), InputLayout::Scalar { id: id.clone() });
             }
             ProgramInput::Vector { info } => {
                 // now, we need to create new identifiers
@@ -51,6 +53,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                     all_ids.push(new_id);
                 }
                 assert_eq!(info.len(), size, "Vector input size mismatch");
+                input_layouts.insert(id.clone(), InputLayout::Vector { ids: all_ids.clone() });
                 unroll_vector_map.insert(id.clone(), all_ids);
             }
             ProgramInput::Matrix { info } => {
@@ -64,7 +67,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                 for (i, row) in info.iter().enumerate() {
                     let mut row_ids: Vec<Identifier> = vec![];
                     for (j, element) in row.iter().enumerate() {
-                        let new_name = format!("{:?}_{}_{}", curr_name, i, j);
+                        let new_name = format!("{}_{}_{}", curr_name, i, j);
                         let new_id = Identifier::new_scalar(&new_name);
 
                         new_inputs.insert(
@@ -82,6 +85,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                     info.iter().all(|row| row.len() == col_size),
                     "Matrix column size mismatch"
                 );
+                input_layouts.insert(id.clone(), InputLayout::Matrix { ids: all_ids.clone() });
                 unroll_matrix_map.insert(id.clone(), all_ids);
             }
         }
@@ -90,7 +94,7 @@ pub fn unroll_ir(program: &Program) -> Program {
     // now we need to start handling the body
     for expr in program.get_body() {
         match expr {
-            Expr::Let { id: result_id, opr } => {
+            Expr::Let { id: result_id, opr, location } => {
                 match opr {
                     Opr::ConstantScalar { .. } => new_body.push(expr.clone()),
                     Opr::ConstantVector { value: values } => {
@@ -108,6 +112,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                 opr: Opr::ConstantScalar {
                                     value: value.clone(),
                                 },
+                                location: *location,
                             };
                             new_body.push(new_expr);
                         }
@@ -129,6 +134,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                     opr: Opr::ConstantScalar {
                                         value: value.clone(),
                                     },
+                                    location: *location,
                                 };
                                 new_body.push(new_expr);
                             }
@@ -148,6 +154,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                 opr1: id.clone(),
                                 opr_type: OprUnary::Assign,
                             },
+                            location: *location,
                         };
                         new_body.push(new_expr);
                     }
@@ -166,6 +173,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                     opr1: id.clone(),
                                     opr_type: OprUnary::Assign,
                                 },
+                                location: *location,
                             };
                             new_body.push(new_expr);
                         }
@@ -187,6 +195,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                         opr1: id.clone(),
                                         opr_type: OprUnary::Assign,
                                     },
+                                    location: *location,
                                 };
                                 new_body.push(new_expr);
                             }
@@ -216,6 +225,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                                 opr1: corresponding_ids[index].clone(),
                                                 opr_type: OprUnary::Assign,
                                             },
+                                            location: *location,
                                         };
                                         new_body.push(new_expr);
                                     }
@@ -236,6 +246,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                                     opr1: id.clone(),
                                                     opr_type: opr_type.clone(), // assign or neg
                                                 },
+                                                location: *location,
                                             };
                                             new_body.push(new_expr);
                                         }
@@ -267,6 +278,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                                             opr1: id.clone(),
                                                             opr_type: OprUnary::Assign,
                                                         },
+                                                        location: *location,
                                                     },
                                                     OprUnary::Assign
                                                     | OprUnary::Neg
@@ -314,6 +326,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                                                 opr1: id.clone(),
                                                                 opr_type: opr_type.clone(), // assign or neg
                                                             },
+                                                            location: *location,
                                                         }
                                                     }
                                                     OprUnary::Transpose => {
@@ -341,6 +354,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                                     .clone(),
                                                 opr_type: OprUnary::Assign,
                                             },
+                                            location: *location,
                                         };
                                         new_body.push(new_expr);
                                     }
@@ -380,6 +394,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                         opr2: ids_2[2].clone(),
                                         opr_type: OprBinary::Mul,
                                     },
+                                    location: *location,
                                 };
                                 new_body.push(new_expr);
                                 let mul2 = Identifier::new_scalar("cross_temp2");
@@ -390,6 +405,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                         opr2: ids_2[1].clone(),
                                         opr_type: OprBinary::Mul,
                                     },
+                                    location: *location,
                                 };
                                 new_body.push(new_expr);
                                 let new_expr = Expr::Let {
@@ -399,6 +415,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                         opr2: mul2,
                                         opr_type: OprBinary::Sub,
                                     },
+                                    location: *location,
                                 };
                                 new_body.push(new_expr);
 
@@ -411,6 +428,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                         opr2: ids_2[0].clone(),
                                         opr_type: OprBinary::Mul,
                                     },
+                                    location: *location,
                                 };
                                 new_body.push(new_expr);
                                 let mul2 = Identifier::new_scalar("cross_temp4");
@@ -421,6 +439,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                         opr2: ids_2[2].clone(),
                                         opr_type: OprBinary::Mul,
                                     },
+                                    location: *location,
                                 };
                                 new_body.push(new_expr);
                                 let new_expr = Expr::Let {
@@ -430,6 +449,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                         opr2: mul2,
                                         opr_type: OprBinary::Sub,
                                     },
+                                    location: *location,
                                 };
                                 new_body.push(new_expr);
 
@@ -442,6 +462,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                         opr2: ids_2[1].clone(),
                                         opr_type: OprBinary::Mul,
                                     },
+                                    location: *location,
                                 };
                                 new_body.push(new_expr);
                                 let mul2 = Identifier::new_scalar("cross_temp6");
@@ -452,6 +473,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                         opr2: ids_2[0].clone(),
                                         opr_type: OprBinary::Mul,
                                     },
+                                    location: *location,
                                 };
                                 new_body.push(new_expr);
                                 let new_expr = Expr::Let {
@@ -461,6 +483,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                         opr2: mul2,
                                         opr_type: OprBinary::Sub,
                                     },
+                                    location: *location,
                                 };
                                 new_body.push(new_expr);
 
@@ -488,6 +511,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                             opr2: ids_2[i].clone(),
                                             opr_type: OprBinary::Mul,
                                         },
+                                        location: *location,
                                     };
 
                                     mult_ids.push(new_id);
@@ -509,6 +533,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                             opr2: item.clone(),
                                             opr_type: OprBinary::Add,
                                         },
+                                        location: *location,
                                     };
                                     new_body.push(new_expr);
                                     curr_sum_id = new_id;
@@ -521,6 +546,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                         opr1: curr_sum_id,
                                         opr_type: OprUnary::Assign,
                                     },
+                                    location: *location,
                                 };
                                 new_body.push(new_expr);
                             }
@@ -553,6 +579,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                                     opr2: ids_1[i].clone(),
                                                     opr_type: opr_type.clone(),
                                                 },
+                                                location: *location,
                                             };
                                             new_body.push(new_expr);
                                             all_ids.push(new_id);
@@ -586,6 +613,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                                         opr2: ids_1[i][j].clone(),
                                                         opr_type: opr_type.clone(),
                                                     },
+                                                    location: *location,
                                                 };
                                                 new_body.push(new_expr);
                                                 row_ids.push(new_id);
@@ -609,6 +637,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                                     opr2: opr2.clone(),
                                                     opr_type: opr_type.clone(),
                                                 },
+                                                location: *location,
                                             };
                                             new_body.push(new_expr);
                                             all_ids.push(new_id);
@@ -633,6 +662,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                                         opr2: opr2.clone(),
                                                         opr_type: opr_type.clone(),
                                                     },
+                                                    location: *location,
                                                 };
                                                 new_body.push(new_expr);
                                                 row_ids.push(new_id);
@@ -667,6 +697,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                                     opr2: id.clone(),
                                                     opr_type: opr_type.clone(),
                                                 },
+                                                location: *location,
                                             };
                                             new_body.push(new_expr);
                                             all_ids.push(new_id);
@@ -701,6 +732,7 @@ pub fn unroll_ir(program: &Program) -> Program {
                                                         opr2: id.clone(),
                                                         opr_type: opr_type.clone(),
                                                     },
+                                                    location: *location,
                                                 };
                                                 new_body.push(new_expr);
                                                 row_ids.push(new_id);
@@ -774,5 +806,6 @@ pub fn unroll_ir(program: &Program) -> Program {
     new_program.set_inputs(&new_inputs);
     new_program.set_outputs(&new_outputs);
     new_program.set_body(&new_body);
+    new_program.set_input_layouts(&input_layouts);
     new_program
 }
@@ -31,6 +31,7 @@
 //!             fractional_bits: -1  // Auto-optimize
 //!         },
 //!         output_dir: PathBuf::from("output/"),
+//!         ..Default::default()
 //!     };
 //!
 //!     analysis(config)?;
@@ -45,7 +46,10 @@
 //! - **[`Matrix`]**: 2D matrices for transformations and dynamics
 //! - **[`Precision`]**: Target numerical precision (Float32/64, Fixed-point)
 //! - **[`Config`]**: Analysis configuration
-//! - **[`analysis`]**: Main analysis entry point
+//! - **[`analysis`]**: Main analysis entry point, returning an [`AnalysisResult`]
+//! - **[`analysis_library`]**: Analyses several [`Kernel`]s into one generated C library
+//! - **[`validate_generated_c`]**: Runs the generated C code and compares it with the exact result
+//! - **[`validate_simulated`]**: The same check on the host, with a bit-accurate simulation of the C code
 //!
 //! ## Features
 //!
@@ -63,8 +67,12 @@ pub mod logger;
 pub mod codegen;
 
 pub use analysis::analysis::analysis;
+pub use analysis::diff::{AnalysisDiff, diff_analyses};
+pub use analysis::library::{Kernel, analysis_library};
+pub use analysis::result::AnalysisResult;
+pub use analysis::validate::{ValidationReport, validate_generated_c, validate_simulated};
 pub use analysis::real::Real;
-pub use config::Config;
+pub use config::{CInterface, Config, MonteCarlo, Sampling};
 pub use ir::precision::Precision;
 pub use ir::program::{
     register_scalar_output,
@@ -1,38 +1,85 @@
 mod algorithms;
 mod helpers;
 mod examples;
+mod registry;
 #[cfg(test)]
 mod tests;
 
 use anyhow::Result;
 use clap::Parser;
-use roboprec::{Config, Precision};
-use crate::examples::fk_7dof;
+use roboprec::{Config, MonteCarlo, Precision, diff_analyses};
+use std::path::PathBuf;
 use std::str::FromStr;
 
 // Re-export types so macros using $crate work in the binary
-pub use roboprec::{Vector, Matrix};
+pub use roboprec::{Scalar, Vector, Matrix};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     /// Precision format (e.g., Fixed16-8, Float32, Float64)
-    #[arg(short, long)]
-    precision: String,
+    #[arg(short, long, required_unless_present_any = ["diff", "list"])]
+    precision: Option<String>,
+
+    /// Built-in robot to analyze, see --list
+    #[arg(long, default_value = "panda")]
+    robot: String,
+
+    /// Kernel to analyze for the robot, see --list
+    #[arg(long, default_value = "fk")]
+    kernel: String,
+
+    /// List the built-in robots and kernels
+    #[arg(long)]
+    list: bool,
+
+    /// Also estimate the errors empirically from this many random inputs
+    #[arg(long)]
+    monte_carlo: Option<usize>,
+
+    /// Also report this many operations contributing most to each output error
+    #[arg(long)]
+    error_attribution: Option<usize>,
+
+    /// Compare the output bounds of two analysis runs (output directories or
+    /// JSON results) instead of running an analysis
+    #[arg(long, num_args = 2, value_names = ["OLD", "NEW"])]
+    diff: Option<Vec<PathBuf>>,
+
+    /// Relative growth of an error bound reported as a regression by --diff
+    #[arg(long, default_value_t = 0.0)]
+    threshold: f64,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let precision = Precision::from_str(&args.precision).map_err(|e| anyhow::anyhow!(e))?;
+    if args.list {
+        print!("{}", registry::list());
+        return Ok(());
+    }
+    if let Some(paths) = &args.diff {
+        let diff = diff_analyses(&paths[0], &paths[1], args.threshold)?;
+        print!("{}", diff);
+        if diff.has_regressions() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let robot = registry::find_robot(&args.robot)?;
+    let kernel = registry::find_kernel(&args.kernel)?;
+    let precision = args.precision.as_deref().unwrap_or_default();
+    let precision = Precision::from_str(precision).map_err(|e| anyhow::anyhow!(e))?;
 
     let config = Config {
         precision,
+        monte_carlo: args.monte_carlo.map(|samples| MonteCarlo {
+            samples,
+            ..Default::default()
+        }),
+        error_attribution: args.error_attribution,
         ..Default::default()
     };
 
-    fk_7dof(config)?;
-    // rnea_deriv_4dof(config)?;
-    // rnea_deriv_7dof(config)?;
-
-    Ok(())
+    registry::run(robot, kernel, config)
 }
@@ -0,0 +1,147 @@
+use anyhow::{Context, Result, bail};
+use roboprec::{Config, Real, Vector, analysis};
+
+use crate::{
+    algorithms::robots::{
+        indy7::{indy7, indy7_get_bounds},
+        panda::{panda, panda_get_bounds},
+        roarm_m2::{roarm_m2, roarm_m2_get_bounds},
+        roarm_m3::{roarm_m3, roarm_m3_get_bounds},
+        robot_info::RobotInfo,
+    },
+    examples::{A_RANGE, V_RANGE, angle_inputs, fk, jacobian_world, joint_inputs, rnea_deriv, rnea_tau},
+};
+
+/// A built-in robot, selected with `--robot`
+pub struct RobotEntry {
+    pub name: &'static str,
+    pub robot_info: fn() -> RobotInfo,
+    pub bounds: fn() -> Vec<(Real, Real)>,
+}
+
+/// A kernel that can be traced for any robot, selected with `--kernel`
+pub struct KernelEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub trace: Trace,
+}
+
+/// Traces a kernel from its inputs and registers its outputs
+pub enum Trace {
+    /// Takes the inputs `qcos` and `qsin`
+    Kinematic(fn(Vector, Vector, &RobotInfo)),
+    /// Takes the inputs `qcos`, `qsin`, `v` and `a`
+    Dynamic(fn(Vector, Vector, Vector, Vector, &RobotInfo)),
+}
+
+pub const ROBOTS: [RobotEntry; 4] = [
+    RobotEntry {
+        name: "panda",
+        robot_info: panda,
+        bounds: panda_get_bounds,
+    },
+    RobotEntry {
+        name: "indy7",
+        robot_info: indy7,
+        bounds: indy7_get_bounds,
+    },
+    RobotEntry {
+        name: "roarm_m2",
+        robot_info: roarm_m2,
+        bounds: roarm_m2_get_bounds,
+    },
+    RobotEntry {
+        name: "roarm_m3",
+        robot_info: roarm_m3,
+        bounds: roarm_m3_get_bounds,
+    },
+];
+
+pub const KERNELS: [KernelEntry; 4] = [
+    KernelEntry {
+        name: "fk",
+        description: "forward kinematics: placement, velocity and acceleration of every joint",
+        trace: Trace::Dynamic(fk),
+    },
+    KernelEntry {
+        name: "jacobian",
+        description: "kinematic Jacobian of the last link in the base frame",
+        trace: Trace::Kinematic(jacobian_world),
+    },
+    KernelEntry {
+        name: "rnea",
+        description: "inverse dynamics: joint torques",
+        trace: Trace::Dynamic(rnea_tau),
+    },
+    KernelEntry {
+        name: "rnea_derivatives",
+        description: "derivatives of the joint torques in a, v and q",
+        trace: Trace::Dynamic(rnea_deriv),
+    },
+];
+
+pub fn find_robot(name: &str) -> Result<&'static RobotEntry> {
+    match ROBOTS.iter().find(|robot| robot.name == name) {
+        Some(robot) => Ok(robot),
+        None => {
+            let names: Vec<&str> = ROBOTS.iter().map(|robot| robot.name).collect();
+            bail!("Unknown robot '{}', expected one of: {}", name, names.join(", "))
+        }
+    }
+}
+
+pub fn find_kernel(name: &str) -> Result<&'static KernelEntry> {
+    match KERNELS.iter().find(|kernel| kernel.name == name) {
+        Some(kernel) => Ok(kernel),
+        None => {
+            let names: Vec<&str> = KERNELS.iter().map(|kernel| kernel.name).collect();
+            bail!("Unknown kernel '{}', expected one of: {}", name, names.join(", "))
+        }
+    }
+}
+
+/// The robots with their joint counts and the kernels, as printed by `--list`
+pub fn list() -> String {
+    let mut text = String::from("Robots:\n");
+    for robot in &ROBOTS {
+        text += &format!("  {:<18} {} joints\n", robot.name, (robot.bounds)().len());
+    }
+    text += "Kernels:\n";
+    for kernel in &KERNELS {
+        text += &format!("  {:<18} {}\n", kernel.name, kernel.description);
+    }
+    text += &format!(
+        "Joint angles range over the robot's bounds, velocities over [{}, {}] and accelerations over [{}, {}].\n",
+        V_RANGE.0, V_RANGE.1, A_RANGE.0, A_RANGE.1
+    );
+    text
+}
+
+/// Traces `kernel` for `robot` and registers its outputs, with the input
+/// ranges built from the robot's joint bounds and joint types
+pub fn trace(robot: &RobotEntry, kernel: &KernelEntry) {
+    let robot_info = (robot.robot_info)();
+    let bounds = (robot.bounds)();
+    match kernel.trace {
+        Trace::Kinematic(trace) => {
+            let (qcos, qsin) = angle_inputs(&bounds, &robot_info.joint_types);
+            trace(qcos, qsin, &robot_info);
+        }
+        Trace::Dynamic(trace) => {
+            let (qcos, qsin, v, a) = joint_inputs(&bounds, &robot_info.joint_types);
+            trace(qcos, qsin, v, a, &robot_info);
+        }
+    }
+}
+
+/// Traces and analyzes `kernel` for `robot`. The generated code is named
+/// `{kernel}_{robot}`, e.g. `rnea_panda`.
+pub fn run(robot: &RobotEntry, kernel: &KernelEntry, config: Config) -> Result<()> {
+    trace(robot, kernel);
+    let config = Config {
+        kernel_name: format!("{}_{}", kernel.name, robot.name),
+        ..config
+    };
+    analysis(config).with_context(|| format!("Failed to analyze {} for {}", kernel.name, robot.name))?;
+    Ok(())
+}
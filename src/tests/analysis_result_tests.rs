@@ -0,0 +1,279 @@
+use indexmap::IndexMap;
+use roboprec::{
+    AnalysisResult, Config, Matrix, Precision, Real, Scalar, add_input_scalar, add_input_vector,
+    analysis::{
+        diff::{diff_analyses, load_output_bounds},
+        analysis::{DaisyResults, generated_files},
+        daisy::DaisyRange,
+        report::{AnalysisReport, program_hash},
+        result::StageTimings,
+    },
+    ir::{
+        program::{get_program, take_program, update_program_outputs},
+        unroll::unroll_ir,
+    },
+    register_matrix_output, register_scalar_output, register_vector_output,
+};
+
+use crate::tests::{
+    codegen_tests::{test_output_dir, uniform_precisions},
+    test_wrapper::run_default_test,
+};
+
+#[test]
+fn test_analysis_result() {
+    run_default_test(|| {
+        analysis_result();
+    });
+}
+
+#[test]
+fn test_analysis_diff() {
+    run_default_test(|| {
+        analysis_diff();
+    });
+}
+
+#[test]
+fn test_analysis_report() {
+    run_default_test(|| {
+        analysis_report();
+    });
+}
+
+#[test]
+fn test_bounds_from_handles() {
+    run_default_test(|| {
+        bounds_from_handles();
+    });
+}
+
+fn analysis_result() {
+    let range = (Real::from_f64(-1.0), Real::from_f64(1.0));
+    let q = add_input_vector("q", vec![range.clone(); 2], vec![0.5, 0.25]);
+    let s = add_input_scalar("s", range, 0.5);
+    let mut norm = &q.dot(&q) * &s;
+    let column = Matrix::from_vector("q_col", &q);
+    let mut outer = column.matmul(&column.transpose());
+    register_scalar_output(&mut norm, "norm");
+    register_matrix_output(&mut outer, "outer");
+
+    let config = Config {
+        precision: Precision::Fixed { total_bits: 32, fractional_bits: -1 },
+        kernel_name: String::from("outer"),
+        ..Default::default()
+    };
+    let timings = StageTimings {
+        daisy: std::time::Duration::from_millis(1500),
+        ..Default::default()
+    };
+    let (result, results) = fake_analysis(&config, timings);
+
+    // indexed by the user's names
+    assert_eq!(result.outputs.keys().collect::<Vec<_>>(), vec!["norm", "outer"]);
+    assert!(result.outputs["norm"].shape.is_empty());
+    assert_eq!(result.outputs["outer"].shape, vec![2, 2]);
+    assert_eq!(result.outputs["outer"].elements[2].name, "outer_1_0");
+
+    // and by unrolled element
+    let element = result.element("outer_1_0").unwrap();
+    let variable = element.variable.clone();
+    assert_eq!(result.element(&variable).unwrap().name, "outer_1_0");
+    assert_eq!(element.error, results.errors[&variable]);
+    assert_eq!(element.range.1, results.ranges[&variable].upper);
+    assert_eq!(element.precision, Some(Precision::Fixed { total_bits: 32, fractional_bits: 16 }));
+    assert_eq!(
+        result.outputs["outer"].max_error(),
+        result.outputs["outer"].elements.iter().map(|element| element.error).fold(0.0, f64::max)
+    );
+    assert!(result.element("missing").is_none());
+    assert_eq!(result.program.get_outputs().len(), 2);
+
+    let json: serde_json::Value = serde_json::from_str(&result.to_json().unwrap()).unwrap();
+    assert_eq!(json["kernel_name"], "outer");
+    assert_eq!(json["outputs"]["outer"]["elements"][2]["name"], "outer_1_0");
+    assert_eq!(json["outputs"]["outer"]["elements"][2]["variable"], variable.as_str());
+    assert_eq!(json["timings"]["daisy"], 1.5);
+    assert_eq!(json["files"]["c"], "output/codegen/C/outer.cpp");
+    assert_eq!(json["precisions"][variable.as_str()]["Fixed"]["fractional_bits"], 16);
+    assert!(json.get("program").is_none());
+}
+
+/// Analyses the program traced so far with fake Daisy results, where the
+/// variable `i` has range `[-i, i]` and error `i * 1e-6`
+fn fake_analysis(config: &Config, timings: StageTimings) -> (AnalysisResult, DaisyResults) {
+    let mut program = unroll_ir(&get_program());
+    let precisions = uniform_precisions(&program, Precision::Fixed { total_bits: 32, fractional_bits: 16 });
+    let mut results = DaisyResults {
+        ranges: IndexMap::new(),
+        errors: IndexMap::new(),
+        precisions: precisions.clone(),
+    };
+    for (i, name) in precisions.keys().enumerate() {
+        results.ranges.insert(name.clone(), DaisyRange { lower: -(i as f64), upper: i as f64 });
+        results.errors.insert(name.clone(), i as f64 * 1e-6);
+    }
+    update_program_outputs(&mut program, &results.ranges, &results.errors);
+    let result = AnalysisResult::new(program, &results, config, generated_files(config), timings);
+    (result, results)
+}
+
+fn bounds_from_handles() {
+    let range = (Real::from_f64(-1.0), Real::from_f64(1.0));
+    let q = add_input_vector("q", vec![range.clone(); 3], vec![0.5, 0.25, 0.125]);
+    let s = add_input_scalar("s", range, 0.5);
+    let mut scaled = &q * &s;
+    let mut norm = q.dot(&q);
+    let column = Matrix::from_vector("q_col", &q);
+    let mut outer = column.matmul(&column.transpose());
+    let unregistered = &norm + &s;
+    register_vector_output(&mut scaled, "scaled");
+    register_scalar_output(&mut norm, "norm");
+    register_matrix_output(&mut outer, "outer");
+
+    let config = Config {
+        kernel_name: String::from("handles"),
+        ..Default::default()
+    };
+    let (result, _) = fake_analysis(&config, StageTimings::default());
+
+    // per element, in the shape of the handle
+    let errors = scaled.error_bound(&result).unwrap();
+    let ranges = scaled.range(&result).unwrap();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[1], result.outputs["scaled"].elements[1].error);
+    assert_eq!(ranges[2], result.outputs["scaled"].elements[2].range);
+    assert_eq!(norm.error_bound(&result).unwrap(), result.outputs["norm"].elements[0].error);
+    assert_eq!(norm.range(&result).unwrap(), result.outputs["norm"].elements[0].range);
+    let errors = outer.error_bound(&result).unwrap();
+    assert_eq!((errors.len(), errors[0].len()), (3, 3));
+    assert_eq!(errors[1][2], result.element("outer_1_2").unwrap().error);
+    assert_eq!(outer.range(&result).unwrap()[2][0], result.element("outer_2_0").unwrap().range);
+    assert!(errors.iter().flatten().all(|error| *error > 0.0));
+
+    let error = unregistered.error_bound(&result).unwrap_err();
+    assert!(error.to_string().contains("is not an output of kernel handles"));
+}
+
+fn trace_scaled(factor: f64) {
+    let range = (Real::from_f64(-1.0), Real::from_f64(1.0));
+    let q = add_input_vector("q", vec![range.clone(); 2], vec![0.5, 0.25]);
+    let s = add_input_scalar("s", range, 0.5);
+    let mut scaled = &(&q * &s) * &Scalar::new("factor", factor);
+    register_vector_output(&mut scaled, "scaled");
+}
+
+fn analysis_report() {
+    trace_scaled(2.0);
+    let config = Config {
+        precision: Precision::Fixed { total_bits: 32, fractional_bits: -1 },
+        kernel_name: String::from("scaled"),
+        ..Default::default()
+    };
+    let (result, results) = fake_analysis(&config, StageTimings::default());
+    let report = AnalysisReport::new(&result.program, &results, &config).unwrap();
+
+    let inputs: Vec<&str> = report.inputs.iter().map(|input| input.name.as_str()).collect();
+    assert_eq!(inputs, vec!["q_0", "q_1", "s"]);
+    assert_eq!(report.inputs[0].declared_range, (-1.0, 1.0));
+    let outputs: Vec<Option<&str>> = report.outputs.iter().map(|output| output.output.as_deref()).collect();
+    assert_eq!(outputs, vec![Some("scaled_0"), Some("scaled_1")]);
+    assert_eq!(report.outputs[1].error, result.element("scaled_1").map(|element| element.error));
+    assert!(!report.intermediates.is_empty());
+    assert!(report.intermediates.iter().all(|variable| variable.output.is_none() && variable.error.is_some()));
+    assert_eq!(report.daisy.range_method, "interval");
+    assert!(report.intermediates[0].location.as_deref().unwrap().starts_with("src/tests/analysis_result_tests.rs:"));
+
+    // the hash only depends on what was traced
+    take_program();
+    trace_scaled(2.0);
+    let same = unroll_ir(&get_program());
+    assert_eq!(program_hash(&same), report.program_hash);
+    take_program();
+    trace_scaled(3.0);
+    assert_ne!(program_hash(&unroll_ir(&get_program())), report.program_hash);
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    assert_eq!(json["program_hash"], report.program_hash.as_str());
+    assert_eq!(json["outputs"][0]["output"], "scaled_0");
+    assert_eq!(json["daisy"]["error_method"], "interval");
+    assert_eq!(json["inputs"][2]["name"], "s");
+
+    let html = report.to_html();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert_eq!(html.matches("<table class=\"sortable\">").count(), 3);
+    assert_eq!(html.matches("<div class=\"bar\"").count(), 2);
+    assert!(html.contains("<td>scaled_1</td>"));
+    assert!(html.contains("<td>Fixed16-16</td>"));
+    assert!(html.contains("<th>Location</th>"));
+    assert!(!html.contains("src=\"http"));
+
+    let folder = test_output_dir("analysis_report");
+    report.write(&folder, "scaled_").unwrap();
+    assert!(folder.join("scaled_report.json").exists());
+    assert!(folder.join("scaled_report.html").exists());
+}
+
+fn analysis_diff() {
+    trace_scaled(2.0);
+    let config = Config {
+        kernel_name: String::from("scaled"),
+        ..Default::default()
+    };
+    let (mut result, results) = fake_analysis(&config, StageTimings::default());
+    let folder = test_output_dir("analysis_diff");
+    std::fs::remove_dir_all(&folder).ok();
+    std::fs::create_dir_all(folder.join("old/analysis_data/scaled")).unwrap();
+    std::fs::create_dir_all(folder.join("new")).unwrap();
+    result.write_json(folder.join("old/analysis_data/scaled/analysis_result.json")).unwrap();
+    let report = AnalysisReport::new(&result.program, &results, &config).unwrap();
+    report.write(&folder, "").unwrap();
+
+    // a report and a result directory carry the same bounds
+    let old = load_output_bounds(&folder.join("old")).unwrap();
+    assert_eq!(load_output_bounds(&folder.join("report.json")).unwrap(), old);
+    assert_eq!(old.keys().collect::<Vec<_>>(), vec!["scaled_0", "scaled_1"]);
+
+    // scaled_0 grows by 5%, scaled_1 shrinks
+    let elements = &mut result.outputs["scaled"].elements;
+    elements[0].error *= 1.05;
+    elements[1].error *= 0.5;
+    result.write_json(folder.join("new/analysis_result.json")).unwrap();
+
+    let diff = diff_analyses(&folder.join("old"), &folder.join("new"), 0.01).unwrap();
+    assert_eq!(diff.changes.len(), 2);
+    assert!((diff.changes[0].relative_change().unwrap() - 0.05).abs() < 1e-12);
+    assert!((diff.changes[1].relative_change().unwrap() + 0.5).abs() < 1e-12);
+    let regressions: Vec<&str> = diff.regressions().iter().map(|change| change.name.as_str()).collect();
+    assert_eq!(regressions, vec!["scaled_0"]);
+    let table = diff.to_string();
+    assert!(table.starts_with("output "));
+    assert!(table.contains("+5.00% !"));
+    assert!(table.contains("-50.00%\n"));
+    assert!(table.ends_with("1 of 2 outputs regressed by more than 1%\n"));
+
+    // within the threshold, and unchanged
+    assert!(!diff_analyses(&folder.join("old"), &folder.join("new"), 0.1).unwrap().has_regressions());
+    assert!(!diff_analyses(&folder.join("old"), &folder.join("old"), 0.0).unwrap().has_regressions());
+
+    // library results are prefixed with the kernel, so nothing matches here
+    let json = serde_json::to_string(&vec![&result]).unwrap();
+    std::fs::write(folder.join("library.json"), json).unwrap();
+    let diff = diff_analyses(&folder.join("old"), &folder.join("library.json"), 0.0).unwrap();
+    assert_eq!(diff.changes.len(), 4);
+    assert_eq!(diff.changes[2].name, "scaled.scaled_0");
+    assert!(diff.changes[0].new.is_none() && diff.changes[2].old.is_none());
+    assert!(!diff.has_regressions());
+    assert!(diff.to_string().contains("removed"));
+
+    let error = load_output_bounds(&folder.join("missing")).unwrap_err();
+    assert!(error.to_string().contains("Failed to read analysis result"));
+
+    // a second kernel in the same output directory keeps its own folder, so
+    // the output directory no longer names one run
+    std::fs::create_dir_all(folder.join("old/analysis_data/doubled")).unwrap();
+    result.write_json(folder.join("old/analysis_data/doubled/analysis_result.json")).unwrap();
+    let error = load_output_bounds(&folder.join("old")).unwrap_err().to_string();
+    assert!(error.contains("Several kernels") && error.contains("doubled, scaled"), "{}", error);
+    assert_eq!(load_output_bounds(&folder.join("old/analysis_data/scaled")).unwrap(), old);
+}
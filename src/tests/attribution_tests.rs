@@ -0,0 +1,103 @@
+use roboprec::{
+    MonteCarlo, Precision, Real, Scalar, add_input_scalar,
+    analysis::{
+        attribution::{attribute_errors, source_name, write_attribution_to_file},
+        daisy::DaisyRange,
+        monte_carlo::monte_carlo,
+    },
+    ir::{
+        program::{get_program, update_program_outputs},
+        unroll::unroll_ir,
+    },
+    register_scalar_output,
+};
+
+use crate::tests::{
+    codegen_tests::{test_output_dir, uniform_precisions},
+    test_wrapper::run_default_test,
+};
+
+#[test]
+fn test_error_attribution() {
+    run_default_test(|| {
+        error_attribution();
+    });
+}
+
+#[test]
+fn test_source_names() {
+    run_default_test(|| {
+        source_names();
+    });
+}
+
+fn error_attribution() {
+    // the product is stored with few fractional bits and then amplified
+    let range = (Real::from_f64(-1.0), Real::from_f64(2.0));
+    let x = add_input_scalar("x", range.clone(), 0.5);
+    let y = add_input_scalar("y", range, 0.5);
+    let product = &x * &y;
+    let mut result = &(&product * &Scalar::new("gain", 8.0)) + &x;
+    register_scalar_output(&mut result, "result");
+
+    let mut program = unroll_ir(&get_program());
+    let mut precisions = uniform_precisions(&program, Precision::Fixed { total_bits: 32, fractional_bits: 16 });
+    let coarse = product.id.name().clone();
+    precisions.insert(coarse.clone(), Precision::Fixed { total_bits: 32, fractional_bits: 6 });
+    let ranges = precisions
+        .keys()
+        .map(|name| (name.clone(), DaisyRange { lower: -32.0, upper: 32.0 }))
+        .collect();
+    let errors = precisions.keys().map(|name| (name.clone(), 1e-1)).collect();
+    update_program_outputs(&mut program, &ranges, &errors);
+
+    let attributions = attribute_errors(&program, &precisions).unwrap();
+    assert_eq!(attributions.len(), 1);
+    let attribution = &attributions[0];
+    assert_eq!(attribution.output, "result");
+    assert_eq!(attribution.bound, 1e-1);
+
+    // the coarse product dominates, amplified by the gain
+    let worst = &attribution.top(1)[0];
+    assert_eq!(worst.variable, coarse);
+    assert_eq!(worst.source, source_name(&coarse));
+    assert_eq!(worst.operation, "Mul");
+    assert_eq!(worst.roundoff, 2f64.powi(-6));
+    assert_eq!(worst.sensitivity, 8.0);
+    assert!(attribution.fraction(worst) > 0.9);
+    assert!(attribution.contributions.windows(2).all(|pair| pair[0].contribution >= pair[1].contribution));
+    // the constant is exact
+    assert!(attribution.contributions.iter().all(|contribution| contribution.operation != "constant"));
+    // x is an input and an operand of the final sum
+    let x = attribution.contributions.iter().find(|contribution| contribution.variable == "x").unwrap();
+    assert_eq!(x.operation, "input");
+    assert_eq!(x.sensitivity, 8.0 * 2.0 + 1.0);
+    let total: f64 = attribution.contributions.iter().map(|contribution| contribution.contribution).sum();
+    assert_eq!(attribution.first_order_bound, total);
+    assert_eq!(attribution.by_source()[0].0, worst.source);
+
+    // the first-order estimate covers what the generated code actually does
+    let settings = MonteCarlo {
+        samples: 300,
+        ..Default::default()
+    };
+    let observed = monte_carlo(&program, &precisions, &settings).unwrap().outputs[0].max_error;
+    assert!(observed > 0.0 && observed <= attribution.first_order_bound);
+
+    let file = test_output_dir("error_attribution").join("error_attribution.txt");
+    std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+    write_attribution_to_file(&attributions, 2, &file).unwrap();
+    let text = std::fs::read_to_string(&file).unwrap();
+    assert!(text.contains(&format!("result: first-order {:e}, bound 1e-1\n", attribution.first_order_bound)));
+    assert!(text.contains(&format!("  {} ({}, Mul): ", coarse, worst.source)));
+    assert!(text.contains(&format!("  by source:\n    {}: ", worst.source)));
+}
+
+fn source_names() {
+    assert_eq!(source_name("act_inv_fun_4_2_1"), "act_inv_fun");
+    assert_eq!(source_name("r_12_rhs_mult_3"), "rhs_mult");
+    assert_eq!(source_name("dot_sum_2"), "dot_sum");
+    assert_eq!(source_name("r_3"), "r");
+    assert_eq!(source_name("q_0"), "q");
+    assert_eq!(source_name("tau"), "tau");
+}
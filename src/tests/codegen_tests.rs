@@ -0,0 +1,430 @@
+use indexmap::IndexMap;
+use roboprec::{
+    analysis::daisy::DaisyRange,
+    CInterface, Config, Kernel, Matrix, Precision, Real, Scalar, add_input_matrix,
+    add_input_scalar, add_input_vector,
+    codegen::{
+        c::generate_c,
+        c_with_conversion::generate_c_with_conversion,
+        daisy_dsl::generate_daisy_dsl,
+        library::{LibraryKernel, generate_c_library},
+        test_harness::generate_c_test_harness,
+    },
+    ir::{
+        expr::Expr,
+        program::{Program, get_program, update_program_outputs},
+        unroll::unroll_ir,
+    },
+    register_matrix_output, register_scalar_output, register_vector_output, validate_generated_c,
+};
+
+use crate::tests::test_wrapper::run_default_test;
+
+#[test]
+fn test_array_interface_c() {
+    run_default_test(|| {
+        array_interface_c();
+    });
+}
+
+#[test]
+fn test_array_interface_c_with_conversion() {
+    run_default_test(|| {
+        array_interface_c_with_conversion();
+    });
+}
+
+#[test]
+fn test_scalar_interface_is_default() {
+    run_default_test(|| {
+        scalar_interface_is_default();
+    });
+}
+
+#[test]
+fn test_kernel_name() {
+    run_default_test(|| {
+        kernel_name();
+    });
+}
+
+#[test]
+fn test_invalid_kernel_name() {
+    run_default_test(|| {
+        invalid_kernel_name();
+    });
+}
+
+#[test]
+fn test_c_library() {
+    run_default_test(|| {
+        c_library();
+    });
+}
+
+#[test]
+fn test_c_library_duplicate_kernel_names() {
+    run_default_test(|| {
+        c_library_duplicate_kernel_names();
+    });
+}
+
+#[test]
+fn test_c_test_harness() {
+    run_default_test(|| {
+        c_test_harness();
+    });
+}
+
+#[test]
+fn test_source_locations() {
+    run_default_test(|| {
+        source_locations();
+    });
+}
+
+#[test]
+fn test_validate_generated_c() {
+    run_default_test(|| {
+        validate_generated_c_bounds();
+    });
+}
+
+/// Every unrolled variable gets the same precision, like a uniform Daisy run would
+pub(crate) fn uniform_precisions(program: &Program, precision: Precision) -> IndexMap<String, Precision> {
+    let mut precisions = IndexMap::new();
+    for id in program.get_inputs().keys() {
+        precisions.insert(id.name().clone(), precision.clone());
+    }
+    for expr in program.get_body() {
+        match expr {
+            Expr::Let { id, .. } => {
+                precisions.insert(id.name().clone(), precision.clone());
+            }
+        }
+    }
+    precisions
+}
+
+pub(crate) fn test_output_dir(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join("roboprec_tests").join(name)
+}
+
+fn trace_small_kernel() -> Program {
+    let range = (Real::from_f64(-1.0), Real::from_f64(1.0));
+    let q = add_input_vector("q", vec![range.clone(); 2], vec![0.5, 0.25]);
+    let m = add_input_matrix("m", vec![vec![range.clone(); 2]; 2], vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+    let s = add_input_scalar("s", range, 0.5);
+
+    let mut scaled = &q * &s;
+    let mut product = m.matmul(&Matrix::from_vector("q_col", &q));
+    register_vector_output(&mut scaled, "scaled");
+    register_matrix_output(&mut product, "product");
+
+    unroll_ir(&get_program())
+}
+
+fn array_interface_c() {
+    let program = trace_small_kernel();
+    let precisions = uniform_precisions(&program, Precision::Fixed { total_bits: 32, fractional_bits: 16 });
+    let config = Config {
+        output_dir: test_output_dir("array_interface_c"),
+        c_interface: CInterface::Array,
+        ..Default::default()
+    };
+    generate_c(&program, &precisions, &config).unwrap();
+
+    let code = std::fs::read_to_string(config.output_dir.join("codegen/C/codegen.cpp")).unwrap();
+    assert!(code.contains("    int32_t scaled[2];\n"));
+    assert!(code.contains("    int32_t product[2][1];\n"));
+    assert!(code.contains("const int32_t q[2],\n"));
+    assert!(code.contains("const int32_t m[2][2],\n"));
+    assert!(code.contains("    int32_t s\n) {\n"));
+    assert!(code.contains("    int32_t q_1 = q[1];\n"));
+    assert!(code.contains("    int32_t m_1_0 = m[1][0];\n"));
+    assert!(code.contains("    _output.scaled[1] = "));
+    assert!(code.contains("    _output.product[1][0] = "));
+    assert!(code.contains("    return _output;\n"));
+}
+
+fn array_interface_c_with_conversion() {
+    let program = trace_small_kernel();
+    let precisions = uniform_precisions(&program, Precision::Fixed { total_bits: 32, fractional_bits: 16 });
+    let config = Config {
+        output_dir: test_output_dir("array_interface_c_with_conversion"),
+        c_interface: CInterface::Array,
+        ..Default::default()
+    };
+    generate_c_with_conversion(&program, &precisions, &config).unwrap();
+
+    let code = std::fs::read_to_string(config.output_dir.join("codegen/C/codegen_with_conversion.cpp")).unwrap();
+    assert!(code.contains("    double scaled[2];\n"));
+    assert!(code.contains("const double _double_q[2],\n"));
+    assert!(code.contains("    int32_t q_1 = (int32_t)(_double_q[1] * (1 << 16));\n"));
+    assert!(code.contains("    int32_t s = (int32_t)(_double_s * (1 << 16));\n"));
+    assert!(code.contains("    _output.product[0][0] = ((double)"));
+}
+
+fn scalar_interface_is_default() {
+    let program = trace_small_kernel();
+    let precisions = uniform_precisions(&program, Precision::Float64);
+    let config = Config {
+        output_dir: test_output_dir("scalar_interface_is_default"),
+        ..Default::default()
+    };
+    generate_c(&program, &precisions, &config).unwrap();
+
+    let code = std::fs::read_to_string(config.output_dir.join("codegen/C/codegen.cpp")).unwrap();
+    assert!(code.contains("    double q_0,\n"));
+    assert!(code.contains("    double m_1_1,\n"));
+    assert!(code.contains("    return {\n"));
+}
+
+fn kernel_name() {
+    let program = trace_small_kernel();
+    let precisions = uniform_precisions(&program, Precision::Float32);
+    let config = Config {
+        output_dir: test_output_dir("kernel_name"),
+        kernel_name: String::from("small_kernel"),
+        ..Default::default()
+    };
+    generate_c(&program, &precisions, &config).unwrap();
+    generate_c_with_conversion(&program, &precisions, &config).unwrap();
+    generate_daisy_dsl(&program, &config).unwrap();
+
+    let code = std::fs::read_to_string(config.output_dir.join("codegen/C/small_kernel.cpp")).unwrap();
+    assert!(code.contains("} small_kernel_output_t;\n"));
+    assert!(code.contains("\nsmall_kernel_output_t small_kernel(\n"));
+    // location comments name this file
+    assert!(code.lines().filter(|line| !line.trim_start().starts_with("//")).all(|line| !line.contains("codegen")));
+
+    let code =
+        std::fs::read_to_string(config.output_dir.join("codegen/C/small_kernel_with_conversion.cpp")).unwrap();
+    assert!(code.contains("\nsmall_kernel_output_t small_kernel(\n"));
+
+    let code = std::fs::read_to_string(config.output_dir.join("codegen/daisy/small_kernel.scala")).unwrap();
+    assert!(code.contains("object small_kernel {\ndef small_kernel(\n"));
+}
+
+fn invalid_kernel_name() {
+    let program = trace_small_kernel();
+    let precisions = uniform_precisions(&program, Precision::Float32);
+    for name in ["", "1st_kernel", "fk-7dof", "../fk"] {
+        let config = Config {
+            output_dir: test_output_dir("invalid_kernel_name"),
+            kernel_name: String::from(name),
+            ..Default::default()
+        };
+        assert!(generate_c(&program, &precisions, &config).is_err());
+        assert!(generate_daisy_dsl(&program, &config).is_err());
+    }
+}
+
+/// Two kernels over an input with the same name, both using the constant 0.5
+fn trace_two_kernels() -> Vec<Kernel> {
+    let range = (Real::from_f64(-1.0), Real::from_f64(1.0));
+    let x = add_input_scalar("x", range.clone(), 0.5);
+    let mut half = &x * &Scalar::new("k", 0.5);
+    register_scalar_output(&mut half, "half");
+    let halve = Kernel::take("halve");
+
+    let x = add_input_scalar("x", range, 0.5);
+    let mut quarter = &(&x * &Scalar::new("k", 0.5)) * &Scalar::new("k", 0.5);
+    register_scalar_output(&mut quarter, "quarter");
+    let quarter_kernel = Kernel::take("quarter");
+
+    vec![halve, quarter_kernel]
+}
+
+fn c_library() {
+    let kernels = trace_two_kernels();
+    assert!(get_program().get_inputs().is_empty());
+
+    let programs: Vec<Program> = kernels.iter().map(|kernel| unroll_ir(&kernel.program)).collect();
+    let precisions: Vec<_> = programs
+        .iter()
+        .map(|program| uniform_precisions(program, Precision::Fixed { total_bits: 32, fractional_bits: 16 }))
+        .collect();
+    let library_kernels: Vec<LibraryKernel> = kernels
+        .iter()
+        .zip(programs.iter().zip(precisions.iter()))
+        .map(|(kernel, (program, precisions))| LibraryKernel {
+            name: &kernel.name,
+            program,
+            precisions,
+        })
+        .collect();
+    let config = Config {
+        output_dir: test_output_dir("c_library"),
+        kernel_name: String::from("scaling"),
+        ..Default::default()
+    };
+    generate_c_library(&library_kernels, &config).unwrap();
+
+    let code = std::fs::read_to_string(config.output_dir.join("codegen/C/scaling.cpp")).unwrap();
+    // one shared constant, declared once
+    assert_eq!(code.matches("static const int32_t scaling_const_0 = 32768;\n").count(), 1);
+    assert!(!code.contains("_const_1"));
+    assert!(code.contains("    int32_t k = scaling_const_0;\n"));
+    // both entry points, with the input names they were traced with
+    assert!(code.contains("\nhalve_output_t halve(\n    int32_t x\n) {\n"));
+    assert!(code.contains("    int32_t half;\n"));
+    assert!(code.contains("\nquarter_output_t quarter(\n    int32_t x\n) {\n"));
+    assert_eq!(code.matches("#include <math.h>").count(), 1);
+
+    let code =
+        std::fs::read_to_string(config.output_dir.join("codegen/C/scaling_with_conversion.cpp")).unwrap();
+    assert!(code.contains("static const int32_t scaling_const_0 = 32768;\n"));
+    assert!(code.contains("\nquarter_output_t quarter(\n    double _double_x\n) {\n"));
+}
+
+fn c_library_duplicate_kernel_names() {
+    let kernels = trace_two_kernels();
+    let program = unroll_ir(&kernels[0].program);
+    let precisions = uniform_precisions(&program, Precision::Float64);
+    let kernel = LibraryKernel {
+        name: "halve",
+        program: &program,
+        precisions: &precisions,
+    };
+    let config = Config {
+        output_dir: test_output_dir("c_library_duplicate_kernel_names"),
+        kernel_name: String::from("scaling"),
+        ..Default::default()
+    };
+    assert!(generate_c_library(&[kernel, kernel], &config).is_err());
+
+    // the library name would clash with the entry point
+    let config = Config {
+        kernel_name: String::from("halve"),
+        ..config
+    };
+    assert!(generate_c_library(&[kernel], &config).is_err());
+}
+
+/// Pretends every variable was analysed to lie in [-4, 4] with the given error
+fn set_uniform_bounds(program: &mut Program, precisions: &IndexMap<String, Precision>, error: f64) {
+    let ranges = precisions
+        .keys()
+        .map(|name| (name.clone(), DaisyRange { lower: -4.0, upper: 4.0 }))
+        .collect();
+    let errors = precisions.keys().map(|name| (name.clone(), error)).collect();
+    update_program_outputs(program, &ranges, &errors);
+}
+
+fn c_test_harness() {
+    let mut program = trace_small_kernel();
+    let precisions = uniform_precisions(&program, Precision::Fixed { total_bits: 32, fractional_bits: 16 });
+    set_uniform_bounds(&mut program, &precisions, 1e-3);
+
+    let config = Config {
+        output_dir: test_output_dir("c_test_harness"),
+        c_interface: CInterface::Array,
+        kernel_name: String::from("small_kernel"),
+        ..Default::default()
+    };
+    generate_c_with_conversion(&program, &precisions, &config).unwrap();
+    generate_c_test_harness(&program, &config, "small_kernel_with_conversion.cpp").unwrap();
+
+    let code = std::fs::read_to_string(config.output_dir.join("codegen/C/test_small_kernel.cpp")).unwrap();
+    assert!(code.contains("#include \"small_kernel_with_conversion.cpp\"\n"));
+    // the reference is the same program in double precision
+    assert!(code.contains("\nsmall_kernel_double_output_t small_kernel_double(\n"));
+    assert!(code.contains("    double q_1 = (double)_double_q[1];\n"));
+    assert!(code.contains("static const int small_kernel_n_outputs = 4;\n"));
+    assert!(code.contains("    \"product[1][0]\",\n"));
+    assert_eq!(code.matches("    1e-3,\n").count(), 4);
+    assert!(code.contains("        in_q[1] = small_kernel_uniform(-1e0, 1e0);\n"));
+    assert!(code.contains("        small_kernel_output_t out = small_kernel(in_q, in_m, in_s);\n"));
+    assert!(code.contains("        small_kernel_check(3, out.product[1][0], ref.product[1][0]);\n"));
+}
+
+fn validate_generated_c_bounds() {
+    let mut program = trace_small_kernel();
+    let precisions = uniform_precisions(&program, Precision::Fixed { total_bits: 32, fractional_bits: 16 });
+    set_uniform_bounds(&mut program, &precisions, 1e-3);
+    let config = Config {
+        output_dir: test_output_dir("validate_generated_c"),
+        ..Default::default()
+    };
+    generate_c_with_conversion(&program, &precisions, &config).unwrap();
+
+    let report = validate_generated_c(&program, &config, 200).unwrap();
+    assert_eq!(report.samples, 200);
+    assert_eq!(report.outputs.len(), 4);
+    assert_eq!(report.outputs[3].name, "product_1_0");
+    assert!(report.all_within_bounds());
+    // 16 fractional bits cannot be exact on random inputs
+    assert!(report.outputs.iter().all(|output| output.max_error > 0.0));
+
+    // a bound tighter than the fixed-point resolution must be violated
+    set_uniform_bounds(&mut program, &precisions, 1e-9);
+    let report = validate_generated_c(&program, &config, 200).unwrap();
+    assert!(!report.all_within_bounds());
+    assert!(report.worst().unwrap().max_error > 1e-9);
+}
+
+fn source_locations() {
+    let range = (Real::from_f64(-1.0), Real::from_f64(1.0));
+    let q = add_input_vector("q", vec![range.clone(); 2], vec![0.5, 0.25]);
+    let s = add_input_scalar("s", range, 0.5);
+    let scaled_line = line!() + 1;
+    let mut scaled = &q * &s;
+    let column = Matrix::from_vector("q_col", &q);
+    let matmul_line = line!() + 1;
+    let mut outer = column.matmul(&column.transpose());
+    let (scaled_name, outer_name) = (scaled.id.name().clone(), outer.id.name().clone());
+    let register_line = line!() + 1;
+    register_vector_output(&mut scaled, "scaled");
+    register_matrix_output(&mut outer, "outer");
+    let at = |line: u32| format!("{}:{}:", file!(), line);
+
+    // the traced expression points at the operator, not the library
+    let traced = get_program().get_body().clone();
+    let location = |name: &str| {
+        traced
+            .iter()
+            .find(|expr| matches!(expr, Expr::Let { id, .. } if id.name() == name))
+            .and_then(|expr| expr.location())
+            .unwrap()
+    };
+    assert!(location(&scaled_name).starts_with(&at(scaled_line)));
+    assert!(location(&outer_name).starts_with(&at(matmul_line)));
+    assert!(location(scaled.id.name()).starts_with(&at(register_line)));
+
+    // unrolling keeps the location of every element
+    let program = unroll_ir(&get_program());
+    let element = |name: String| {
+        program
+            .get_body()
+            .iter()
+            .find(|expr| matches!(expr, Expr::Let { id, .. } if *id.name() == name))
+            .and_then(|expr| expr.location())
+            .unwrap()
+    };
+    assert!(element(format!("{}_1", scaled_name)).starts_with(&at(scaled_line)));
+    assert!(element(format!("{}_1_0", outer_name)).starts_with(&at(matmul_line)));
+    // every dot product of the matrix product
+    let products: Vec<String> = program
+        .get_body()
+        .iter()
+        .filter(|expr| matches!(expr, Expr::Let { id, .. } if id.name().contains("dot")))
+        .map(|expr| expr.location().unwrap())
+        .collect();
+    assert!(!products.is_empty());
+    assert!(products.iter().all(|location| location.starts_with(&at(matmul_line))));
+
+    // and the generated code groups statements under one comment per line
+    let precisions = uniform_precisions(&program, Precision::Fixed { total_bits: 32, fractional_bits: 16 });
+    let config = Config {
+        output_dir: test_output_dir("source_locations"),
+        ..Default::default()
+    };
+    generate_c(&program, &precisions, &config).unwrap();
+    generate_daisy_dsl(&program, &config).unwrap();
+    let code = std::fs::read_to_string(config.output_dir.join("codegen/C/codegen.cpp")).unwrap();
+    assert_eq!(code.matches(&format!("    // {}", at(scaled_line))).count(), 1);
+    assert!(code.contains(&format!("    // {}", at(matmul_line))));
+    let code = std::fs::read_to_string(config.output_dir.join("codegen/daisy/codegen.scala")).unwrap();
+    assert!(code.contains(&format!("\n// {}", at(scaled_line))));
+}
@@ -0,0 +1,128 @@
+use std::f64::consts::FRAC_PI_2;
+
+use indexmap::IndexMap;
+use roboprec::{
+    Real, add_input_vector,
+    ir::{
+        interpreter::{Exact, Value, interpret},
+        program::{get_program, take_program},
+        unroll::unroll_ir,
+    },
+    register_vector_output,
+};
+
+use crate::{
+    algorithms::{
+        forward_kinematics::forward_kinematics,
+        robots::{
+            dh::{DhConvention, DhParameters, DhTable, LinkInertial},
+            panda::{panda, panda_get_bounds},
+            robot_info::RobotInfo,
+        },
+    },
+    tests::{robot_fixtures::fk_outputs, test_wrapper::run_default_test},
+};
+
+#[test]
+fn test_dh_panda() {
+    run_default_test(|| {
+        dh_panda();
+    });
+}
+
+#[test]
+fn test_dh_exact_twists() {
+    run_default_test(|| {
+        dh_exact_twists();
+    });
+}
+
+const LINK: LinkInertial = LinkInertial {
+    mass: 1.0,
+    lever: [0.0; 3],
+    inertia: [[0.01, 0.0, 0.0], [0.0, 0.02, 0.0], [0.0, 0.0, 0.03]],
+};
+
+fn row(a: f64, alpha: f64, d: f64, theta_offset: f64, bounds: (f64, f64)) -> DhParameters {
+    DhParameters {
+        a,
+        alpha,
+        d,
+        theta_offset,
+        bounds,
+    }
+}
+
+fn dh_panda() {
+    // the modified DH table of Franka's documentation
+    let bounds: Vec<(f64, f64)> = panda_get_bounds().iter().map(|(low, high)| (low.to_f64(), high.to_f64())).collect();
+    let parameters = [
+        (0.0, 0.0, 0.333),
+        (0.0, -FRAC_PI_2, 0.0),
+        (0.0, FRAC_PI_2, 0.316),
+        (0.0825, FRAC_PI_2, 0.0),
+        (-0.0825, -FRAC_PI_2, 0.384),
+        (0.0, FRAC_PI_2, 0.0),
+        (0.088, FRAC_PI_2, 0.0),
+    ];
+    let table = DhTable {
+        convention: DhConvention::Modified,
+        joints: parameters
+            .iter()
+            .zip(&bounds)
+            .map(|(&(a, alpha, d), &bounds)| row(a, alpha, d, 0.0, bounds))
+            .collect(),
+    };
+    let model = table.model("panda", &[LINK; 7]);
+    assert_eq!(model.bounds(), panda_get_bounds());
+    // the same joint frames as the URDF
+    assert_eq!(model.joints[2].translation, [0.0, -0.316, 0.0]);
+    assert_eq!(model.joints[4].translation, [-0.0825, 0.384, 0.0]);
+    assert_eq!(model.joints[1].rpy, [-FRAC_PI_2, 0.0, 0.0]);
+    assert_eq!(model.joints[6].inertia, LINK.inertia);
+
+    // the hand-written robot approximates the twists
+    let expected = fk_outputs(panda());
+    let dh = fk_outputs(RobotInfo::from_dh(&table, &[LINK; 7]));
+    for (expected, dh) in expected.iter().zip(&dh) {
+        assert!((expected - dh).abs() < 1e-5, "{} != {}", expected, dh);
+    }
+}
+
+fn dh_exact_twists() {
+    let table = DhTable {
+        convention: DhConvention::Standard,
+        joints: vec![
+            row(0.5, FRAC_PI_2, 0.25, 0.0, (-1.0, 1.0)),
+            row(0.3, 0.0, 0.1, FRAC_PI_2, (-1.0, 1.0)),
+            row(0.0, 0.0, 0.0, 0.0, (-1.0, 1.0)),
+        ],
+    };
+    // link frames sit at the end of their links
+    let model = table.model("arm", &[LINK; 3]);
+    assert_eq!(model.joints[0].lever, [0.5, 0.0, 0.25]);
+    assert_eq!(model.joints[1].lever, [0.0, 0.3, 0.1]);
+
+    let range = vec![(Real::from_f64(-1.0), Real::from_f64(1.0)); 3];
+    let qsin = add_input_vector("qsin", range.clone(), vec![0.0; 3]);
+    let qcos = add_input_vector("qcos", range.clone(), vec![1.0; 3]);
+    let v = add_input_vector("v", range.clone(), vec![0.0; 3]);
+    let a = add_input_vector("a", range, vec![0.0; 3]);
+    let mut result = forward_kinematics(qcos, qsin, v, a, &RobotInfo::from_dh(&table, &[LINK; 3]));
+    register_vector_output(&mut result.omi_translations[2], "translation");
+    let program = unroll_ir(&get_program());
+    take_program();
+
+    let inputs: IndexMap<String, Value<Real>> = program
+        .get_inputs()
+        .keys()
+        .map(|id| {
+            let value = if id.name().starts_with("qcos") { 1.0 } else { 0.0 };
+            (id.name().clone(), Value::Scalar(Real::from_f64(value)))
+        })
+        .collect();
+    let outputs = interpret(&program, &inputs, &Exact).unwrap();
+    // cos(pi/2) in floating point would leave traces of 6e-17 in every element
+    let expected = vec![Real::from_f64(0.5), Real::from_f64(-0.1), Real::from_f64(0.25) + Real::from_f64(0.3)];
+    assert_eq!(outputs["translation"], Value::Vector(expected));
+}
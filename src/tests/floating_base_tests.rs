@@ -0,0 +1,151 @@
+use indexmap::IndexMap;
+use roboprec::{Real, Vector, add_input_vector, ir::interpreter::Value, register_vector_output};
+
+use crate::{
+    algorithms::{
+        rnea::rnea_floating_base,
+        robots::{
+            model::{BaseModel, RobotModel},
+            robot_info::{JointType, RobotInfo},
+            urdf::parse_urdf,
+        },
+    },
+    tests::{
+        robot_fixtures::{Z, arms, assert_close, evaluate, evaluate_with, joint, rnea_kernel, robot},
+        test_wrapper::run_default_test,
+    },
+};
+
+#[test]
+fn test_floating_base_at_rest() {
+    run_default_test(|| {
+        floating_base_at_rest();
+    });
+}
+
+#[test]
+fn test_floating_base_moving() {
+    run_default_test(|| {
+        floating_base_moving();
+    });
+}
+
+#[test]
+fn test_floating_base_import() {
+    run_default_test(|| {
+        floating_base_import();
+    });
+}
+
+const BASE: BaseModel = BaseModel {
+    mass: 3.0,
+    lever: [0.05, 0.02, 0.1],
+    inertia: [[0.1, 0.01, 0.0], [0.01, 0.2, 0.02], [0.0, 0.02, 0.3]],
+};
+
+/// The arms of `arms` as two legs on a trunk, the second one with two joints
+fn legs() -> RobotModel {
+    let [_, left, right, foot] = arms();
+    let mut model = robot(vec![left, right, foot]);
+    model.base = BASE;
+    model.joints[1].parent = None;
+    model
+}
+
+/// `rnea_floating_base` for `legs`, with the base state as inputs
+fn floating_kernel(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot: &RobotInfo) {
+    let range = vec![(Real::from_f64(-10.0), Real::from_f64(10.0)); 6];
+    let base_v = add_input_vector("base_v", range.clone(), vec![0.0; 6]);
+    let base_a_gf = add_input_vector("base_a_gf", range, vec![0.0; 6]);
+    let (mut wrench, mut tau) = rnea_floating_base(qcos, qsin, v, a, base_v, base_a_gf, robot, &legs().base_info());
+    register_vector_output(&mut wrench, "wrench");
+    register_vector_output(&mut tau, "tau");
+}
+
+/// Evaluates `floating_kernel` in double precision, returning the base wrench
+/// and the joint torques
+fn floating(q: &[f64], v: &[f64], a: &[f64], base_v: [f64; 6], base_a_gf: [f64; 6]) -> (Vec<f64>, Vec<f64>) {
+    let vector = |values: [f64; 6]| Value::Vector(values.map(Real::from_f64).to_vec());
+    let inputs = IndexMap::from([
+        ("base_v".to_string(), vector(base_v)),
+        ("base_a_gf".to_string(), vector(base_a_gf)),
+    ]);
+    let mut outputs = evaluate_with(&legs(), q, v, a, &|_| {}, inputs, floating_kernel);
+    (outputs.swap_remove("wrench").unwrap(), outputs.swap_remove("tau").unwrap())
+}
+
+fn floating_base_at_rest() {
+    // a trunk held level carries the legs like a fixed base
+    let model = legs();
+    let (q, v, a) = ([0.3, -0.5, 0.8], [0.4, -0.3, 0.2], [0.1, 0.7, -0.4]);
+    let (wrench, tau) = floating(&q, &v, &a, [0.0; 6], [0.0, 0.0, 9.81, 0.0, 0.0, 0.0]);
+    assert_close(&evaluate(&model, &q, &v, &a, rnea_kernel)["tau"], &tau);
+
+    // standing still, the base wrench holds up the weight of the whole robot
+    let (wrench_still, _) = floating(&q, &[0.0; 3], &[0.0; 3], [0.0; 6], [0.0, 0.0, 9.81, 0.0, 0.0, 0.0]);
+    assert_close(&[0.0, 0.0, 6.0 * 9.81], &wrench_still[..3]);
+    assert!(wrench.iter().zip(&wrench_still).any(|(moving, still)| (moving - still).abs() > 1e-3));
+}
+
+fn floating_base_moving() {
+    // the trunk sliding along x and turning about z moves like the link of a
+    // fixed chain of a prismatic x and a revolute z joint at their zero position
+    let mut slide = joint([0.0; 3], [0.0; 3], JointType::Prismatic([1.0, 0.0, 0.0]), [0.0; 3], [[0.0; 3]; 3]);
+    slide.mass = 0.0;
+    let mut turn = joint([0.0; 3], [0.0; 3], Z, BASE.lever, BASE.inertia);
+    turn.mass = BASE.mass;
+    let mut chain = legs();
+    for joint in &mut chain.joints {
+        joint.parent = Some(joint.parent.map_or(1, |parent| parent + 2));
+    }
+    chain.joints.splice(0..0, [slide, turn]);
+    chain.joints[0].parent = None;
+    chain.joints[1].parent = Some(0);
+
+    let (q, v, a) = ([0.3, -0.5, 0.8], [0.4, -0.3, 0.2], [0.1, 0.7, -0.4]);
+    let (vx, w, ax, aw) = (0.4, 0.7, 0.3, -0.5);
+    let chain_state = |base: [f64; 2], legs: [f64; 3]| [&base[..], &legs[..]].concat();
+    let (chain_q, chain_v, chain_a) = (chain_state([0.0, 0.0], q), chain_state([vx, w], v), chain_state([ax, aw], a));
+    let expected = &evaluate(&chain, &chain_q, &chain_v, &chain_a, rnea_kernel)["tau"];
+
+    // the spatial acceleration of the link, with v x (S w) = (-vx w, 0, 0) and gravity
+    let base_v = [vx, 0.0, 0.0, 0.0, 0.0, w];
+    let base_a_gf = [ax, -vx * w, 9.81, 0.0, 0.0, aw];
+    let (wrench, tau) = floating(&q, &v, &a, base_v, base_a_gf);
+    assert_close(&[expected[0], expected[1]], &[wrench[0], wrench[5]]);
+    assert_close(&expected[2..], &tau);
+}
+
+fn floating_base_import() {
+    // the trunk and a sensor fixed to it make up the base
+    let urdf = r#"
+        <robot name="legs">
+          <link name="trunk">
+            <inertial>
+              <mass value="3.0"/>
+              <inertia ixx="0.1" ixy="0" ixz="0" iyy="0.2" iyz="0" izz="0.3"/>
+            </inertial>
+          </link>
+          <link name="sensor">
+            <inertial>
+              <mass value="1.0"/>
+              <inertia ixx="0" ixy="0" ixz="0" iyy="0" iyz="0" izz="0"/>
+            </inertial>
+          </link>
+          <link name="leg"/>
+          <joint name="mount" type="fixed">
+            <origin xyz="0.4 0 0"/>
+            <parent link="trunk"/>
+            <child link="sensor"/>
+          </joint>
+          <joint name="hip" type="continuous">
+            <parent link="trunk"/>
+            <child link="leg"/>
+          </joint>
+        </robot>"#;
+    let model = parse_urdf(urdf).unwrap();
+    assert_eq!(model.base.mass, 4.0);
+    assert_close(&[0.1, 0.0, 0.0], &model.base.lever);
+    // both bodies are moved to the common center of mass
+    assert_close(&[0.1, 0.2 + 0.12, 0.3 + 0.12], &[0, 1, 2].map(|i| model.base.inertia[i][i]));
+}
@@ -0,0 +1,151 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use roboprec::{Scalar, Vector, register_matrix_output, register_vector_output};
+
+use crate::{
+    algorithms::{
+        rnea::rnea_with_forces,
+        rnea_derivatives::rneaderivatives_with_forces,
+        robots::{
+            model::RobotModel,
+            robot_info::{JointType, RobotInfo},
+        },
+    },
+    tests::{
+        robot_fixtures::{INERTIA, arm, assert_close, assert_kernel_derivatives, evaluate, joint, rnea_kernel},
+        test_wrapper::run_default_test,
+    },
+};
+
+#[test]
+fn test_gravity_mounting() {
+    run_default_test(|| {
+        gravity_mounting();
+    });
+}
+
+#[test]
+fn test_gravity_external_forces() {
+    run_default_test(|| {
+        gravity_external_forces();
+    });
+}
+
+#[test]
+fn test_gravity_derivatives() {
+    run_default_test(|| {
+        gravity_derivatives();
+    });
+}
+
+/// `arm` with its base joint at `translation`, turned by `rpy`
+fn mounted(translation: [f64; 3], rpy: [f64; 3]) -> RobotModel {
+    let mut model = arm();
+    model.joints[0].translation = translation;
+    model.joints[0].rpy = rpy;
+    model
+}
+
+fn rnea_under(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot: &RobotInfo, gravity: [f64; 3], f_ext: Option<&[Vector]>) {
+    let gravity = Vector::new("gravity", gravity.to_vec());
+    let mut tau = rnea_with_forces(qcos, qsin, v, a, &gravity, f_ext, robot);
+    register_vector_output(&mut tau, "tau");
+}
+
+fn upside_down_kernel(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot: &RobotInfo) {
+    rnea_under(qcos, qsin, v, a, robot, [0.0, 0.0, 9.81], None);
+}
+
+fn sideways_kernel(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot: &RobotInfo) {
+    rnea_under(qcos, qsin, v, a, robot, [9.81, 0.0, 0.0], None);
+}
+
+fn weightless_kernel(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot: &RobotInfo) {
+    rnea_under(qcos, qsin, v, a, robot, [0.0; 3], None);
+}
+
+/// The forces that hold up every link at its center of mass, in the joint
+/// frames of a robot whose joint frames are all aligned with the base
+fn supports(robot: &RobotInfo) -> Vec<Vector> {
+    let (zero, g) = (Scalar::new("zero", 0.0), Scalar::new("g", 9.81));
+    (0..robot.n_joints)
+        .map(|i| {
+            let weight = &robot.masses.at(i) * &g;
+            let lever = &robot.levers[i];
+            // lever x (0, 0, weight)
+            let (torque_x, torque_y) = (&lever.at(1) * &weight, -&(&lever.at(0) * &weight));
+            Vector::from_scalars("support", vec![&zero, &zero, &weight, &torque_x, &torque_y, &zero])
+        })
+        .collect()
+}
+
+fn supported_kernel(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot: &RobotInfo) {
+    rnea_under(qcos, qsin, v, a, robot, [0.0, 0.0, -9.81], Some(&supports(robot)));
+}
+
+const GRAVITY: [f64; 3] = [1.0, -2.0, -9.0];
+
+fn pushes(robot: &RobotInfo) -> Vec<Vector> {
+    (0..robot.n_joints)
+        .map(|i| {
+            let i = i as f64;
+            Vector::new("push", vec![0.5 - i, -1.0, 2.0 + i, 0.1, 0.3 * i, -0.2])
+        })
+        .collect()
+}
+
+fn pushed_rnea_kernel(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot: &RobotInfo) {
+    rnea_under(qcos, qsin, v, a, robot, GRAVITY, Some(&pushes(robot)));
+}
+
+fn pushed_derivatives_kernel(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot: &RobotInfo) {
+    let gravity = Vector::new("gravity", GRAVITY.to_vec());
+    let (mut da, mut dv, mut dq, _) = rneaderivatives_with_forces(qcos, qsin, v, a, &gravity, Some(&pushes(robot)), robot);
+    register_matrix_output(&mut da, "da");
+    register_matrix_output(&mut dv, "dv");
+    register_matrix_output(&mut dq, "dq");
+}
+
+fn gravity_mounting() {
+    let (q, v, a) = ([0.3, -0.5, 0.8], [0.4, -0.3, 0.2], [0.1, 0.7, -0.4]);
+    let upright = arm();
+
+    // hanging from the ceiling, gravity points up in the base frame
+    let flipped = mounted([0.0, 0.0, -0.3], [PI, 0.0, 0.0]);
+    let expected = &evaluate(&flipped, &q, &v, &a, rnea_kernel)["tau"];
+    assert_close(expected, &evaluate(&upright, &q, &v, &a, upside_down_kernel)["tau"]);
+
+    // mounted on a wall, gravity points along the base x axis
+    let turned = mounted([0.3, 0.0, 0.0], [0.0, FRAC_PI_2, 0.0]);
+    let expected = &evaluate(&turned, &q, &v, &a, rnea_kernel)["tau"];
+    assert_close(expected, &evaluate(&upright, &q, &v, &a, sideways_kernel)["tau"]);
+
+    // holding still, the arm needs the opposite torques upside-down
+    let holding = &evaluate(&upright, &q, &[0.0; 3], &[0.0; 3], rnea_kernel)["tau"];
+    let hanging = &evaluate(&upright, &q, &[0.0; 3], &[0.0; 3], upside_down_kernel)["tau"];
+    assert_close(&holding.iter().map(|tau| -tau).collect::<Vec<f64>>(), hanging);
+    assert!(holding.iter().any(|tau| tau.abs() > 0.1));
+}
+
+fn gravity_external_forces() {
+    // without the roll of the last joint, the joint frames are aligned with the
+    // base at q = 0, so supporting every link against its weight leaves the
+    // torques of a weightless arm
+    let mut model = arm();
+    model.joints[2].rpy = [0.0; 3];
+    let (q, v, a) = ([0.0; 3], [0.4, -0.3, 0.2], [0.1, 0.7, -0.4]);
+    let expected = &evaluate(&model, &q, &v, &a, weightless_kernel)["tau"];
+    assert_close(expected, &evaluate(&model, &q, &v, &a, supported_kernel)["tau"]);
+
+    // and holding still takes no torque at all
+    let tau = &evaluate(&model, &q, &[0.0; 3], &[0.0; 3], supported_kernel)["tau"];
+    assert_close(&[0.0; 3], tau);
+}
+
+fn gravity_derivatives() {
+    let mut model = mounted([0.0, 0.0, 0.3], [0.2, 0.0, 0.0]);
+    model.joints.push(joint([0.0, 0.0, 0.1], [0.0, 0.3, 0.0], JointType::Prismatic([0.0, 0.0, 1.0]), [0.05, 0.0, 0.0], INERTIA));
+    model.joints[3].parent = Some(2);
+    let state = [[0.3, -0.5, 0.8, 0.1], [0.4, -0.3, 0.2, 0.5], [0.1, 0.7, -0.4, 0.2]];
+    assert_kernel_derivatives(&model, state, pushed_rnea_kernel, pushed_derivatives_kernel);
+}
@@ -0,0 +1,175 @@
+use indexmap::IndexMap;
+use roboprec::{
+    Precision, Real, add_input_scalar, add_input_vector,
+    ir::{
+        interpreter::{Double, Exact, Simulated, Value, interpret},
+        program::get_program,
+        unroll::unroll_ir,
+    },
+    register_matrix_output, register_scalar_output, register_vector_output,
+};
+
+use crate::{
+    algorithms::{
+        forward_kinematics::{FKResult, forward_kinematics},
+        robots::roarm_m2::roarm_m2,
+    },
+    tests::test_wrapper::run_default_test,
+};
+
+#[test]
+fn test_interpret_matches_traced_values() {
+    run_default_test(|| {
+        interpret_matches_traced_values();
+    });
+}
+
+#[test]
+fn test_interpret_unrolled_program() {
+    run_default_test(|| {
+        interpret_unrolled_program();
+    });
+}
+
+#[test]
+fn test_interpret_double_and_simulated() {
+    run_default_test(|| {
+        interpret_double_and_simulated();
+    });
+}
+
+#[test]
+fn test_interpret_fixed_wraparound() {
+    run_default_test(|| {
+        interpret_fixed_wraparound();
+    });
+}
+
+#[test]
+fn test_interpret_input_errors() {
+    run_default_test(|| {
+        interpret_input_errors();
+    });
+}
+
+const DOF: usize = 4;
+
+fn joint_inputs(q: &[f64]) -> IndexMap<String, Value<Real>> {
+    let vector = |values: Vec<f64>| Value::Vector(values.into_iter().map(Real::from_f64).collect());
+    let mut inputs = IndexMap::new();
+    inputs.insert("qsin".to_string(), vector(q.iter().map(|q| q.sin()).collect()));
+    inputs.insert("qcos".to_string(), vector(q.iter().map(|q| q.cos()).collect()));
+    inputs.insert("v".to_string(), vector(vec![0.1; DOF]));
+    inputs.insert("a".to_string(), vector(vec![-0.2; DOF]));
+    inputs
+}
+
+/// Traces forward kinematics of the 4 DoF arm at `q` and returns its outputs
+fn trace_fk(q: &[f64]) -> FKResult {
+    let range = vec![(Real::from_f64(-1.0), Real::from_f64(1.0)); DOF];
+    let qsin = add_input_vector("qsin", range.clone(), q.iter().map(|q| q.sin()).collect());
+    let qcos = add_input_vector("qcos", range.clone(), q.iter().map(|q| q.cos()).collect());
+    let v = add_input_vector("v", range.clone(), vec![0.1; DOF]);
+    let a = add_input_vector("a", range, vec![-0.2; DOF]);
+
+    let mut result = forward_kinematics(qcos, qsin, v, a, &roarm_m2());
+    register_vector_output(&mut result.omi_translations[DOF - 1], "translation");
+    register_matrix_output(&mut result.omi_rotations[DOF - 1], "rotation");
+    result
+}
+
+fn interpret_matches_traced_values() {
+    let q = [0.3, -0.7, 1.1, 0.2];
+    let result = trace_fk(&q);
+
+    // at the traced inputs, the interpreter reproduces the values carried by the types
+    let program = get_program().clone();
+    let outputs = interpret(&program, &joint_inputs(&q), &Exact).unwrap();
+    assert_eq!(outputs["translation"], Value::Vector(result.omi_translations[DOF - 1].value.clone()));
+    assert_eq!(outputs["rotation"], Value::Matrix(result.omi_rotations[DOF - 1].value.clone()));
+}
+
+fn interpret_unrolled_program() {
+    trace_fk(&[0.0; DOF]);
+    let program = get_program().clone();
+    let unrolled = unroll_ir(&program);
+
+    let inputs = joint_inputs(&[0.5, 0.4, -0.3, 1.2]);
+    let outputs = interpret(&program, &inputs, &Exact).unwrap();
+    let unrolled_outputs = interpret(&unrolled, &inputs, &Exact).unwrap();
+    assert_eq!(outputs, unrolled_outputs);
+
+    // a new configuration changes the result without re-tracing
+    let at_zero = interpret(&program, &joint_inputs(&[0.0; DOF]), &Exact).unwrap();
+    assert_ne!(outputs["translation"], at_zero["translation"]);
+
+    // unrolled inputs can also be given element by element
+    let mut element_inputs = IndexMap::new();
+    for (name, value) in &inputs {
+        for (i, element) in value.elements().into_iter().enumerate() {
+            element_inputs.insert(format!("{}_{}", name, i), Value::Scalar(element.clone()));
+        }
+    }
+    assert_eq!(interpret(&unrolled, &element_inputs, &Exact).unwrap(), outputs);
+}
+
+fn interpret_double_and_simulated() {
+    let q = [0.5, 0.4, -0.3, 1.2];
+    trace_fk(&[0.0; DOF]);
+    let program = unroll_ir(&get_program().clone());
+    let inputs = joint_inputs(&q);
+
+    let exact = interpret(&program, &inputs, &Exact).unwrap();
+    let double = interpret(&program, &inputs, &Double).unwrap();
+    let fixed = interpret(
+        &program,
+        &inputs,
+        &Simulated::uniform(Precision::Fixed { total_bits: 32, fractional_bits: 20 }),
+    )
+    .unwrap();
+    let float = interpret(&program, &inputs, &Simulated::uniform(Precision::Float32)).unwrap();
+
+    let exact = exact["translation"].elements();
+    for (i, exact) in exact.iter().enumerate() {
+        let exact_f64 = exact.to_f64();
+        assert!((double["translation"].elements()[i] - exact_f64).abs() < 1e-12);
+
+        let fixed = fixed["translation"].elements()[i];
+        // every fixed-point value lies on the 2^-20 grid
+        assert_eq!((fixed * &Real::from_i64(1 << 20)).floor(), fixed * &Real::from_i64(1 << 20));
+        assert!((fixed - *exact).abs().to_f64() < 1e-4);
+
+        let float = float["translation"].elements()[i];
+        assert_eq!(float.to_f64(), float.to_f64() as f32 as f64);
+        assert!((float - *exact).abs().to_f64() < 1e-5);
+    }
+}
+
+fn interpret_input_errors() {
+    trace_fk(&[0.0; DOF]);
+    let program = get_program().clone();
+
+    let mut inputs = joint_inputs(&[0.0; DOF]);
+    inputs.shift_remove("v");
+    let error = interpret(&program, &inputs, &Exact).unwrap_err();
+    assert!(error.to_string().contains("No value given for input v"));
+
+    let mut inputs = joint_inputs(&[0.0; DOF]);
+    inputs.insert("a".to_string(), Value::Vector(vec![Real::zero(); DOF + 1]));
+    let error = interpret(&program, &inputs, &Exact).unwrap_err();
+    assert!(error.to_string().contains("Input a does not have the traced shape"));
+}
+
+/// Too few integer bits wrap around like the C code would
+fn interpret_fixed_wraparound() {
+    let x = add_input_scalar("x", (Real::from_f64(-4.0), Real::from_f64(4.0)), 3.0);
+    let mut doubled = &x + &x;
+    register_scalar_output(&mut doubled, "doubled");
+    let mut inputs = IndexMap::new();
+    inputs.insert("x".to_string(), Value::Scalar(Real::from_f64(3.0)));
+    let tiny = Simulated::uniform(Precision::Fixed { total_bits: 8, fractional_bits: 5 });
+    let program = get_program().clone();
+    let wrapped = interpret(&program, &inputs, &tiny).unwrap();
+    assert_eq!(wrapped["doubled"], Value::Scalar(Real::from_f64(6.0 - 8.0)));
+}
+
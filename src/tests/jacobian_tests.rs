@@ -0,0 +1,137 @@
+use roboprec::{Vector, register_matrix_output};
+
+use crate::{
+    algorithms::{
+        jacobian::{ReferenceFrame, jacobian},
+        robots::{
+            model::RobotModel,
+            robot_info::{JointType, RobotInfo},
+        },
+    },
+    tests::{
+        robot_fixtures::{INERTIA, Z, assert_close, evaluate, fk_kernel, joint, robot, tree},
+        test_wrapper::run_default_test,
+    },
+};
+
+#[test]
+fn test_jacobian_planar() {
+    run_default_test(|| {
+        jacobian_planar();
+    });
+}
+
+#[test]
+fn test_jacobian_velocity() {
+    run_default_test(|| {
+        jacobian_velocity();
+    });
+}
+
+fn last_link(qcos: Vector, qsin: Vector, robot: &RobotInfo, frame: ReferenceFrame) {
+    let mut jacobian = jacobian(qcos, qsin, robot, robot.n_joints - 1, frame);
+    register_matrix_output(&mut jacobian, "jacobian");
+}
+
+fn world_kernel(qcos: Vector, qsin: Vector, _v: Vector, _a: Vector, robot: &RobotInfo) {
+    last_link(qcos, qsin, robot, ReferenceFrame::World);
+}
+
+fn local_kernel(qcos: Vector, qsin: Vector, _v: Vector, _a: Vector, robot: &RobotInfo) {
+    last_link(qcos, qsin, robot, ReferenceFrame::Local);
+}
+
+fn aligned_kernel(qcos: Vector, qsin: Vector, _v: Vector, _a: Vector, robot: &RobotInfo) {
+    last_link(qcos, qsin, robot, ReferenceFrame::LocalWorldAligned);
+}
+
+/// `jacobian * v` for a 6 x n jacobian in row-major order
+fn times(jacobian: &[f64], v: &[f64]) -> Vec<f64> {
+    jacobian.chunks(v.len()).map(|row| row.iter().zip(v).map(|(j, v)| j * v).sum()).collect()
+}
+
+fn jacobian_planar() {
+    // two links of length 0.5 turning about z
+    let model = robot(vec![
+        joint([0.0; 3], [0.0; 3], Z, [0.25, 0.0, 0.0], INERTIA),
+        joint([0.5, 0.0, 0.0], [0.0; 3], Z, [0.25, 0.0, 0.0], INERTIA),
+    ]);
+    let q: [f64; 2] = [0.4, -0.9];
+    let (x, y) = (0.5 * q[0].cos(), 0.5 * q[0].sin());
+
+    // in row-major order; at the base origin, the second joint's axis passes
+    // through (x, y)
+    let world = &evaluate(&model, &q, &[0.0; 2], &[0.0; 2], world_kernel)["jacobian"];
+    let expected = [0.0, y, 0.0, -x, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0];
+    assert_close(&expected, world);
+
+    // at the second joint, the first joint moves it round the base
+    let aligned = &evaluate(&model, &q, &[0.0; 2], &[0.0; 2], aligned_kernel)["jacobian"];
+    let expected = [-y, 0.0, x, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0];
+    assert_close(&expected, aligned);
+
+    // in the second link's frame, turned by q0 + q1
+    let local = &evaluate(&model, &q, &[0.0; 2], &[0.0; 2], local_kernel)["jacobian"];
+    let (cos, sin) = ((q[0] + q[1]).cos(), (q[0] + q[1]).sin());
+    let expected = [-y * cos + x * sin, 0.0, y * sin + x * cos, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0];
+    assert_close(&expected, local);
+}
+
+/// `tree` with a fixed wrist and a hand at the end of the second arm, joints 4
+/// and 5
+fn wrist() -> RobotModel {
+    let mut model = tree();
+    model.joints.push(joint([0.0, -0.1, 0.0], [0.0, 0.3, 0.0], JointType::Fixed, [0.05, 0.0, 0.0], INERTIA));
+    model.joints.push(joint([0.1, 0.0, 0.1], [0.2, 0.0, 0.5], JointType::Revolute([0.0, 0.6, 0.8]), [0.0, 0.0, 0.1], INERTIA));
+    model.joints[4].parent = Some(3);
+    model.joints[5].parent = Some(4);
+    model
+}
+
+fn jacobian_velocity() {
+    let model = wrist();
+    let (q, v) = ([0.3, -0.5, 0.8, 0.1, 0.0, 0.6], [0.4, -0.3, 0.2, 0.5, 0.0, -0.2]);
+    let a = [0.0; 6];
+
+    // the local jacobian maps the joint velocities to the hand's spatial
+    // velocity in its own frame, as forward kinematics computes it
+    let local = &evaluate(&model, &q, &v, &a, local_kernel)["jacobian"];
+    let velocity = &evaluate(&model, &q, &v, &a, fk_kernel)["velocity"];
+    assert_close(velocity, &times(local, &v));
+
+    // the one-joint arm and the fixed wrist do not move the hand
+    for row in 0..6 {
+        assert_eq!(local[row * 6 + 1], 0.0);
+        assert_eq!(local[row * 6 + 4], 0.0);
+    }
+
+    // the aligned jacobian gives the velocity of the hand's origin in the base
+    // frame, the rate of change of its translation
+    let aligned = &evaluate(&model, &q, &v, &a, aligned_kernel)["jacobian"];
+    let h = 1e-6;
+    let translation = |step: f64| {
+        let q: Vec<f64> = q.iter().zip(&v).map(|(q, v)| q + step * v).collect();
+        evaluate(&model, &q, &v, &a, fk_kernel).swap_remove("translation").unwrap()
+    };
+    let (plus, minus) = (translation(h), translation(-h));
+    let rate: Vec<f64> = plus.iter().zip(&minus).map(|(plus, minus)| (plus - minus) / (2.0 * h)).collect();
+    let aligned_velocity = times(aligned, &v);
+    for (expected, actual) in rate.iter().zip(&aligned_velocity[..3]) {
+        assert!((expected - actual).abs() < 1e-6, "{} != {}", expected, actual);
+    }
+
+    // in the world frame the velocity is taken at the base origin instead,
+    // with the same angular part
+    let world = &evaluate(&model, &q, &v, &a, world_kernel)["jacobian"];
+    let world_velocity = times(world, &v);
+    assert_close(&aligned_velocity[3..], &world_velocity[3..]);
+    let [wx, wy, wz] = [3, 4, 5].map(|k| world_velocity[k]);
+    let p = &evaluate(&model, &q, &v, &a, fk_kernel)["translation"];
+    // v_origin = v_hand + p x w
+    let shifted = [
+        aligned_velocity[0] + (p[1] * wz - p[2] * wy),
+        aligned_velocity[1] + (p[2] * wx - p[0] * wz),
+        aligned_velocity[2] + (p[0] * wy - p[1] * wx),
+    ];
+    assert_close(&shifted, &world_velocity[..3]);
+}
@@ -0,0 +1,170 @@
+use std::f64::consts::FRAC_PI_2;
+
+use roboprec::ir::program::take_program;
+
+use crate::{
+    algorithms::robots::robot_info::JointType,
+    examples::joint_inputs,
+    tests::{
+        robot_fixtures::{
+            INERTIA, Z, assert_close, assert_derivatives, derivatives_kernel, evaluate, fk_kernel, input_ranges,
+            joint, rnea_kernel, robot,
+        },
+        test_wrapper::run_default_test,
+    },
+};
+
+#[test]
+fn test_joint_types_rotated_axes() {
+    run_default_test(|| {
+        joint_types_rotated_axes();
+    });
+}
+
+#[test]
+fn test_joint_types_prismatic() {
+    run_default_test(|| {
+        joint_types_prismatic();
+    });
+}
+
+#[test]
+fn test_joint_types_input_ranges() {
+    run_default_test(|| {
+        joint_types_input_ranges();
+    });
+}
+
+#[test]
+fn test_joint_types_fixed() {
+    run_default_test(|| {
+        joint_types_fixed();
+    });
+}
+
+#[test]
+fn test_joint_types_derivatives() {
+    run_default_test(|| {
+        joint_types_derivatives();
+    });
+}
+
+fn joint_types_rotated_axes() {
+    // the same arm twice: once turning about z, once with every joint frame
+    // turned by Ry(pi/2)^T so that the joints turn about x
+    let about_z = robot(vec![
+        joint([0.0, 0.0, 0.3], [0.0, 0.0, 0.0], Z, [0.05, 0.0, 0.1], INERTIA),
+        joint([0.1, 0.0, 0.2], [FRAC_PI_2, 0.0, 0.0], Z, [0.2, 0.01, 0.0], INERTIA),
+        joint([0.4, 0.05, 0.0], [0.0, 0.0, 0.0], Z, [0.0, 0.1, 0.05], INERTIA),
+    ]);
+    // Ry(pi/2) maps (x, y, z) to (z, y, -x)
+    let x = JointType::Revolute([1.0, 0.0, 0.0]);
+    let inertia = [[0.04, 0.003, -0.002], [0.003, 0.03, -0.001], [-0.002, -0.001, 0.02]];
+    let about_x = robot(vec![
+        joint([0.0, 0.0, 0.3], [0.0, -FRAC_PI_2, 0.0], x, [0.1, 0.0, -0.05], inertia),
+        joint([0.2, 0.0, -0.1], [0.0, 0.0, -FRAC_PI_2], x, [0.0, 0.01, -0.2], inertia),
+        joint([0.0, 0.05, -0.4], [0.0, 0.0, 0.0], x, [0.05, 0.1, 0.0], inertia),
+    ]);
+
+    let (q, v, a) = ([0.3, -0.5, 0.8], [0.4, -0.3, 0.2], [0.1, 0.7, -0.4]);
+    let expected = evaluate(&about_z, &q, &v, &a, fk_kernel);
+    let actual = evaluate(&about_x, &q, &v, &a, fk_kernel);
+    assert_close(&expected["translation"], &actual["translation"]);
+
+    let expected = evaluate(&about_z, &q, &v, &a, rnea_kernel);
+    let actual = evaluate(&about_x, &q, &v, &a, rnea_kernel);
+    assert_close(&expected["tau"], &actual["tau"]);
+
+    let expected = evaluate(&about_z, &q, &v, &a, derivatives_kernel);
+    let actual = evaluate(&about_x, &q, &v, &a, derivatives_kernel);
+    for name in ["da", "dv", "dq"] {
+        assert_close(&expected[name], &actual[name]);
+    }
+}
+
+fn joint_types_prismatic() {
+    // a carriage lifted along z
+    let inertia = [[0.01, 0.0, 0.0], [0.0, 0.01, 0.0], [0.0, 0.0, 0.01]];
+    let lift = robot(vec![joint(
+        [0.1, 0.0, 0.5],
+        [0.0, 0.0, 0.0],
+        JointType::Prismatic([0.0, 0.0, 1.0]),
+        [0.0, 0.0, 0.1],
+        inertia,
+    )]);
+    let fk = evaluate(&lift, &[0.25], &[0.5], &[2.0], fk_kernel);
+    assert_close(&fk["translation"], &[0.1, 0.0, 0.75]);
+    assert_close(&fk["velocity"], &[0.0, 0.0, 0.5, 0.0, 0.0, 0.0]);
+    assert_close(&fk["acceleration"], &[0.0, 0.0, 2.0, 0.0, 0.0, 0.0]);
+
+    // the actuator carries the weight and accelerates the mass
+    let tau = evaluate(&lift, &[0.25], &[0.5], &[2.0], rnea_kernel);
+    assert_close(&tau["tau"], &[2.0 + 9.81]);
+
+    // a horizontal axis turned by a revolute joint below it
+    let slide = robot(vec![
+        joint([0.0, 0.0, 0.0], [0.0, 0.0, 0.0], Z, [0.0, 0.0, 0.0], inertia),
+        joint([0.0, 0.0, 0.2], [0.0, 0.0, 0.0], JointType::Prismatic([1.0, 0.0, 0.0]), [0.0; 3], inertia),
+    ]);
+    let fk = evaluate(&slide, &[FRAC_PI_2, 0.3], &[1.0, 0.2], &[1.0, 0.5], fk_kernel);
+    assert_close(&fk["translation"], &[0.0, 0.3, 0.2]);
+    assert_close(&fk["velocity"], &[0.2, 0.3, 0.0, 0.0, 0.0, 1.0]);
+    // the classical acceleration (0.5 - 0.3, 0.3 + 2 * 0.2) less w x v, in the frame of the slide
+    assert_close(&fk["acceleration"], &[0.5, 0.5, 0.0, 0.0, 0.0, 1.0]);
+}
+
+fn joint_types_input_ranges() {
+    // a revolute joint turning over [0, pi/2] and a slide over [-0.1, 0.4] m
+    let inertia = [[0.01, 0.0, 0.0], [0.0, 0.01, 0.0], [0.0, 0.0, 0.01]];
+    let mut slide = robot(vec![
+        joint([0.0, 0.0, 0.0], [0.0, 0.0, 0.0], Z, [0.0, 0.0, 0.0], inertia),
+        joint([0.0, 0.0, 0.2], [0.0, 0.0, 0.0], JointType::Prismatic([1.0, 0.0, 0.0]), [0.0; 3], inertia),
+    ]);
+    slide.joints[0].bounds = (0.0, FRAC_PI_2);
+    slide.joints[1].bounds = (-0.1, 0.4);
+    joint_inputs(&slide.bounds(), &slide.joint_types());
+    let program = take_program();
+
+    // the slide's displacement keeps its bounds, and its qcos is unused
+    assert_eq!(input_ranges(&program, "qsin"), vec![(0.0, 1.0), (-0.1, 0.4)]);
+    assert_eq!(input_ranges(&program, "qcos"), vec![(FRAC_PI_2.cos(), 1.0), (1.0, 1.0)]);
+}
+
+fn joint_types_fixed() {
+    // a flange fixed 0.2 above the first link is the same as a heavier first link
+    let inertia = [[0.01, 0.0, 0.0], [0.0, 0.01, 0.0], [0.0, 0.0, 0.01]];
+    let x = JointType::Revolute([1.0, 0.0, 0.0]);
+    let fixed = robot(vec![
+        joint([0.0, 0.0, 0.1], [0.0, 0.0, 0.0], Z, [0.0, 0.0, 0.1], inertia),
+        joint([0.0, 0.0, 0.2], [0.0, 0.0, 0.0], JointType::Fixed, [0.0; 3], inertia),
+        joint([0.3, 0.0, 0.0], [0.0, 0.0, 0.0], x, [0.0, 0.1, 0.0], inertia),
+    ]);
+    let mut merged = robot(vec![
+        joint([0.0, 0.0, 0.1], [0.0, 0.0, 0.0], Z, [0.0, 0.0, 0.15], [[0.025, 0.0, 0.0], [0.0, 0.025, 0.0], [0.0, 0.0, 0.02]]),
+        joint([0.3, 0.0, 0.2], [0.0, 0.0, 0.0], x, [0.0, 0.1, 0.0], inertia),
+    ]);
+    merged.joints[0].mass = 2.0;
+
+    let (q, v, a) = ([0.4, 0.0, -0.6], [0.5, 0.0, 0.7], [-0.3, 0.0, 0.2]);
+    let skip_fixed = |values: &[f64]| [values[0], values[2]];
+    let expected = evaluate(&merged, &skip_fixed(&q), &skip_fixed(&v), &skip_fixed(&a), fk_kernel);
+    let actual = evaluate(&fixed, &q, &v, &a, fk_kernel);
+    assert_close(&expected["translation"], &actual["translation"]);
+
+    let expected = evaluate(&merged, &skip_fixed(&q), &skip_fixed(&v), &skip_fixed(&a), rnea_kernel);
+    let actual = evaluate(&fixed, &q, &v, &a, rnea_kernel);
+    assert_close(&[expected["tau"][0], 0.0, expected["tau"][1]], &actual["tau"]);
+}
+
+fn joint_types_derivatives() {
+    // every kind of joint, with axes off the coordinate axes
+    let model = robot(vec![
+        joint([0.0, 0.0, 0.2], [0.0, 0.0, 0.0], Z, [0.0, 0.05, 0.1], INERTIA),
+        joint([0.1, 0.0, 0.1], [0.3, 0.0, 0.0], JointType::Prismatic([0.6, 0.0, 0.8]), [0.1, 0.0, 0.0], INERTIA),
+        joint([0.0, 0.2, 0.0], [0.0, 0.5, 0.0], JointType::Fixed, [0.0, 0.0, 0.05], INERTIA),
+        joint([0.2, 0.0, 0.0], [0.0, 0.0, 0.4], JointType::Revolute([0.0, 0.6, -0.8]), [0.1, 0.1, 0.0], INERTIA),
+        joint([0.0, 0.1, 0.1], [0.0, 0.0, 0.0], JointType::Revolute([1.0, 0.0, 0.0]), [0.0, 0.1, 0.0], INERTIA),
+    ]);
+    let state = [[0.3, 0.2, 0.0, 0.8, -0.4], [0.4, -0.3, 0.0, 0.5, 0.6], [0.1, 0.7, 0.0, -0.4, 0.3]];
+    assert_derivatives(&model, state);
+}
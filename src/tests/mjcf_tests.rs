@@ -0,0 +1,163 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use roboprec::ir::program::take_program;
+
+use crate::{
+    algorithms::robots::{
+        mjcf::{load_mjcf, parse_mjcf},
+        robot_info::JointType,
+        urdf::load_urdf,
+    },
+    examples::joint_inputs,
+    tests::{
+        robot_fixtures::{assert_close, evaluate, fk_kernel, fk_outputs, input_ranges},
+        test_wrapper::run_default_test,
+    },
+};
+
+#[test]
+fn test_mjcf_roarm_m2() {
+    run_default_test(|| {
+        mjcf_roarm_m2();
+    });
+}
+
+#[test]
+fn test_mjcf_frames() {
+    run_default_test(|| {
+        mjcf_frames();
+    });
+}
+
+#[test]
+fn test_mjcf_slide() {
+    run_default_test(|| {
+        mjcf_slide();
+    });
+}
+
+#[test]
+fn test_mjcf_errors() {
+    run_default_test(|| {
+        mjcf_errors();
+    });
+}
+
+fn close(a: &[f64], b: &[f64]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-12)
+}
+
+fn mjcf_roarm_m2() {
+    let mjcf = load_mjcf(concat!(env!("CARGO_MANIFEST_DIR"), "/models/roarm_m2.xml")).unwrap();
+    let urdf = load_urdf(concat!(env!("CARGO_MANIFEST_DIR"), "/models/roarm_m2.urdf")).unwrap();
+    assert_eq!(mjcf.name, "roarm_m2");
+    assert_eq!(mjcf.bounds(), urdf.bounds());
+    for (mjcf, urdf) in mjcf.joints.iter().zip(&urdf.joints) {
+        assert_eq!(mjcf.name, urdf.name);
+        assert_eq!(mjcf.translation, urdf.translation);
+        assert_eq!((mjcf.joint_type, mjcf.mass, mjcf.lever, mjcf.inertia), (urdf.joint_type, urdf.mass, urdf.lever, urdf.inertia));
+    }
+
+    // the angles are recovered from the rotations, so compare the kinematics
+    let expected = fk_outputs(urdf.robot_info());
+    let imported = fk_outputs(mjcf.robot_info());
+    assert!(close(&expected, &imported), "{:?} != {:?}", expected, imported);
+}
+
+fn mjcf_frames() {
+    // angles in degrees, a tool welded to link1 and a joint away from its body's origin
+    let mjcf = r#"
+        <mujoco model="arm">
+          <default>
+            <joint range="-90 90"/>
+            <default class="wide">
+              <joint range="-180 180"/>
+            </default>
+          </default>
+          <worldbody>
+            <body name="link1" pos="0 0 0.1" quat="0.7071067811865476 0 0 0.7071067811865476">
+              <joint name="j1" class="wide"/>
+              <inertial pos="0 0 0.1" mass="1" diaginertia="0.01 0.01 0.01"/>
+              <body name="tool" pos="0 0 0.2" euler="0 0 90">
+                <inertial pos="0.1 0 0" mass="1" diaginertia="0.02 0.03 0.04"/>
+                <body name="link2" pos="0.5 0 0">
+                  <joint name="j2" pos="0 0 0.05"/>
+                  <inertial pos="0 0 0.05" mass="2" fullinertia="1 2 3 0.1 0.2 0.3"/>
+                </body>
+              </body>
+            </body>
+          </worldbody>
+        </mujoco>"#;
+    let model = parse_mjcf(mjcf).unwrap();
+    assert_eq!(model.n_joints(), 2);
+
+    let j1 = &model.joints[0];
+    assert!(close(&j1.translation, &[0.0, 0.0, 0.1]));
+    assert!(close(&j1.rpy, &[0.0, 0.0, FRAC_PI_2]));
+    assert!(close(&[j1.bounds.0, j1.bounds.1], &[-PI, PI]));
+    // the tool at (0, 0.1, 0.2) and link1 merge into one body
+    assert_eq!(j1.mass, 2.0);
+    assert!(close(&j1.lever, &[0.0, 0.05, 0.15]));
+    let expected = [0.05, 0.0, 0.0, 0.0, 0.035, -0.005, 0.0, -0.005, 0.055];
+    assert!(close(j1.inertia.as_flattened(), &expected), "{:?}", j1.inertia);
+
+    let j2 = &model.joints[1];
+    assert!(close(&j2.translation, &[0.0, 0.5, 0.25]));
+    assert!(close(&j2.rpy, &[0.0, 0.0, FRAC_PI_2]));
+    assert!(close(&[j2.bounds.0, j2.bounds.1], &[-FRAC_PI_2, FRAC_PI_2]));
+    assert!(close(&j2.lever, &[0.0; 3]));
+    assert_eq!(j2.inertia, [[1.0, 0.1, 0.2], [0.1, 2.0, 0.3], [0.2, 0.3, 3.0]]);
+}
+
+fn mjcf_slide() {
+    // a turntable over [-90, 90] degrees carrying a slide over [-0.1, 0.4] m
+    let mjcf = r#"
+        <mujoco model="slide">
+          <worldbody>
+            <body name="table">
+              <joint name="turn" range="-90 90"/>
+              <inertial pos="0 0 0" mass="1" diaginertia="0.01 0.01 0.01"/>
+              <body name="carriage" pos="0 0 0.2">
+                <joint name="extend" type="slide" axis="1 0 0" range="-0.1 0.4"/>
+                <inertial pos="0 0 0" mass="2" diaginertia="0.01 0.01 0.01"/>
+              </body>
+            </body>
+          </worldbody>
+        </mujoco>"#;
+    let model = parse_mjcf(mjcf).unwrap();
+    assert_eq!(model.joints[1].joint_type, JointType::Prismatic([1.0, 0.0, 0.0]));
+
+    // the slide's displacement ranges over its range in metres, not degrees
+    let (qcos, qsin, v, a) = joint_inputs(&model.bounds(), &model.joint_types());
+    fk_kernel(qcos, qsin, v, a, &model.robot_info());
+    let program = take_program();
+    let qsin = input_ranges(&program, "qsin");
+    assert!(close(&[qsin[0].0, qsin[0].1, qsin[1].0, qsin[1].1], &[-1.0, 1.0, -0.1, 0.4]), "{:?}", qsin);
+    let qcos = input_ranges(&program, "qcos");
+    assert!(close(&[qcos[0].0, qcos[0].1, qcos[1].0, qcos[1].1], &[0.0, 1.0, 1.0, 1.0]), "{:?}", qcos);
+
+    let fk = evaluate(&model, &[0.0, 0.3], &[0.0; 2], &[0.0; 2], fk_kernel);
+    assert_close(&fk["translation"], &[0.3, 0.0, 0.2]);
+}
+
+fn mjcf_errors() {
+    let body = |contents: &str| {
+        format!(
+            r#"<mujoco model="arm"><worldbody><body name="b">{}</body></worldbody></mujoco>"#,
+            contents
+        )
+    };
+    let error = |text: &str| format!("{:#}", parse_mjcf(text).unwrap_err());
+
+    assert!(error(&body(r#"<joint type="ball"/>"#)).contains("Body b has a ball joint"));
+    assert!(error(&body(r#"<joint type="slide"/>"#)).contains("Slide joint b has no range"));
+    assert!(error(&body("<freejoint/>")).contains("Body b has a free joint"));
+    assert!(error(&body("<joint/><joint/>")).contains("Body b has 2 joints"));
+    assert!(error(&body(r#"<joint axis="0 0 0"/>"#)).contains("Joint b has a zero axis"));
+    assert!(error(&body(r#"<joint range="1"/>"#)).contains("Expected two values in range"));
+    assert!(error(&body(r#"<joint/><body axisangle="1 0 0 90"/>"#)).contains("only quat and euler"));
+    assert!(error(&body(r#"<joint/><inertial mass="1"/>"#)).contains("one of diaginertia and fullinertia"));
+    assert!(error(&body("")).contains("MJCF has no hinge or slide joints"));
+    assert!(error("<mujoco/>").contains("MJCF has no <worldbody>"));
+    assert!(error("<robot/>").contains("Expected a <mujoco> element"));
+}
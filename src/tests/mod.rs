@@ -1,9 +1,26 @@
 #![cfg(test)]
+pub mod analysis_result_tests;
 pub mod arithmetic_tests;
+pub mod attribution_tests;
+pub mod codegen_tests;
+pub mod dh_tests;
+pub mod floating_base_tests;
+pub mod gravity_tests;
 pub mod integration_tests;
+pub mod interpreter_tests;
+pub mod jacobian_tests;
+pub mod joint_type_tests;
 pub mod macro_tests;
 pub mod matrix_tests;
+pub mod mjcf_tests;
+pub mod monte_carlo_tests;
+pub mod parameter_tests;
+pub mod registry_tests;
+pub mod robot_fixtures;
 pub mod scalar_tests;
+pub mod simulator_tests;
 pub mod test_wrapper;
+pub mod tree_tests;
+pub mod urdf_tests;
 pub mod value_tests;
 pub mod vector_tests;
@@ -0,0 +1,139 @@
+use indexmap::IndexMap;
+use roboprec::{
+    MonteCarlo, Precision, Real, Sampling, add_input_scalar,
+    analysis::{
+        daisy::DaisyRange,
+        monte_carlo::{monte_carlo, write_monte_carlo_to_file},
+        worst_case::{WorstCaseSearch, search_worst_case},
+    },
+    ir::{
+        program::{Program, get_program, update_program_outputs},
+        unroll::unroll_ir,
+    },
+    register_scalar_output,
+};
+
+use crate::tests::{
+    codegen_tests::{test_output_dir, uniform_precisions},
+    test_wrapper::run_default_test,
+};
+
+#[test]
+fn test_monte_carlo_uniform() {
+    run_default_test(|| {
+        monte_carlo_uniform();
+    });
+}
+
+#[test]
+fn test_monte_carlo_corners() {
+    run_default_test(|| {
+        monte_carlo_corners();
+    });
+}
+
+#[test]
+fn test_worst_case_search() {
+    run_default_test(|| {
+        worst_case_search();
+    });
+}
+
+/// `x * y + x` with both inputs in [-1, 2], analysed with the given error bound
+fn trace_kernel(bound: f64) -> (Program, IndexMap<String, Precision>) {
+    let range = (Real::from_f64(-1.0), Real::from_f64(2.0));
+    let x = add_input_scalar("x", range.clone(), 0.5);
+    let y = add_input_scalar("y", range, 0.5);
+    let mut result = &(&x * &y) + &x;
+    register_scalar_output(&mut result, "result");
+
+    let mut program = unroll_ir(&get_program());
+    let precisions = uniform_precisions(&program, Precision::Fixed { total_bits: 16, fractional_bits: 10 });
+    let ranges = precisions
+        .keys()
+        .map(|name| (name.clone(), DaisyRange { lower: -4.0, upper: 4.0 }))
+        .collect();
+    let errors = precisions.keys().map(|name| (name.clone(), bound)).collect();
+    update_program_outputs(&mut program, &ranges, &errors);
+    (program, precisions)
+}
+
+fn monte_carlo_uniform() {
+    let (program, precisions) = trace_kernel(1e-2);
+    let settings = MonteCarlo {
+        samples: 500,
+        ..Default::default()
+    };
+    let report = monte_carlo(&program, &precisions, &settings).unwrap();
+    assert_eq!(report.samples, 500);
+    assert_eq!(report.outputs.len(), 1);
+
+    let output = &report.outputs[0];
+    assert_eq!(output.name, "result");
+    assert_eq!(output.bound, 1e-2);
+    // truncating to 10 fractional bits loses less than a few ulps
+    assert!(output.max_error > 0.0 && output.max_error < 4.0 / 1024.0);
+    assert!(output.p50_error <= output.p90_error && output.p90_error <= output.p99_error);
+    assert!(output.p99_error <= output.max_error && output.mean_error <= output.max_error);
+    assert!(output.tightness() > 1.0);
+
+    // the same seed draws the same inputs
+    let again = monte_carlo(&program, &precisions, &settings).unwrap();
+    assert_eq!(again.outputs[0].mean_error, output.mean_error);
+
+    let file = test_output_dir("monte_carlo_uniform").join("monte_carlo.txt");
+    std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+    write_monte_carlo_to_file(&report, &file).unwrap();
+    let text = std::fs::read_to_string(&file).unwrap();
+    assert!(text.starts_with("# 500 Uniform samples\n"));
+    assert!(text.contains(&format!("result: {:e} ", output.max_error)));
+}
+
+fn monte_carlo_corners() {
+    let (program, precisions) = trace_kernel(1e-2);
+
+    // two inputs have four corners, all of them are tried once
+    let settings = MonteCarlo {
+        samples: 100,
+        sampling: Sampling::Corners,
+        ..Default::default()
+    };
+    let report = monte_carlo(&program, &precisions, &settings).unwrap();
+    assert_eq!(report.samples, 4);
+    // every corner is representable, so only the product can be inexact
+    assert_eq!(report.outputs[0].max_error, 0.0);
+
+    // with fewer samples than corners, random corners are drawn
+    let settings = MonteCarlo {
+        samples: 3,
+        sampling: Sampling::Corners,
+        ..Default::default()
+    };
+    assert_eq!(monte_carlo(&program, &precisions, &settings).unwrap().samples, 3);
+}
+
+fn worst_case_search() {
+    let (program, precisions) = trace_kernel(1e-2);
+    let settings = WorstCaseSearch {
+        restarts: 4,
+        evaluations: 300,
+        ..Default::default()
+    };
+    let worst = search_worst_case(&program, &precisions, "result", &settings).unwrap();
+    assert_eq!(worst.output, "result");
+    assert!(worst.evaluations <= 4 * 300);
+    assert!(worst.inputs.values().all(|value| (-1.0..=2.0).contains(value)));
+    assert_eq!(worst.ratio(), worst.error / 1e-2);
+
+    // the search does at least as well as random sampling with a similar budget
+    let random = MonteCarlo {
+        samples: 200,
+        ..Default::default()
+    };
+    let random = monte_carlo(&program, &precisions, &random).unwrap();
+    assert!(worst.error >= random.outputs[0].max_error);
+    assert!(worst.error <= 1e-2);
+
+    let error = search_worst_case(&program, &precisions, "missing", &settings).unwrap_err();
+    assert!(error.to_string().contains("Output missing not found, expected one of result"));
+}
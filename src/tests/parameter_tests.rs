@@ -0,0 +1,123 @@
+use indexmap::IndexMap;
+use roboprec::{
+    Real,
+    ir::{
+        interpreter::Value,
+        program::{ProgramInput, take_program},
+    },
+};
+
+use crate::{
+    algorithms::robots::{model::JointModel, robot_info::RobotInfo},
+    tests::{
+        robot_fixtures::{arm, assert_close, derivatives_kernel, evaluate, evaluate_with, rnea_kernel},
+        test_wrapper::run_default_test,
+    },
+};
+
+#[test]
+fn test_parameters_ranged_rnea() {
+    run_default_test(|| {
+        parameters_ranged_rnea();
+    });
+}
+
+#[test]
+fn test_parameters_ranged_derivatives() {
+    run_default_test(|| {
+        parameters_ranged_derivatives();
+    });
+}
+
+#[test]
+fn test_parameters_input_ranges() {
+    run_default_test(|| {
+        parameters_input_ranges();
+    });
+}
+
+/// The last link of `arm` carrying a payload
+fn loaded(link: &JointModel) -> JointModel {
+    JointModel {
+        mass: 2.5,
+        lever: [0.15, -0.02, 0.03],
+        inertia: [[0.05, 0.002, 0.0], [0.002, 0.06, 0.004], [0.0, 0.004, 0.03]],
+        ..link.clone()
+    }
+}
+
+/// Marks the mass properties of the last link as uncertain, over a range
+/// taking in both the bare and the loaded link
+fn mark_payload(robot: &mut RobotInfo) {
+    let last = robot.n_joints - 1;
+    robot.ranged_mass(last, (0.5, 3.0));
+    robot.ranged_lever(last, [(0.0, 0.2), (-0.05, 0.05), (-0.05, 0.05)]);
+    robot.ranged_inertia(last, [[(0.0, 0.1), (-0.01, 0.01), (-0.01, 0.01)]; 3]);
+}
+
+/// The values of the inputs `mark_payload` makes of the last link's mass
+/// properties, set to those of `link`
+fn payload_inputs(last: usize, link: &JointModel) -> IndexMap<String, Value<Real>> {
+    let real = |value: &f64| Real::from_f64(*value);
+    let rows = link.inertia.iter().map(|row| row.iter().map(real).collect()).collect();
+    IndexMap::from([
+        (format!("param_mass_{}", last), Value::Scalar(real(&link.mass))),
+        (format!("param_lever_{}", last), Value::Vector(link.lever.iter().map(real).collect())),
+        (format!("param_inertia_{}", last), Value::Matrix(rows)),
+    ])
+}
+
+fn parameters_ranged_rnea() {
+    let (q, v, a) = ([0.3, -0.5, 0.8], [0.4, -0.3, 0.2], [0.1, 0.7, -0.4]);
+    let bare = arm();
+    let mut payload = arm();
+    payload.joints[2] = loaded(&bare.joints[2]);
+
+    // one traced program gives the torques for any parameters in range
+    for (model, link) in [(&bare, &bare.joints[2]), (&payload, &payload.joints[2])] {
+        let expected = &evaluate(model, &q, &v, &a, rnea_kernel)["tau"];
+        let actual = evaluate_with(&bare, &q, &v, &a, &mark_payload, payload_inputs(2, link), rnea_kernel);
+        assert_close(expected, &actual["tau"]);
+    }
+}
+
+fn parameters_ranged_derivatives() {
+    let (q, v, a) = ([0.3, -0.5, 0.8], [0.4, -0.3, 0.2], [0.1, 0.7, -0.4]);
+    let bare = arm();
+    let mut payload = arm();
+    payload.joints[2] = loaded(&bare.joints[2]);
+
+    let expected = evaluate(&payload, &q, &v, &a, derivatives_kernel);
+    let inputs = payload_inputs(2, &payload.joints[2]);
+    let actual = evaluate_with(&bare, &q, &v, &a, &mark_payload, inputs, derivatives_kernel);
+    for name in ["da", "dv", "dq"] {
+        assert_close(&expected[name], &actual[name]);
+    }
+}
+
+fn parameters_input_ranges() {
+    let model = arm();
+    let mut robot_info = model.robot_info();
+    mark_payload(&mut robot_info);
+    let program = take_program();
+    let input = |name: &str| program.get_inputs().iter().find(|(id, _)| id.name() == name).unwrap().1;
+
+    let ProgramInput::Scalar { info } = input("param_mass_2") else {
+        panic!("param_mass_2 is not a scalar");
+    };
+    assert_eq!(info.range, (Real::from_f64(0.5), Real::from_f64(3.0)));
+    let ProgramInput::Vector { info } = input("param_lever_2") else {
+        panic!("param_lever_2 is not a vector");
+    };
+    assert_eq!(info[0].range, (Real::from_f64(0.0), Real::from_f64(0.2)));
+    let ProgramInput::Matrix { info } = input("param_inertia_2") else {
+        panic!("param_inertia_2 is not a matrix");
+    };
+    assert_eq!((info.len(), info[2].len()), (3, 3));
+
+    // the marked parameters keep their constant values as defaults, and the
+    // others stay constants
+    assert_eq!(robot_info.masses.value_f64(), vec![1.0; 3]);
+    assert_eq!(robot_info.levers[2].value_f64(), model.joints[2].lever);
+    assert_eq!(program.get_inputs().len(), 3);
+}
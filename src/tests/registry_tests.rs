@@ -0,0 +1,91 @@
+use roboprec::{
+    Real,
+    ir::program::{ProgramInput, take_program},
+};
+
+use crate::{
+    registry::{KERNELS, ROBOTS, find_kernel, find_robot, list, trace},
+    tests::test_wrapper::run_default_test,
+};
+
+#[test]
+fn test_registry_traces_every_kernel() {
+    run_default_test(|| {
+        registry_traces_every_kernel();
+    });
+}
+
+#[test]
+fn test_registry_input_ranges() {
+    run_default_test(|| {
+        registry_input_ranges();
+    });
+}
+
+#[test]
+fn test_registry_lookup() {
+    run_default_test(|| {
+        registry_lookup();
+    });
+}
+
+fn registry_traces_every_kernel() {
+    for robot in &ROBOTS {
+        let n = (robot.bounds)().len();
+        for kernel in &KERNELS {
+            trace(robot, kernel);
+            let program = take_program();
+            let inputs: Vec<&String> = program.get_inputs().keys().map(|id| id.name()).collect();
+            let expected_inputs = match kernel.name {
+                "jacobian" => &["qsin", "qcos"][..],
+                _ => &["qsin", "qcos", "v", "a"][..],
+            };
+            assert_eq!(inputs, expected_inputs, "{} for {}", kernel.name, robot.name);
+            // one output per joint for fk, one vector or matrix for rnea and
+            // the jacobian, three matrices for the derivatives
+            let expected = match kernel.name {
+                "fk" => 4 * n,
+                "jacobian" | "rnea" => 1,
+                _ => 3,
+            };
+            assert_eq!(program.get_outputs().len(), expected, "{} for {}", kernel.name, robot.name);
+        }
+    }
+}
+
+fn registry_input_ranges() {
+    let roarm_m2 = find_robot("roarm_m2").unwrap();
+    trace(roarm_m2, find_kernel("rnea").unwrap());
+    let program = take_program();
+    let ranges = |name: &str| -> Vec<(f64, f64)> {
+        let (_, input) = program.get_inputs().iter().find(|(id, _)| id.name() == name).unwrap();
+        let ProgramInput::Vector { info } = input else {
+            panic!("{} is not a vector", name);
+        };
+        info.iter().map(|input| (input.range.0.to_f64(), input.range.1.to_f64())).collect()
+    };
+    let (qsin, qcos, v) = (ranges("qsin"), ranges("qcos"), ranges("v"));
+
+    // the elbow turns over [-1, 2.95], through pi/2, and the gripper over [0, 1.5]
+    let bounds = (roarm_m2.bounds)();
+    assert_eq!(bounds[2], (Real::from_f64(-1.0), Real::from_f64(2.95)));
+    assert_eq!(qsin[2], ((-1.0f64).sin(), 1.0));
+    assert_eq!(qcos[2], (2.95f64.cos(), 1.0));
+    assert_eq!(qsin[3], (0.0, 1.5f64.sin()));
+    assert_eq!(qcos[3], (1.5f64.cos(), 1.0));
+    assert_eq!(v, vec![(-0.5, 0.5); 4]);
+}
+
+fn registry_lookup() {
+    assert_eq!(find_robot("indy7").unwrap().name, "indy7");
+    assert_eq!(find_kernel("rnea_derivatives").unwrap().name, "rnea_derivatives");
+
+    let error = find_robot("ur5").err().unwrap().to_string();
+    assert!(error.contains("Unknown robot 'ur5'") && error.contains("panda, indy7, roarm_m2, roarm_m3"), "{}", error);
+    let error = find_kernel("aba").err().unwrap().to_string();
+    assert!(error.contains("Unknown kernel 'aba'") && error.contains("fk, jacobian, rnea, rnea_derivatives"), "{}", error);
+
+    let list = list();
+    assert!(list.contains("panda") && list.contains("7 joints"), "{}", list);
+    assert!(list.contains("rnea_derivatives"), "{}", list);
+}
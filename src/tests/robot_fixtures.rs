@@ -0,0 +1,236 @@
+use std::f64::consts::PI;
+
+use indexmap::IndexMap;
+use roboprec::{
+    Real, Vector, add_input_vector,
+    ir::{
+        interpreter::{Double, Value, interpret},
+        program::{Program, ProgramInput, get_program, take_program},
+        unroll::unroll_ir,
+    },
+    register_matrix_output, register_vector_output,
+};
+
+use crate::{
+    algorithms::{
+        forward_kinematics::forward_kinematics,
+        rnea::rnea,
+        rnea_derivatives::rneaderivatives,
+        robots::{
+            model::{BaseModel, JointModel, RobotModel},
+            robot_info::{JointType, RobotInfo},
+        },
+    },
+    examples::joint_inputs,
+};
+
+pub const Z: JointType = JointType::Revolute([0.0, 0.0, 1.0]);
+
+/// Inertia of the links of the test robots, with distinct principal moments
+/// and off-diagonal terms
+pub const INERTIA: [[f64; 3]; 3] = [[0.02, 0.001, 0.002], [0.001, 0.03, 0.003], [0.002, 0.003, 0.04]];
+
+pub fn joint(translation: [f64; 3], rpy: [f64; 3], joint_type: JointType, lever: [f64; 3], inertia: [[f64; 3]; 3]) -> JointModel {
+    JointModel {
+        name: "joint".to_string(),
+        parent: None,
+        translation,
+        rpy,
+        joint_type,
+        bounds: (-PI, PI),
+        mass: 1.0,
+        lever,
+        inertia,
+    }
+}
+
+/// A serial chain of the joints
+pub fn robot(mut joints: Vec<JointModel>) -> RobotModel {
+    for (i, joint) in joints.iter_mut().enumerate() {
+        joint.parent = i.checked_sub(1);
+    }
+    RobotModel {
+        name: "robot".to_string(),
+        base: BaseModel::default(),
+        joints,
+    }
+}
+
+/// A serial arm of three joints turning about z, x and y
+pub fn arm() -> RobotModel {
+    robot(vec![
+        joint([0.0, 0.0, 0.3], [0.0, 0.0, 0.0], Z, [0.0, 0.0, 0.2], INERTIA),
+        joint([0.0, 0.1, 0.3], [0.0, 0.0, 0.0], JointType::Revolute([1.0, 0.0, 0.0]), [0.0, 0.1, 0.05], INERTIA),
+        joint([0.0, 0.3, 0.0], [0.2, 0.0, 0.0], JointType::Revolute([0.0, 1.0, 0.0]), [0.1, 0.0, 0.0], INERTIA),
+    ])
+}
+
+/// A torso carrying a one-joint arm and a two-joint arm, `torso` and the
+/// joints of the arms in order
+pub fn arms() -> [JointModel; 4] {
+    let x = JointType::Revolute([1.0, 0.0, 0.0]);
+    [
+        joint([0.0, 0.0, 0.3], [0.0, 0.0, 0.0], Z, [0.0, 0.0, 0.2], INERTIA),
+        joint([0.0, 0.2, 0.4], [0.0, 0.0, 0.0], x, [0.0, 0.1, 0.0], INERTIA),
+        joint([0.0, -0.2, 0.4], [0.4, 0.0, 0.0], x, [0.0, -0.1, 0.05], INERTIA),
+        joint([0.0, -0.3, 0.0], [0.0, 0.0, 0.0], JointType::Prismatic([0.0, -1.0, 0.0]), [0.05, 0.0, 0.0], INERTIA),
+    ]
+}
+
+/// Both arms on the torso: the one-joint arm is joint 1, the other arm joints
+/// 2 and 3
+pub fn tree() -> RobotModel {
+    let [torso, left, right, hand] = arms();
+    let mut tree = robot(vec![torso, left, right, hand]);
+    tree.joints[2].parent = Some(0);
+    tree
+}
+
+pub fn assert_close(expected: &[f64], actual: &[f64]) {
+    assert_eq!(expected.len(), actual.len());
+    for (expected, actual) in expected.iter().zip(actual) {
+        assert!((expected - actual).abs() < 1e-9, "{:?} != {:?}", expected, actual);
+    }
+}
+
+pub type Kernel = fn(Vector, Vector, Vector, Vector, &RobotInfo);
+
+/// Traces `kernel` for `model`, with the inputs of `joint_inputs`, and
+/// evaluates it in double precision at the joint state; prismatic joints take
+/// their displacement from `qsin`
+pub fn evaluate(model: &RobotModel, q: &[f64], v: &[f64], a: &[f64], kernel: Kernel) -> IndexMap<String, Vec<f64>> {
+    evaluate_with(model, q, v, a, &|_| {}, IndexMap::new(), kernel)
+}
+
+/// `evaluate`, with `setup` applied to the robot before tracing, e.g. to make
+/// its parameters inputs, and `extra_inputs` holding the values of the inputs
+/// besides the joint state
+pub fn evaluate_with(
+    model: &RobotModel,
+    q: &[f64],
+    v: &[f64],
+    a: &[f64],
+    setup: &dyn Fn(&mut RobotInfo),
+    extra_inputs: IndexMap<String, Value<Real>>,
+    kernel: Kernel,
+) -> IndexMap<String, Vec<f64>> {
+    let qsin: Vec<f64> = model
+        .joints
+        .iter()
+        .zip(q)
+        .map(|(joint, q)| match joint.joint_type {
+            JointType::Prismatic(_) => *q,
+            _ => q.sin(),
+        })
+        .collect();
+    let qcos: Vec<f64> = q.iter().map(|q| q.cos()).collect();
+
+    let mut inputs = IndexMap::new();
+    for (name, values) in [("qcos", qcos), ("qsin", qsin), ("v", v.to_vec()), ("a", a.to_vec())] {
+        inputs.insert(name.to_string(), Value::Vector(values.iter().map(|value| Real::from_f64(*value)).collect()));
+    }
+    inputs.extend(extra_inputs);
+    let (qcos, qsin, v, a) = joint_inputs(&model.bounds(), &model.joint_types());
+
+    let mut robot_info = model.robot_info();
+    setup(&mut robot_info);
+    kernel(qcos, qsin, v, a, &robot_info);
+    let program = get_program().clone();
+    take_program();
+    interpret(&program, &inputs, &Double)
+        .unwrap()
+        .into_iter()
+        .map(|(name, value)| (name, value.elements().into_iter().copied().collect()))
+        .collect()
+}
+
+/// Ranges of the elements of the vector input `name`
+pub fn input_ranges(program: &Program, name: &str) -> Vec<(f64, f64)> {
+    let (_, input) = program.get_inputs().iter().find(|(id, _)| id.name() == name).unwrap();
+    let ProgramInput::Vector { info } = input else {
+        panic!("{} is not a vector", name);
+    };
+    info.iter().map(|input| (input.range.0.to_f64(), input.range.1.to_f64())).collect()
+}
+
+/// Final translation and velocity of forward kinematics at a fixed configuration
+pub fn fk_outputs(robot: RobotInfo) -> Vec<f64> {
+    let n = robot.n_joints;
+    let range = vec![(Real::from_f64(-1.0), Real::from_f64(1.0)); n];
+    let qsin = add_input_vector("qsin", range.clone(), vec![0.0; n]);
+    let qcos = add_input_vector("qcos", range.clone(), vec![1.0; n]);
+    let v = add_input_vector("v", range.clone(), vec![0.1; n]);
+    let a = add_input_vector("a", range, vec![-0.2; n]);
+
+    let mut result = forward_kinematics(qcos, qsin, v, a, &robot);
+    register_vector_output(&mut result.omi_translations[n - 1], "translation");
+    register_vector_output(&mut result.all_v[n - 1], "velocity");
+    let program = unroll_ir(&get_program());
+    take_program();
+
+    let inputs: IndexMap<String, Value<Real>> = program
+        .get_inputs()
+        .keys()
+        .enumerate()
+        .map(|(i, id)| (id.name().clone(), Value::Scalar(Real::from_f64((i as f64 * 0.7).sin()))))
+        .collect();
+    let outputs = interpret(&program, &inputs, &Double).unwrap();
+    outputs.values().flat_map(|value| value.elements()).copied().collect()
+}
+
+pub fn fk_kernel(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot: &RobotInfo) {
+    let n = robot.n_joints;
+    let mut result = forward_kinematics(qcos, qsin, v, a, robot);
+    register_vector_output(&mut result.omi_translations[n - 1], "translation");
+    register_vector_output(&mut result.all_v[n - 1], "velocity");
+    register_vector_output(&mut result.all_a[n - 1], "acceleration");
+}
+
+pub fn rnea_kernel(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot: &RobotInfo) {
+    let mut tau = rnea(qcos, qsin, v, a, robot);
+    register_vector_output(&mut tau, "tau");
+}
+
+pub fn derivatives_kernel(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot: &RobotInfo) {
+    let (mut da, mut dv, mut dq, _) = rneaderivatives(qcos, qsin, v, a, robot);
+    register_matrix_output(&mut da, "da");
+    register_matrix_output(&mut dv, "dv");
+    register_matrix_output(&mut dq, "dq");
+}
+
+/// Compares the derivatives of rnea at `state`, the rows q, v and a, with
+/// central differences
+pub fn assert_derivatives<const N: usize>(model: &RobotModel, state: [[f64; N]; 3]) {
+    assert_kernel_derivatives(model, state, rnea_kernel, derivatives_kernel);
+}
+
+/// Compares the derivatives output by `derivatives` with central differences
+/// of the torques output by `rnea`
+pub fn assert_kernel_derivatives<const N: usize>(model: &RobotModel, state: [[f64; N]; 3], rnea: Kernel, derivatives: Kernel) {
+    let n = model.n_joints();
+    let derivatives = evaluate(model, &state[0], &state[1], &state[2], derivatives);
+
+    // central differences of rnea in q, v and a
+    let h = 1e-6;
+    for (variable, name) in ["dq", "dv", "da"].into_iter().enumerate() {
+        for j in 0..n {
+            let tau = |step: f64| {
+                let mut state = state;
+                state[variable][j] += step;
+                evaluate(model, &state[0], &state[1], &state[2], rnea).swap_remove("tau").unwrap()
+            };
+            let (plus, minus) = (tau(h), tau(-h));
+            for i in 0..n {
+                let expected = (plus[i] - minus[i]) / (2.0 * h);
+                // dq and dv hold the derivatives of tau in q_j and v_j in row j,
+                // da holds the lower triangle of the mass matrix
+                let actual = match name {
+                    "da" if j > i => continue,
+                    "da" => derivatives[name][i * n + j],
+                    _ => derivatives[name][j * n + i],
+                };
+                assert!((expected - actual).abs() < 1e-6, "{}[{}][{}]: {} != {}", name, i, j, expected, actual);
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use indexmap::IndexMap;
+use roboprec::{
+    Config, Precision, Real, add_input_scalar, add_input_vector,
+    analysis::daisy::DaisyRange,
+    codegen::{
+        c_with_conversion::generate_c_with_conversion,
+        interface::output_fields,
+        simulator::{CSimulator, CValue, simulate_c},
+    },
+    config::CInterface,
+    ir::{
+        expr::Expr,
+        interpreter::{Value, interpret},
+        program::{Program, get_program, update_program_outputs},
+        unroll::unroll_ir,
+    },
+    register_scalar_output, register_vector_output, validate_generated_c, validate_simulated,
+};
+
+use crate::{
+    algorithms::{forward_kinematics::forward_kinematics, robots::roarm_m2::roarm_m2},
+    tests::{
+        codegen_tests::{test_output_dir, uniform_precisions},
+        test_wrapper::run_default_test,
+    },
+};
+
+#[test]
+fn test_simulator_matches_compiled_c() {
+    run_default_test(|| {
+        simulator_matches_compiled_c();
+    });
+}
+
+#[test]
+fn test_simulator_c_semantics() {
+    run_default_test(|| {
+        simulator_c_semantics();
+    });
+}
+
+#[test]
+fn test_validate_simulated() {
+    run_default_test(|| {
+        validate_simulated_bounds();
+    });
+}
+
+const DOF: usize = 4;
+
+fn trace_fk() -> Program {
+    let range = vec![(Real::from_f64(-1.0), Real::from_f64(1.0)); DOF];
+    let qsin = add_input_vector("qsin", range.clone(), vec![0.0; DOF]);
+    let qcos = add_input_vector("qcos", range.clone(), vec![1.0; DOF]);
+    let v = add_input_vector("v", range.clone(), vec![0.1; DOF]);
+    let a = add_input_vector("a", range, vec![-0.2; DOF]);
+
+    let mut result = forward_kinematics(qcos, qsin, v, a, &roarm_m2());
+    register_vector_output(&mut result.omi_translations[DOF - 1], "translation");
+    unroll_ir(&get_program())
+}
+
+/// Mixes 16 and 32 bit variables with different fractional bits, so every
+/// kind of shift and cast of the generated code is exercised. Products always
+/// have at least the fractional bits of their result, as Daisy ensures.
+fn mixed_precisions(program: &Program) -> IndexMap<String, Precision> {
+    let mut names: Vec<String> = program.get_inputs().keys().map(|id| id.name().clone()).collect();
+    for expr in program.get_body() {
+        match expr {
+            Expr::Let { id, .. } => names.push(id.name().clone()),
+        }
+    }
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let precision = if i % 3 == 0 {
+                Precision::Fixed { total_bits: 16, fractional_bits: 11 + (i / 3 % 3) as i32 }
+            } else {
+                Precision::Fixed { total_bits: 32, fractional_bits: 16 + (i % 6) as i32 }
+            };
+            (name, precision)
+        })
+        .collect()
+}
+
+/// Compiles a driver around the generated kernel and returns its outputs for each sample
+fn run_compiled_c(program: &Program, config: &Config, samples: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let folder = config.output_dir.join("codegen/C");
+    let kernel = std::fs::canonicalize(folder.join("codegen_with_conversion.cpp")).unwrap();
+    let n_inputs = program.get_inputs().len();
+    let args: Vec<String> = (0..n_inputs).map(|i| format!("in[{}]", i)).collect();
+    let prints: String = output_fields(program, CInterface::Scalar)
+        .iter()
+        .map(|(field, _)| format!("        printf(\"%.17g \", out.{});\n", field))
+        .collect();
+    let driver = format!(
+        "#include <math.h>\n#include <stdint.h>\n#include <stdio.h>\n#include \"{}\"\n\
+         int main(void) {{\n    double in[{}];\n    while (1) {{\n\
+         for (int i = 0; i < {}; i++) if (scanf(\"%lf\", &in[i]) != 1) return 0;\n\
+         codegen_output_t out = codegen({});\n{}        printf(\"\\n\");\n    }}\n}}\n",
+        kernel.display(),
+        n_inputs,
+        n_inputs,
+        args.join(", "),
+        prints
+    );
+    std::fs::write(folder.join("driver.cpp"), driver).unwrap();
+    let status = std::process::Command::new("cc")
+        .args(["-x", "c++", "-O1", "-o"])
+        .arg(folder.join("driver"))
+        .arg(folder.join("driver.cpp"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let input: String = samples
+        .iter()
+        .flatten()
+        .map(|value| format!("{:e}\n", value))
+        .collect();
+    std::fs::write(folder.join("inputs.txt"), input).unwrap();
+    let output = std::process::Command::new(folder.join("driver"))
+        .stdin(std::fs::File::open(folder.join("inputs.txt")).unwrap())
+        .output()
+        .unwrap();
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| line.split_whitespace().map(|value| value.parse().unwrap()).collect())
+        .collect()
+}
+
+fn simulator_matches_compiled_c() {
+    let program = trace_fk();
+    let precisions = mixed_precisions(&program);
+    let config = Config {
+        output_dir: test_output_dir("simulator_matches_compiled_c"),
+        ..Default::default()
+    };
+    generate_c_with_conversion(&program, &precisions, &config).unwrap();
+
+    let samples: Vec<Vec<f64>> = (0..50)
+        .map(|sample| {
+            (0..program.get_inputs().len())
+                .map(|i| ((sample * 7 + i * 13) as f64 * 0.37).sin())
+                .collect()
+        })
+        .collect();
+    let compiled = run_compiled_c(&program, &config, &samples);
+    assert_eq!(compiled.len(), samples.len());
+
+    for (sample, compiled) in samples.iter().zip(compiled) {
+        let inputs = program
+            .get_inputs()
+            .keys()
+            .zip(sample)
+            .map(|(id, value)| (id.name().clone(), Value::Scalar(Real::from_f64(*value))))
+            .collect();
+        let simulated = simulate_c(&program, &precisions, &inputs).unwrap();
+        let simulated: Vec<f64> = simulated.values().flat_map(|value| value.elements()).copied().collect();
+        // bit for bit, not just within the analysed error
+        assert_eq!(simulated, compiled);
+    }
+}
+
+fn simulator_c_semantics() {
+    let x = add_input_scalar("x", (Real::from_f64(-4.0), Real::from_f64(4.0)), 3.0);
+    let mut doubled = &x + &x;
+    register_scalar_output(&mut doubled, "doubled");
+    let program = unroll_ir(&get_program());
+    let mut inputs = IndexMap::new();
+    inputs.insert("x".to_string(), Value::Scalar(Real::from_f64(3.0)));
+
+    // 3 * 2^5 + 3 * 2^5 = 192 does not fit into an int8_t and wraps to -64
+    let tiny = uniform_precisions(&program, Precision::Fixed { total_bits: 8, fractional_bits: 5 });
+    let outputs = simulate_c(&program, &tiny, &inputs).unwrap();
+    assert_eq!(outputs["doubled"], Value::Scalar(-2.0));
+
+    // inputs are truncated towards zero like the C cast
+    let mut negative = IndexMap::new();
+    negative.insert("x".to_string(), Value::Scalar(Real::from_f64(-0.3)));
+    let simulator = CSimulator::new(tiny.clone());
+    let raw = interpret(&program, &negative, &simulator).unwrap();
+    assert_eq!(
+        raw["doubled"],
+        Value::Scalar(CValue::Fixed { raw: -18, bits: 8, fractional_bits: 5 })
+    );
+
+    // the generated code rejects mixing floats and fixed-point
+    let mut mixed = tiny.clone();
+    mixed.insert("x".to_string(), Precision::Float32);
+    let error = simulate_c(&program, &mixed, &inputs).unwrap_err();
+    assert!(error.to_string().contains("Mixed types are not supported"));
+
+    let mut missing = tiny;
+    missing.shift_remove("x");
+    let error = simulate_c(&program, &missing, &inputs).unwrap_err();
+    assert!(error.to_string().contains("Precision for variable x not found"));
+}
+
+/// Pretends every output was analysed with the given error bound
+fn set_bounds(program: &mut Program, precisions: &IndexMap<String, Precision>, error: f64) {
+    let ranges = precisions
+        .keys()
+        .map(|name| (name.clone(), DaisyRange { lower: -4.0, upper: 4.0 }))
+        .collect();
+    let errors = precisions.keys().map(|name| (name.clone(), error)).collect();
+    update_program_outputs(program, &ranges, &errors);
+}
+
+fn validate_simulated_bounds() {
+    let mut program = trace_fk();
+    let precisions = uniform_precisions(&program, Precision::Fixed { total_bits: 32, fractional_bits: 20 });
+    set_bounds(&mut program, &precisions, 1e-4);
+
+    let report = validate_simulated(&program, &precisions, 200).unwrap();
+    assert_eq!(report.samples, 200);
+    assert_eq!(report.outputs.len(), 3);
+    assert_eq!(report.outputs[0].name, "translation_0");
+    assert!(report.all_within_bounds());
+    assert!(report.outputs.iter().all(|output| output.max_error > 0.0));
+
+    // same samples and same arithmetic as the compiled code
+    let config = Config {
+        output_dir: test_output_dir("validate_simulated"),
+        ..Default::default()
+    };
+    generate_c_with_conversion(&program, &precisions, &config).unwrap();
+    let compiled = validate_generated_c(&program, &config, 200).unwrap();
+    for (simulated, compiled) in report.outputs.iter().zip(&compiled.outputs) {
+        assert_eq!(simulated.max_error, compiled.max_error);
+    }
+
+    set_bounds(&mut program, &precisions, 1e-9);
+    let report = validate_simulated(&program, &precisions, 200).unwrap();
+    assert!(!report.all_within_bounds());
+}
@@ -0,0 +1,160 @@
+use roboprec::{Vector, register_vector_output};
+
+use crate::{
+    algorithms::{
+        forward_kinematics::forward_kinematics,
+        robots::{mjcf::parse_mjcf, robot_info::RobotInfo, urdf::parse_urdf},
+    },
+    tests::{
+        robot_fixtures::{INERTIA, Z, arms, assert_close, assert_derivatives, evaluate, joint, rnea_kernel, robot, tree},
+        test_wrapper::run_default_test,
+    },
+};
+
+#[test]
+fn test_tree_forward_kinematics() {
+    run_default_test(|| {
+        tree_forward_kinematics();
+    });
+}
+
+#[test]
+fn test_tree_rnea() {
+    run_default_test(|| {
+        tree_rnea();
+    });
+}
+
+#[test]
+fn test_tree_derivatives() {
+    run_default_test(|| {
+        tree_derivatives();
+    });
+}
+
+#[test]
+fn test_tree_import() {
+    run_default_test(|| {
+        tree_import();
+    });
+}
+
+fn translations_kernel(qcos: Vector, qsin: Vector, v: Vector, a: Vector, robot: &RobotInfo) {
+    let mut result = forward_kinematics(qcos, qsin, v, a, robot);
+    for (i, translation) in result.omi_translations.iter_mut().enumerate() {
+        register_vector_output(translation, &format!("translation_{}", i));
+    }
+}
+
+fn tree_forward_kinematics() {
+    let [torso, left, right, hand] = arms();
+    let tree = tree();
+    assert_eq!(tree.robot_info().parents, vec![None, Some(0), Some(0), Some(2)]);
+
+    // every branch moves like the serial chain from the base to its end
+    let (q, v, a) = ([0.3, -0.5, 0.8, 0.1], [0.4, -0.3, 0.2, 0.5], [0.1, 0.7, -0.4, 0.2]);
+    let actual = evaluate(&tree, &q, &v, &a, translations_kernel);
+    let left_arm = robot(vec![torso.clone(), left]);
+    let expected = evaluate(&left_arm, &q[..2], &v[..2], &a[..2], translations_kernel);
+    assert_close(&expected["translation_1"], &actual["translation_1"]);
+
+    let right_arm = robot(vec![torso, right, hand]);
+    let pick = |values: &[f64]| [values[0], values[2], values[3]];
+    let expected = evaluate(&right_arm, &pick(&q), &pick(&v), &pick(&a), translations_kernel);
+    for (serial, branched) in [(0, 0), (1, 2), (2, 3)] {
+        assert_close(
+            &expected[&format!("translation_{}", serial)],
+            &actual[&format!("translation_{}", branched)],
+        );
+    }
+}
+
+fn tree_rnea() {
+    let [torso, left, right, hand] = arms();
+    let (q, v, a) = ([0.3, -0.5, 0.8, 0.1], [0.4, -0.3, 0.2, 0.5], [0.1, 0.7, -0.4, 0.2]);
+    let tau = &evaluate(&tree(), &q, &v, &a, rnea_kernel)["tau"];
+
+    // the arms only load each other through the torso, whose torque is the sum
+    // of what the two arms need less the torso's own, counted twice
+    let left_arm = &evaluate(&robot(vec![torso.clone(), left]), &q[..2], &v[..2], &a[..2], rnea_kernel)["tau"];
+    let pick = |values: &[f64]| [values[0], values[2], values[3]];
+    let right_arm = robot(vec![torso.clone(), right, hand]);
+    let right_arm = &evaluate(&right_arm, &pick(&q), &pick(&v), &pick(&a), rnea_kernel)["tau"];
+    let torso = &evaluate(&robot(vec![torso]), &q[..1], &v[..1], &a[..1], rnea_kernel)["tau"];
+
+    let expected = [left_arm[0] + right_arm[0] - torso[0], left_arm[1], right_arm[1], right_arm[2]];
+    assert_close(&expected, tau);
+}
+
+fn tree_derivatives() {
+    let state = [[0.3, -0.5, 0.8, 0.1], [0.4, -0.3, 0.2, 0.5], [0.1, 0.7, -0.4, 0.2]];
+    assert_derivatives(&tree(), state);
+
+    // a second branch off the end of the first one
+    let mut model = tree();
+    model.joints.push(joint([0.1, 0.0, 0.1], [0.0, 0.3, 0.0], Z, [0.0, 0.0, 0.1], INERTIA));
+    model.joints[4].parent = Some(2);
+    assert_derivatives(&model, [[0.3, -0.5, 0.8, 0.1, 0.6], [0.4, -0.3, 0.2, 0.5, -0.2], [0.1, 0.7, -0.4, 0.2, 0.3]]);
+}
+
+fn tree_import() {
+    // a torso with two arms, the right one on a fixed shoulder
+    let urdf = r#"
+        <robot name="arms">
+          <link name="base"/>
+          <link name="torso"/>
+          <link name="left"/>
+          <link name="shoulder"/>
+          <link name="right"/>
+          <link name="hand"/>
+          <joint name="waist" type="continuous">
+            <parent link="base"/>
+            <child link="torso"/>
+          </joint>
+          <joint name="left_arm" type="continuous">
+            <parent link="torso"/>
+            <child link="left"/>
+          </joint>
+          <joint name="to_shoulder" type="fixed">
+            <origin xyz="0 -0.2 0.4"/>
+            <parent link="torso"/>
+            <child link="shoulder"/>
+          </joint>
+          <joint name="right_arm" type="continuous">
+            <parent link="shoulder"/>
+            <child link="right"/>
+          </joint>
+          <joint name="wrist" type="continuous">
+            <parent link="right"/>
+            <child link="hand"/>
+          </joint>
+        </robot>"#;
+    let model = parse_urdf(urdf).unwrap();
+    let names: Vec<&str> = model.joints.iter().map(|joint| joint.name.as_str()).collect();
+    assert_eq!(names, ["waist", "left_arm", "right_arm", "wrist"]);
+    let parents: Vec<Option<usize>> = model.joints.iter().map(|joint| joint.parent).collect();
+    assert_eq!(parents, [None, Some(0), Some(0), Some(2)]);
+    assert_eq!(model.joints[2].translation, [0.0, -0.2, 0.4]);
+
+    let mjcf = r#"
+        <mujoco model="arms">
+          <worldbody>
+            <body name="torso">
+              <joint name="waist"/>
+              <body name="left"><joint name="left_arm"/></body>
+              <body name="shoulder" pos="0 -0.2 0.4">
+                <body name="right">
+                  <joint name="right_arm"/>
+                  <body name="hand"><joint name="wrist"/></body>
+                </body>
+              </body>
+            </body>
+          </worldbody>
+        </mujoco>"#;
+    let model = parse_mjcf(mjcf).unwrap();
+    let names: Vec<&str> = model.joints.iter().map(|joint| joint.name.as_str()).collect();
+    assert_eq!(names, ["waist", "left_arm", "right_arm", "wrist"]);
+    let parents: Vec<Option<usize>> = model.joints.iter().map(|joint| joint.parent).collect();
+    assert_eq!(parents, [None, Some(0), Some(0), Some(2)]);
+    assert_eq!(model.joints[2].translation, [0.0, -0.2, 0.4]);
+}
@@ -0,0 +1,191 @@
+use roboprec::ir::program::take_program;
+
+use crate::{
+    algorithms::robots::{
+        robot_info::JointType,
+        roarm_m2::{roarm_m2, roarm_m2_get_bounds},
+        urdf::{load_urdf, parse_urdf},
+    },
+    examples::joint_inputs,
+    tests::{
+        robot_fixtures::{assert_close, evaluate, fk_kernel, fk_outputs, input_ranges},
+        test_wrapper::run_default_test,
+    },
+};
+
+#[test]
+fn test_urdf_roarm_m2() {
+    run_default_test(|| {
+        urdf_roarm_m2();
+    });
+}
+
+#[test]
+fn test_urdf_fixed_joints() {
+    run_default_test(|| {
+        urdf_fixed_joints();
+    });
+}
+
+#[test]
+fn test_urdf_prismatic() {
+    run_default_test(|| {
+        urdf_prismatic();
+    });
+}
+
+#[test]
+fn test_urdf_errors() {
+    run_default_test(|| {
+        urdf_errors();
+    });
+}
+
+const ROARM_M2: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/models/roarm_m2.urdf");
+
+#[allow(clippy::approx_constant)]
+fn urdf_roarm_m2() {
+    let model = load_urdf(ROARM_M2).unwrap();
+    assert_eq!(model.name, "roarm_m2");
+    assert_eq!(model.n_joints(), 4);
+    assert_eq!(model.joints[3].name, "link3_to_gripper_link");
+    assert_eq!(model.bounds(), roarm_m2_get_bounds());
+    assert_eq!(model.joints[0].translation, [0.0100000008759151, 0.0, 0.123059270461044]);
+    assert_eq!(model.joints[1].rpy, [-1.5708, -1.5708, 0.0]);
+    assert_eq!(model.joints[2].lever, [0.002, -0.13687, 0.0059]);
+    assert_eq!(model.joints[1].mass, 0.0703216);
+    assert_eq!(model.joints[3].inertia[1], [0.0, 1.82071E-06, 0.0]);
+    assert!(model.joints.iter().all(|joint| joint.joint_type == JointType::Revolute([0.0, 0.0, 1.0])));
+
+    // the imported robot traces the same kinematics as the hand-written one
+    let expected = fk_outputs(roarm_m2());
+    let imported = fk_outputs(model.robot_info());
+    assert_eq!(expected.len(), 9);
+    for (expected, imported) in expected.iter().zip(&imported) {
+        assert!((expected - imported).abs() < 1e-7, "{} != {}", expected, imported);
+    }
+}
+
+fn urdf_fixed_joints() {
+    // a flange and a tool are fixed to link1, the tool turned about z
+    let urdf = r#"
+        <robot name="arm">
+          <link name="base"/>
+          <link name="link1">
+            <inertial>
+              <origin xyz="0 0 0.1"/>
+              <mass value="1.0"/>
+              <inertia ixx="0.01" ixy="0" ixz="0" iyy="0.01" iyz="0" izz="0.01"/>
+            </inertial>
+          </link>
+          <link name="flange"/>
+          <link name="tool">
+            <inertial>
+              <origin xyz="0.1 0 0"/>
+              <mass value="1.0"/>
+              <inertia ixx="0.02" ixy="0" ixz="0" iyy="0.03" iyz="0" izz="0.04"/>
+            </inertial>
+          </link>
+          <link name="link2"/>
+          <joint name="joint1" type="continuous">
+            <parent link="base"/>
+            <child link="link1"/>
+            <axis xyz="0 0 1"/>
+          </joint>
+          <joint name="to_flange" type="fixed">
+            <origin xyz="0 0 0.2" rpy="0 0 1.5707963267948966"/>
+            <parent link="link1"/>
+            <child link="flange"/>
+          </joint>
+          <joint name="to_tool" type="fixed">
+            <parent link="flange"/>
+            <child link="tool"/>
+          </joint>
+          <joint name="joint2" type="revolute">
+            <origin xyz="0.5 0 0"/>
+            <parent link="flange"/>
+            <child link="link2"/>
+            <axis xyz="0 0 1"/>
+            <limit lower="-1" upper="1"/>
+          </joint>
+        </robot>"#;
+    let model = parse_urdf(urdf).unwrap();
+    assert_eq!(model.n_joints(), 2);
+    assert_eq!(model.joints[0].bounds, (-std::f64::consts::PI, std::f64::consts::PI));
+
+    // the tool at (0, 0.1, 0.2) and link1 merge into one body
+    let link1 = &model.joints[0];
+    assert_eq!(link1.mass, 2.0);
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+    assert!(link1.lever.iter().zip([0.0, 0.05, 0.15]).all(|(a, b)| close(*a, b)));
+    // the tool's inertia turns with the flange, and both bodies are 0.05 * sqrt(2) from the center
+    let expected = [[0.01 + 0.03 + 0.01, 0.0, 0.0], [0.0, 0.01 + 0.02 + 0.005, -0.005], [0.0, -0.005, 0.01 + 0.04 + 0.005]];
+    for (row, expected) in link1.inertia.iter().zip(expected) {
+        assert!(row.iter().zip(expected).all(|(a, b)| close(*a, b)), "{:?}", link1.inertia);
+    }
+
+    // joint2 is placed through the fixed flange
+    let joint2 = &model.joints[1];
+    assert!(joint2.translation.iter().zip([0.0, 0.5, 0.2]).all(|(a, b)| close(*a, b)));
+    assert!(joint2.rpy.iter().zip([0.0, 0.0, std::f64::consts::FRAC_PI_2]).all(|(a, b)| close(*a, b)));
+    assert_eq!(joint2.mass, 0.0);
+}
+
+fn urdf_prismatic() {
+    // a turntable carrying a slide that extends over [-0.1, 0.4] m along x
+    let urdf = r#"
+        <robot name="slide">
+          <link name="base"/>
+          <link name="table"/>
+          <link name="carriage">
+            <inertial>
+              <mass value="2.0"/>
+              <inertia ixx="0.01" ixy="0" ixz="0" iyy="0.01" iyz="0" izz="0.01"/>
+            </inertial>
+          </link>
+          <joint name="turn" type="revolute">
+            <parent link="base"/>
+            <child link="table"/>
+            <axis xyz="0 0 1"/>
+            <limit lower="-1" upper="1"/>
+          </joint>
+          <joint name="extend" type="prismatic">
+            <origin xyz="0 0 0.2"/>
+            <parent link="table"/>
+            <child link="carriage"/>
+            <axis xyz="1 0 0"/>
+            <limit lower="-0.1" upper="0.4"/>
+          </joint>
+        </robot>"#;
+    let model = parse_urdf(urdf).unwrap();
+    assert_eq!(model.joint_types(), vec![JointType::Revolute([0.0, 0.0, 1.0]), JointType::Prismatic([1.0, 0.0, 0.0])]);
+
+    // the slide's displacement ranges over its limits in metres
+    let (qcos, qsin, v, a) = joint_inputs(&model.bounds(), &model.joint_types());
+    fk_kernel(qcos, qsin, v, a, &model.robot_info());
+    let program = take_program();
+    assert_eq!(input_ranges(&program, "qsin"), vec![((-1.0f64).sin(), 1.0f64.sin()), (-0.1, 0.4)]);
+    assert_eq!(input_ranges(&program, "qcos"), vec![(1.0f64.cos(), 1.0), (1.0, 1.0)]);
+
+    let fk = evaluate(&model, &[0.0, 0.3], &[0.0; 2], &[0.0; 2], fk_kernel);
+    assert_close(&fk["translation"], &[0.3, 0.0, 0.2]);
+}
+
+fn urdf_errors() {
+    let joint = |kind: &str, extra: &str| {
+        format!(
+            r#"<robot name="arm"><link name="a"/><link name="b"/>
+               <joint name="j" type="{}"><parent link="a"/><child link="b"/>{}</joint></robot>"#,
+            kind, extra
+        )
+    };
+    let error = |text: &str| format!("{:#}", parse_urdf(text).unwrap_err());
+
+    assert!(error(&joint("floating", "")).contains("Joint j has type floating"));
+    assert!(error(&joint("revolute", r#"<axis xyz="0 0 1"/>"#)).contains("Revolute joint j has no limit"));
+    assert!(error(&joint("prismatic", r#"<axis xyz="0 0 1"/>"#)).contains("Prismatic joint j has no limit"));
+    assert!(error(&joint("continuous", r#"<axis xyz="0 0 0"/>"#)).contains("Joint j has a zero axis"));
+    assert!(error(&joint("fixed", "")).contains("no revolute, continuous or prismatic joints"));
+    assert!(error(r#"<robot><link name="a"/><link name="b"/></robot>"#).contains("Expected one root link"));
+    assert!(error("<robot>").contains("Failed to parse URDF"));
+}
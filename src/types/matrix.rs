@@ -1,7 +1,9 @@
 use log::info;
 
+use anyhow::Result;
+
 use crate::{
-    analysis::real::Real,
+    analysis::{real::Real, result::AnalysisResult},
     ir::expr::{
         OprUnary, create_construct_matrix_expr, create_index_matrix_element_expr,
         create_index_matrix_row_expr, create_matrix_constant, create_unary_matrix_expr,
@@ -56,6 +58,7 @@ impl Matrix {
     /// let M = Matrix::new("my_mat", vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
     /// let M = Matrix!([[1.0, 2.0], [3.0, 4.0]]);  // Preferred: use macro
     /// ```
+    #[track_caller]
     pub fn new(name: &str, values: Vec<Vec<f64>>) -> Self {
         let rational_values: Vec<Vec<Real>> = values
             .into_iter()
@@ -64,6 +67,7 @@ impl Matrix {
         Self::new_rational(name, rational_values)
     }
 
+    #[track_caller]
     pub fn new_rational(name: &str, values: Vec<Vec<Real>>) -> Self {
         let new_id = create_matrix_constant(name, values.clone());
         Self {
@@ -73,6 +77,7 @@ impl Matrix {
     }
 
     // This function simply redefines a new value to this vector.
+    #[track_caller]
     pub fn define(&mut self, new_name: String) -> Self {
         let new_id = create_unary_matrix_expr(&new_name, self.id.clone(), OprUnary::AssignNoOpt);
         Self {
@@ -88,8 +93,30 @@ impl Matrix {
             .collect()
     }
 
+    /// Analysed worst-case absolute error of every element of this output, row by row.
+    ///
+    /// The matrix must be the handle passed to [`crate::register_matrix_output`]
+    /// before the analysis that produced `result`.
+    pub fn error_bound(&self, result: &AnalysisResult) -> Result<Vec<Vec<f64>>> {
+        Ok(result
+            .matrix_output(&self.id)?
+            .iter()
+            .map(|row| row.iter().map(|element| element.error).collect())
+            .collect())
+    }
+
+    /// Analysed range `(lower, upper)` of every element of this output, row by row.
+    pub fn range(&self, result: &AnalysisResult) -> Result<Vec<Vec<(f64, f64)>>> {
+        Ok(result
+            .matrix_output(&self.id)?
+            .iter()
+            .map(|row| row.iter().map(|element| element.range).collect())
+            .collect())
+    }
+
     /// Create a matrix from scalars using the Construct operation
     /// The scalars should be arranged in row-major order to form the matrix
+    #[track_caller]
     pub fn from_scalars(name: &str, scalars: Vec<Vec<&Scalar>>) -> Self {
         let ids: Vec<Vec<Identifier>> = scalars
             .iter()
@@ -107,6 +134,7 @@ impl Matrix {
     }
 
     /// Create a matrix from another matrix using the Construct operation
+    #[track_caller]
     pub fn from_matrix(name: &str, matrix: &Matrix) -> Self {
         let ids: Vec<Vec<Identifier>> = vec![vec![matrix.id.clone()]];
         let new_id = create_construct_matrix_expr(name, ids);
@@ -117,6 +145,7 @@ impl Matrix {
         Self { id: new_id, value }
     }
 
+    #[track_caller]
     pub fn from_vector(name: &str, vector: &Vector) -> Self {
         // For this one, get all scalars independently, then construct the matrix
         let mut ids = vec![vec![]; vector.size()];
@@ -139,6 +168,7 @@ impl Matrix {
 
     /// Create a matrix from multiple vectors
     /// Each vector becomes a row in the resulting matrix
+    #[track_caller]
     pub fn from_vectors(name: &str, vectors: Vec<&Vector>) -> Self {
         // iterate over vectors, if their size is not equal, panic
         if vectors.is_empty() {
@@ -151,10 +181,14 @@ impl Matrix {
             }
         }
 
-        let ids: Vec<Vec<Identifier>> = vectors
-            .iter()
-            .map(|v| (0..v.size()).map(|i| v.get(i).id.clone()).collect())
-            .collect();
+        let mut ids: Vec<Vec<Identifier>> = vec![];
+        for v in &vectors {
+            let mut row = vec![];
+            for i in 0..v.size() {
+                row.push(v.get(i).id.clone());
+            }
+            ids.push(row);
+        }
         let new_id = create_construct_matrix_expr(name, ids);
 
         // Construct the matrix values from vector values
@@ -171,6 +205,7 @@ impl Matrix {
     }
 
     /// Create a matrix by concatenating matrices horizontally
+    #[track_caller]
     pub fn from_matrices_horizontal(name: &str, matrices: Vec<&Matrix>) -> Self {
         let ids: Vec<Vec<Identifier>> = vec![matrices.iter().map(|m| m.id.clone()).collect()];
         let new_id = create_construct_matrix_expr(name, ids);
@@ -200,6 +235,7 @@ impl Matrix {
     }
 
     /// Create a matrix by concatenating matrices vertically
+    #[track_caller]
     pub fn from_matrices_vertical(name: &str, matrices: Vec<&Matrix>) -> Self {
         let ids: Vec<Vec<Identifier>> = matrices.iter().map(|m| vec![m.id.clone()]).collect();
         let new_id = create_construct_matrix_expr(name, ids);
@@ -224,6 +260,7 @@ impl Matrix {
     }
 
     /// Get a specific row as a Vector by index
+    #[track_caller]
     pub fn get_row(&self, row_index: usize) -> Vector {
         let new_id = create_index_matrix_row_expr(
             &format!("{}_row_{}", self.id.name, row_index),
@@ -242,6 +279,7 @@ impl Matrix {
         }
     }
 
+    #[track_caller]
     pub fn to_vector(&self) -> Vector {
         // if the column size is not 1, we cannot convert to a vector
         assert_eq!(
@@ -251,9 +289,10 @@ impl Matrix {
         );
 
         // use get to convert all elements into a scalar
-        let all_get = (0..self.size().0)
-            .map(|i| self.get(i, 0))
-            .collect::<Vec<Scalar>>();
+        let mut all_get: Vec<Scalar> = vec![];
+        for i in 0..self.size().0 {
+            all_get.push(self.get(i, 0));
+        }
 
         //println!("converting to vector with scalars: {:?}", all_get);
 
@@ -262,6 +301,7 @@ impl Matrix {
 
     /// TODO: DEPRECATE THIS IN FAVOR OF AT
     /// Get a specific element as a Scalar by row and column index
+    #[track_caller]
     pub fn get(&self, row_index: usize, col_index: usize) -> Scalar {
         let new_id = create_index_matrix_element_expr(
             &format!("{}_elem_{}_{}", self.id.name, row_index, col_index),
@@ -284,6 +324,7 @@ impl Matrix {
         }
     }
 
+    #[track_caller]
     pub fn at(&self, row_index: usize, col_index: usize) -> Scalar {
         self.get(row_index, col_index)
     }
@@ -301,6 +342,7 @@ impl Matrix {
         &self.id.name
     }
 
+    #[track_caller]
     pub fn matmul(&self, other: &Matrix) -> Matrix {
         let (rows_a, cols_a) = self.size();
         let (rows_b, cols_b) = other.size();
@@ -322,28 +364,33 @@ impl Matrix {
         // create vectors from the rows of the first matrix and columns of the second matrix
         // then do dot product
         // compiler optimizations are simpler this way
+        // (loops instead of closures, so the traced expressions keep the caller's location)
         let mut first_matrix_rows: Vec<Vec<Scalar>> = vec![];
         for i in 0..rows_a {
-            let row = (0..cols_a).map(|j| self.get(i, j)).collect::<Vec<Scalar>>();
+            let mut row = vec![];
+            for j in 0..cols_a {
+                row.push(self.get(i, j));
+            }
             first_matrix_rows.push(row);
         }
         let mut second_matrix_cols: Vec<Vec<Scalar>> = vec![];
         for j in 0..cols_b {
-            let col = (0..rows_b)
-                .map(|i| other.get(i, j))
-                .collect::<Vec<Scalar>>();
+            let mut col = vec![];
+            for i in 0..rows_b {
+                col.push(other.get(i, j));
+            }
             second_matrix_cols.push(col);
         }
 
         // now create vectors
-        let first_matrix_row_vectors: Vec<Vector> = first_matrix_rows
-            .iter()
-            .map(|row| Vector::from_scalars("row_vector", row.iter().collect()))
-            .collect();
-        let second_matrix_col_vectors: Vec<Vector> = second_matrix_cols
-            .iter()
-            .map(|col| Vector::from_scalars("col_vector", col.iter().collect()))
-            .collect();
+        let mut first_matrix_row_vectors: Vec<Vector> = vec![];
+        for row in &first_matrix_rows {
+            first_matrix_row_vectors.push(Vector::from_scalars("row_vector", row.iter().collect()));
+        }
+        let mut second_matrix_col_vectors: Vec<Vector> = vec![];
+        for col in &second_matrix_cols {
+            second_matrix_col_vectors.push(Vector::from_scalars("col_vector", col.iter().collect()));
+        }
 
         // now do dot product for each pair
         let mut result_ids = vec![vec![Identifier::new_scalar("bos"); cols_b]; rows_a];
@@ -383,6 +430,7 @@ impl Matrix {
         }
     }
 
+    #[track_caller]
     pub fn matmul_vec(&self, other: &Vector) -> Vector {
         let (_, cols_a) = self.size();
         let rows_b = other.size();
@@ -397,6 +445,7 @@ impl Matrix {
         self.matmul(&vector_as_matrix).to_vector()
     }
 
+    #[track_caller]
     pub fn transpose(&self) -> Matrix {
         let (rows, cols) = self.size();
         let new_id = create_unary_matrix_expr(
@@ -416,6 +465,7 @@ impl Matrix {
     }
 
     /// Set function will create a new matrix, but will set one element to the new scalar
+    #[track_caller]
     pub fn set(&mut self, index: (usize, usize), scalar: &Scalar) {
         let (row_index, col_index) = index;
         assert!(
@@ -451,6 +501,7 @@ impl Matrix {
         self.value = values;
     }
 
+    #[track_caller]
     pub fn zero(row: usize, col: usize) -> Matrix {
         let new_id = create_matrix_constant(
             &format!("zero_matrix_{}_{}", row, col),
@@ -12,6 +12,7 @@ use crate::{
 impl Add for Scalar {
     type Output = Self;
 
+    #[track_caller]
     fn add(self, other: Self) -> Self::Output {
         &self + &other
     }
@@ -21,6 +22,7 @@ impl Add for Scalar {
 impl<'a> Add<&'a Scalar> for Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn add(self, other: &'a Scalar) -> Self::Output {
         &self + other
     }
@@ -29,6 +31,7 @@ impl<'a> Add<&'a Scalar> for Scalar {
 impl Add<Scalar> for &Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn add(self, other: Scalar) -> Self::Output {
         self + &other
     }
@@ -37,6 +40,7 @@ impl Add<Scalar> for &Scalar {
 impl<'b> Add<&'b Scalar> for &Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn add(self, other: &'b Scalar) -> Self::Output {
         let new_id = create_binary_scalar_expr(
             &format!("{}_plus_{}", self.id.name, other.id.name),
@@ -56,6 +60,7 @@ impl<'b> Add<&'b Scalar> for &Scalar {
 impl Add for Matrix {
     type Output = Self;
 
+    #[track_caller]
     fn add(self, other: Self) -> Self::Output {
         &self + &other
     }
@@ -65,6 +70,7 @@ impl Add for Matrix {
 impl<'a> Add<&'a Matrix> for Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn add(self, other: &'a Matrix) -> Self::Output {
         &self + other
     }
@@ -73,6 +79,7 @@ impl<'a> Add<&'a Matrix> for Matrix {
 impl Add<Matrix> for &Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn add(self, other: Matrix) -> Self::Output {
         self + &other
     }
@@ -81,6 +88,7 @@ impl Add<Matrix> for &Matrix {
 impl<'b> Add<&'b Matrix> for &Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn add(self, other: &'b Matrix) -> Self::Output {
         let size0 = self.value.len();
         let size1 = self.value[0].len();
@@ -109,6 +117,7 @@ impl<'b> Add<&'b Matrix> for &Matrix {
 impl<'b> Add<&'b Matrix> for &Scalar {
     type Output = Matrix;
 
+    #[track_caller]
     fn add(self, other: &'b Matrix) -> Self::Output {
         let size0 = other.value.len();
         let size1 = other.value[0].len();
@@ -135,6 +144,7 @@ impl<'b> Add<&'b Matrix> for &Scalar {
 impl Add<Matrix> for &Scalar {
     type Output = Matrix;
 
+    #[track_caller]
     fn add(self, other: Matrix) -> Self::Output {
         self + &other
     }
@@ -143,6 +153,7 @@ impl Add<Matrix> for &Scalar {
 impl<'a> Add<&'a Matrix> for Scalar {
     type Output = Matrix;
 
+    #[track_caller]
     fn add(self, other: &'a Matrix) -> Self::Output {
         &self + other
     }
@@ -151,6 +162,7 @@ impl<'a> Add<&'a Matrix> for Scalar {
 impl Add<Matrix> for Scalar {
     type Output = Matrix;
 
+    #[track_caller]
     fn add(self, other: Matrix) -> Self::Output {
         &self + &other
     }
@@ -160,6 +172,7 @@ impl Add<Matrix> for Scalar {
 impl Add<Scalar> for Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn add(self, other: Scalar) -> Self::Output {
         &other + &self
     }
@@ -168,6 +181,7 @@ impl Add<Scalar> for Matrix {
 impl<'a> Add<&'a Scalar> for Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn add(self, other: &'a Scalar) -> Self::Output {
         other + &self
     }
@@ -176,6 +190,7 @@ impl<'a> Add<&'a Scalar> for Matrix {
 impl Add<Scalar> for &Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn add(self, other: Scalar) -> Self::Output {
         &other + self
     }
@@ -184,6 +199,7 @@ impl Add<Scalar> for &Matrix {
 impl<'b> Add<&'b Scalar> for &Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn add(self, other: &'b Scalar) -> Self::Output {
         other + self
     }
@@ -193,6 +209,7 @@ impl<'b> Add<&'b Scalar> for &Matrix {
 impl Add for Vector {
     type Output = Self;
 
+    #[track_caller]
     fn add(self, other: Self) -> Self::Output {
         &self + &other
     }
@@ -201,6 +218,7 @@ impl Add for Vector {
 impl<'a> Add<&'a Vector> for Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn add(self, other: &'a Vector) -> Self::Output {
         &self + other
     }
@@ -209,6 +227,7 @@ impl<'a> Add<&'a Vector> for Vector {
 impl Add<Vector> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn add(self, other: Vector) -> Self::Output {
         self + &other
     }
@@ -217,6 +236,7 @@ impl Add<Vector> for &Vector {
 impl<'b> Add<&'b Vector> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn add(self, other: &'b Vector) -> Self::Output {
         let size = self.value.len();
         let new_id = create_binary_vector_expr(
@@ -246,6 +266,7 @@ impl<'b> Add<&'b Vector> for &Vector {
 impl<'b> Add<&'b Vector> for &Scalar {
     type Output = Vector;
 
+    #[track_caller]
     fn add(self, other: &'b Vector) -> Self::Output {
         let size = other.value.len();
         let new_id = create_binary_vector_expr(
@@ -266,6 +287,7 @@ impl<'b> Add<&'b Vector> for &Scalar {
 impl Add<Vector> for &Scalar {
     type Output = Vector;
 
+    #[track_caller]
     fn add(self, other: Vector) -> Self::Output {
         self + &other
     }
@@ -274,6 +296,7 @@ impl Add<Vector> for &Scalar {
 impl<'a> Add<&'a Vector> for Scalar {
     type Output = Vector;
 
+    #[track_caller]
     fn add(self, other: &'a Vector) -> Self::Output {
         &self + other
     }
@@ -282,6 +305,7 @@ impl<'a> Add<&'a Vector> for Scalar {
 impl Add<Vector> for Scalar {
     type Output = Vector;
 
+    #[track_caller]
     fn add(self, other: Vector) -> Self::Output {
         &self + &other
     }
@@ -291,6 +315,7 @@ impl Add<Vector> for Scalar {
 impl Add<Scalar> for Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn add(self, other: Scalar) -> Self::Output {
         &other + &self
     }
@@ -299,6 +324,7 @@ impl Add<Scalar> for Vector {
 impl<'a> Add<&'a Scalar> for Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn add(self, other: &'a Scalar) -> Self::Output {
         other + &self
     }
@@ -307,6 +333,7 @@ impl<'a> Add<&'a Scalar> for Vector {
 impl Add<Scalar> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn add(self, other: Scalar) -> Self::Output {
         &other + self
     }
@@ -315,6 +342,7 @@ impl Add<Scalar> for &Vector {
 impl<'b> Add<&'b Scalar> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn add(self, other: &'b Scalar) -> Self::Output {
         other + self
     }
@@ -10,6 +10,7 @@ use crate::{
 impl Div for Scalar {
     type Output = Self;
 
+    #[track_caller]
     fn div(self, other: Self) -> Self::Output {
         &self / &other
     }
@@ -19,6 +20,7 @@ impl Div for Scalar {
 impl<'a> Div<&'a Scalar> for Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn div(self, other: &'a Scalar) -> Self::Output {
         &self / other
     }
@@ -27,6 +29,7 @@ impl<'a> Div<&'a Scalar> for Scalar {
 impl Div<Scalar> for &Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn div(self, other: Scalar) -> Self::Output {
         self / &other
     }
@@ -35,6 +38,7 @@ impl Div<Scalar> for &Scalar {
 impl<'b> Div<&'b Scalar> for &Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn div(self, other: &'b Scalar) -> Self::Output {
         let new_id = create_binary_scalar_expr(
             &format!("{}_div_{}", self.id.name, other.id.name),
@@ -54,6 +58,7 @@ impl<'b> Div<&'b Scalar> for &Scalar {
 impl Div for Vector {
     type Output = Self;
 
+    #[track_caller]
     fn div(self, other: Self) -> Self::Output {
         &self / &other
     }
@@ -62,6 +67,7 @@ impl Div for Vector {
 impl<'a> Div<&'a Vector> for Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn div(self, other: &'a Vector) -> Self::Output {
         &self / other
     }
@@ -70,6 +76,7 @@ impl<'a> Div<&'a Vector> for Vector {
 impl Div<Vector> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn div(self, other: Vector) -> Self::Output {
         self / &other
     }
@@ -78,6 +85,7 @@ impl Div<Vector> for &Vector {
 impl<'b> Div<&'b Vector> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn div(self, other: &'b Vector) -> Self::Output {
         let size = self.value.len();
         let new_id = create_binary_vector_expr(
@@ -107,6 +115,7 @@ impl<'b> Div<&'b Vector> for &Vector {
 impl Div<Scalar> for Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn div(self, scalar: Scalar) -> Vector {
         &self / &scalar
     }
@@ -115,6 +124,7 @@ impl Div<Scalar> for Vector {
 impl Div<Scalar> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn div(self, scalar: Scalar) -> Vector {
         self / &scalar
     }
@@ -123,6 +133,7 @@ impl Div<Scalar> for &Vector {
 impl<'a> Div<&'a Scalar> for Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn div(self, scalar: &'a Scalar) -> Vector {
         &self / scalar
     }
@@ -131,6 +142,7 @@ impl<'a> Div<&'a Scalar> for Vector {
 impl<'b> Div<&'b Scalar> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn div(self, scalar: &'b Scalar) -> Vector {
         let size0 = self.value.len();
         let new_id = create_binary_vector_expr(
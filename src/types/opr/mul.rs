@@ -12,6 +12,7 @@ use crate::{
 impl Mul for Scalar {
     type Output = Self;
 
+    #[track_caller]
     fn mul(self, other: Self) -> Self::Output {
         &self * &other
     }
@@ -21,6 +22,7 @@ impl Mul for Scalar {
 impl<'a> Mul<&'a Scalar> for Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn mul(self, other: &'a Scalar) -> Self::Output {
         &self * other
     }
@@ -29,6 +31,7 @@ impl<'a> Mul<&'a Scalar> for Scalar {
 impl Mul<Scalar> for &Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn mul(self, other: Scalar) -> Self::Output {
         self * &other
     }
@@ -37,6 +40,7 @@ impl Mul<Scalar> for &Scalar {
 impl<'b> Mul<&'b Scalar> for &Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn mul(self, other: &'b Scalar) -> Self::Output {
         let new_id = create_binary_scalar_expr(
             &format!("{}_mul_{}", self.id.name, other.id.name),
@@ -56,6 +60,7 @@ impl<'b> Mul<&'b Scalar> for &Scalar {
 impl Mul for Vector {
     type Output = Self;
 
+    #[track_caller]
     fn mul(self, other: Self) -> Self::Output {
         &self * &other
     }
@@ -64,6 +69,7 @@ impl Mul for Vector {
 impl<'a> Mul<&'a Vector> for Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn mul(self, other: &'a Vector) -> Self::Output {
         &self * other
     }
@@ -72,6 +78,7 @@ impl<'a> Mul<&'a Vector> for Vector {
 impl Mul<Vector> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn mul(self, other: Vector) -> Self::Output {
         self * &other
     }
@@ -80,6 +87,7 @@ impl Mul<Vector> for &Vector {
 impl<'b> Mul<&'b Vector> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn mul(self, other: &'b Vector) -> Self::Output {
         let size = self.value.len();
         let new_id = create_binary_vector_expr(
@@ -109,6 +117,7 @@ impl<'b> Mul<&'b Vector> for &Vector {
 impl Mul<Scalar> for Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn mul(self, scalar: Scalar) -> Vector {
         &self * &scalar
     }
@@ -117,6 +126,7 @@ impl Mul<Scalar> for Vector {
 impl<'a> Mul<&'a Scalar> for Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn mul(self, scalar: &'a Scalar) -> Vector {
         &self * scalar
     }
@@ -125,6 +135,7 @@ impl<'a> Mul<&'a Scalar> for Vector {
 impl<'b> Mul<&'b Scalar> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn mul(self, scalar: &'b Scalar) -> Vector {
         let size = self.value.len();
         let new_id = create_binary_vector_expr(
@@ -146,6 +157,7 @@ impl<'b> Mul<&'b Scalar> for &Vector {
 // scalar * vector
 impl Mul<Vector> for &Scalar {
     type Output = Vector;
+    #[track_caller]
     fn mul(self, vector: Vector) -> Vector {
         &vector * self
     }
@@ -155,6 +167,7 @@ impl Mul<Vector> for &Scalar {
 impl<'b> Mul<&'b Vector> for &Scalar {
     type Output = Vector;
 
+    #[track_caller]
     fn mul(self, vector: &'b Vector) -> Vector {
         vector * self
     }
@@ -162,6 +175,7 @@ impl<'b> Mul<&'b Vector> for &Scalar {
 
 impl Mul<Vector> for Scalar {
     type Output = Vector;
+    #[track_caller]
     fn mul(self, vector: Vector) -> Vector {
         &vector * &self
     }
@@ -169,6 +183,7 @@ impl Mul<Vector> for Scalar {
 
 impl Mul<&Vector> for Scalar {
     type Output = Vector;
+    #[track_caller]
     fn mul(self, vector: &Vector) -> Vector {
         vector * &self
     }
@@ -178,6 +193,7 @@ impl Mul<&Vector> for Scalar {
 impl<'b> Mul<&'b Matrix> for &Scalar {
     type Output = Matrix;
 
+    #[track_caller]
     fn mul(self, matrix: &'b Matrix) -> Matrix {
         let size0 = matrix.value.len();
         let size1 = matrix.value[0].len();
@@ -206,6 +222,7 @@ impl<'b> Mul<&'b Matrix> for &Scalar {
 impl Mul<Matrix> for Scalar {
     type Output = Matrix;
 
+    #[track_caller]
     fn mul(self, matrix: Matrix) -> Matrix {
         &self * &matrix
     }
@@ -214,6 +231,7 @@ impl Mul<Matrix> for Scalar {
 impl<'a> Mul<&'a Matrix> for Scalar {
     type Output = Matrix;
 
+    #[track_caller]
     fn mul(self, matrix: &'a Matrix) -> Matrix {
         &self * matrix
     }
@@ -222,6 +240,7 @@ impl<'a> Mul<&'a Matrix> for Scalar {
 impl Mul<Matrix> for &Scalar {
     type Output = Matrix;
 
+    #[track_caller]
     fn mul(self, matrix: Matrix) -> Matrix {
         self * &matrix
     }
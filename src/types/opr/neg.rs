@@ -12,6 +12,7 @@ use crate::{
 impl Neg for Scalar {
     type Output = Self;
 
+    #[track_caller]
     fn neg(self) -> Self::Output {
         -&self
     }
@@ -21,6 +22,7 @@ impl Neg for Scalar {
 impl Neg for &Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn neg(self) -> Self::Output {
         let new_id = create_unary_scalar_expr(
             &format!("neg_{}", self.id.name),
@@ -39,6 +41,7 @@ impl Neg for &Scalar {
 impl Neg for Vector {
     type Output = Self;
 
+    #[track_caller]
     fn neg(self) -> Self::Output {
         -&self
     }
@@ -47,6 +50,7 @@ impl Neg for Vector {
 impl Neg for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn neg(self) -> Self::Output {
         let new_id = create_unary_vector_expr("vector_neg", self.id.clone(), OprUnary::Neg);
 
@@ -63,6 +67,7 @@ impl Neg for &Vector {
 impl Neg for Matrix {
     type Output = Self;
 
+    #[track_caller]
     fn neg(self) -> Self::Output {
         -&self
     }
@@ -71,6 +76,7 @@ impl Neg for Matrix {
 impl Neg for &Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn neg(self) -> Self::Output {
         let new_id = create_unary_matrix_expr("matrix_neg", self.id.clone(), OprUnary::Neg);
 
@@ -12,6 +12,7 @@ use crate::{
 impl Sub for Scalar {
     type Output = Self;
 
+    #[track_caller]
     fn sub(self, other: Self) -> Self::Output {
         &self - &other
     }
@@ -21,6 +22,7 @@ impl Sub for Scalar {
 impl<'a> Sub<&'a Scalar> for Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn sub(self, other: &'a Scalar) -> Self::Output {
         &self - other
     }
@@ -29,6 +31,7 @@ impl<'a> Sub<&'a Scalar> for Scalar {
 impl Sub<Scalar> for &Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn sub(self, other: Scalar) -> Self::Output {
         self - &other
     }
@@ -37,6 +40,7 @@ impl Sub<Scalar> for &Scalar {
 impl<'b> Sub<&'b Scalar> for &Scalar {
     type Output = Scalar;
 
+    #[track_caller]
     fn sub(self, other: &'b Scalar) -> Self::Output {
         let new_id = create_binary_scalar_expr(
             &format!("{}_minus_{}", self.id.name, other.id.name),
@@ -56,6 +60,7 @@ impl<'b> Sub<&'b Scalar> for &Scalar {
 impl Sub for Matrix {
     type Output = Self;
 
+    #[track_caller]
     fn sub(self, other: Self) -> Self::Output {
         &self - &other
     }
@@ -65,6 +70,7 @@ impl Sub for Matrix {
 impl<'a> Sub<&'a Matrix> for Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn sub(self, other: &'a Matrix) -> Self::Output {
         &self - other
     }
@@ -73,6 +79,7 @@ impl<'a> Sub<&'a Matrix> for Matrix {
 impl Sub<Matrix> for &Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn sub(self, other: Matrix) -> Self::Output {
         self - &other
     }
@@ -81,6 +88,7 @@ impl Sub<Matrix> for &Matrix {
 impl<'b> Sub<&'b Matrix> for &Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn sub(self, other: &'b Matrix) -> Self::Output {
         let size0 = other.value.len();
         let size1 = other.value[0].len();
@@ -115,6 +123,7 @@ impl<'b> Sub<&'b Matrix> for &Matrix {
 impl Sub<Scalar> for Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn sub(self, other: Scalar) -> Self::Output {
         &self - &other
     }
@@ -123,6 +132,7 @@ impl Sub<Scalar> for Matrix {
 impl<'a> Sub<&'a Scalar> for Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn sub(self, other: &'a Scalar) -> Self::Output {
         &self - other
     }
@@ -131,6 +141,7 @@ impl<'a> Sub<&'a Scalar> for Matrix {
 impl Sub<Scalar> for &Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn sub(self, other: Scalar) -> Self::Output {
         self - &other
     }
@@ -139,6 +150,7 @@ impl Sub<Scalar> for &Matrix {
 impl<'b> Sub<&'b Scalar> for &Matrix {
     type Output = Matrix;
 
+    #[track_caller]
     fn sub(self, other: &'b Scalar) -> Self::Output {
         let size0 = self.value.len();
         let size1 = self.value[0].len();
@@ -166,6 +178,7 @@ impl<'b> Sub<&'b Scalar> for &Matrix {
 impl Sub<Scalar> for Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn sub(self, other: Scalar) -> Self::Output {
         &self - &other
     }
@@ -174,6 +187,7 @@ impl Sub<Scalar> for Vector {
 impl<'a> Sub<&'a Scalar> for Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn sub(self, other: &'a Scalar) -> Self::Output {
         &self - other
     }
@@ -182,6 +196,7 @@ impl<'a> Sub<&'a Scalar> for Vector {
 impl Sub<Scalar> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn sub(self, other: Scalar) -> Self::Output {
         self - &other
     }
@@ -190,6 +205,7 @@ impl Sub<Scalar> for &Vector {
 impl<'b> Sub<&'b Scalar> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn sub(self, other: &'b Scalar) -> Self::Output {
         let size = self.value.len();
         let new_id = create_binary_vector_expr(
@@ -215,6 +231,7 @@ impl<'b> Sub<&'b Scalar> for &Vector {
 impl Sub for Vector {
     type Output = Self;
 
+    #[track_caller]
     fn sub(self, other: Self) -> Self::Output {
         &self - &other
     }
@@ -223,6 +240,7 @@ impl Sub for Vector {
 impl<'a> Sub<&'a Vector> for Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn sub(self, other: &'a Vector) -> Self::Output {
         &self - other
     }
@@ -231,6 +249,7 @@ impl<'a> Sub<&'a Vector> for Vector {
 impl Sub<Vector> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn sub(self, other: Vector) -> Self::Output {
         self - &other
     }
@@ -239,6 +258,7 @@ impl Sub<Vector> for &Vector {
 impl<'b> Sub<&'b Vector> for &Vector {
     type Output = Vector;
 
+    #[track_caller]
     fn sub(self, other: &'b Vector) -> Self::Output {
         let size = self.value.len();
         let new_id = create_binary_vector_expr(
@@ -1,5 +1,7 @@
+use anyhow::Result;
+
 use crate::{
-    analysis::real::Real,
+    analysis::{real::Real, result::AnalysisResult},
     ir::{
         expr::{
             OprUnary, create_construct_scalar_expr, create_scalar_constant,
@@ -52,6 +54,7 @@ impl Scalar {
     /// let pi = Scalar::new("pi", 3.14159);
     /// let two = Scalar!(2.0);  // Preferred: use macro
     /// ```
+    #[track_caller]
     pub fn new(name: &str, value: f64) -> Self {
         let rational_value = Real::from_f64(value);
         let new_id = create_scalar_constant(name, Real::from_f64(value));
@@ -64,6 +67,7 @@ impl Scalar {
     /// Creates a new scalar from an arbitrary-precision rational value.
     ///
     /// Used internally when exact rational arithmetic is needed.
+    #[track_caller]
     pub fn new_rational(name: &str, value: Real) -> Self {
         let new_id = create_scalar_constant(name, value.clone());
         Self { id: new_id, value }
@@ -73,6 +77,7 @@ impl Scalar {
     ///
     /// This creates a new scalar that is identical to the current one but with
     /// a different name in the expression tree.
+    #[track_caller]
     pub fn define(&mut self, new_name: String) -> Self {
         let new_id = create_unary_scalar_expr(&new_name, self.id.clone(), OprUnary::AssignNoOpt);
         Self {
@@ -88,9 +93,40 @@ impl Scalar {
         self.value.to_f64()
     }
 
+    /// Returns the analysed worst-case absolute error of this output.
+    ///
+    /// The scalar must be the handle passed to [`crate::register_scalar_output`]
+    /// before the analysis that produced `result`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use roboprec::*;
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let x = add_input_scalar("x", (Real::from_f64(0.0), Real::from_f64(1.0)), 0.5);
+    /// let mut square = &x * &x;
+    /// register_scalar_output(&mut square, "square");
+    ///
+    /// let result = analysis(Config::default())?;
+    /// assert!(square.error_bound(&result)? < 1e-15);
+    /// let (lower, upper) = square.range(&result)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn error_bound(&self, result: &AnalysisResult) -> Result<f64> {
+        Ok(result.scalar_output(&self.id)?.error)
+    }
+
+    /// Returns the analysed range `(lower, upper)` of this output.
+    pub fn range(&self, result: &AnalysisResult) -> Result<(f64, f64)> {
+        Ok(result.scalar_output(&self.id)?.range)
+    }
+
     /// Creates a scalar from another scalar (identity operation).
     ///
     /// Used internally for constructing scalars from existing values.
+    #[track_caller]
     pub fn from_scalar(name: &str, scalar: &Scalar) -> Self {
         let id = scalar.id.clone();
         let new_id = create_construct_scalar_expr(name, id);
@@ -104,6 +140,7 @@ impl Scalar {
     /// Generic constructor accepting either a `Scalar` reference or an f64 reference.
     ///
     /// This enables flexible construction via the [`Scalar!`] macro.
+    #[track_caller]
     pub fn from_any<T: IntoScalarValue>(name: &str, t: T) -> Self {
         t.into_scalar_with_name(name)
     }
@@ -138,16 +175,19 @@ macro_rules! Scalar {
 ///
 /// Allows [`Scalar::from_any`] to accept either `&Scalar` or `&f64`.
 pub trait IntoScalarValue {
+    #[track_caller]
     fn into_scalar_with_name(self, name: &str) -> Scalar;
 }
 
 impl IntoScalarValue for &Scalar {
+    #[track_caller]
     fn into_scalar_with_name(self, name: &str) -> Scalar {
         Scalar::from_scalar(name, self)
     }
 }
 
 impl IntoScalarValue for &f64 {
+    #[track_caller]
     fn into_scalar_with_name(self, name: &str) -> Scalar {
         Scalar::new(name, *self)
     }
@@ -1,5 +1,7 @@
+use anyhow::Result;
+
 use crate::{
-    analysis::real::Real,
+    analysis::{real::Real, result::AnalysisResult},
     ir::{
         expr::{
             OprBinary, OprUnary, create_binary_scalar_expr, create_binary_vector_expr,
@@ -57,12 +59,14 @@ impl Vector {
     /// let v = Vector::new("my_vec", vec![1.0, 2.0, 3.0]);
     /// let v = Vector![1.0, 2.0, 3.0];  // Preferred: use macro
     /// ```
+    #[track_caller]
     pub fn new(name: &str, values: Vec<f64>) -> Self {
         let rational_values: Vec<Real> = values.into_iter().map(Real::from_f64).collect();
         Self::new_rational(name, rational_values)
     }
 
     /// Create a vector from Real values
+    #[track_caller]
     pub fn new_rational(name: &str, values: Vec<Real>) -> Self {
         let new_id = create_vector_constant(name, values.clone());
         Self {
@@ -72,6 +76,7 @@ impl Vector {
     }
 
     /// Redefine the vector with a new name
+    #[track_caller]
     pub fn define(&mut self, new_name: String) -> Self {
         let new_id = create_unary_vector_expr(&new_name, self.id.clone(), OprUnary::AssignNoOpt);
         Self {
@@ -85,7 +90,21 @@ impl Vector {
         self.value.iter().map(|v| v.to_f64()).collect()
     }
 
+    /// Analysed worst-case absolute error of every element of this output.
+    ///
+    /// The vector must be the handle passed to [`crate::register_vector_output`]
+    /// before the analysis that produced `result`.
+    pub fn error_bound(&self, result: &AnalysisResult) -> Result<Vec<f64>> {
+        Ok(result.vector_output(&self.id)?.iter().map(|element| element.error).collect())
+    }
+
+    /// Analysed range `(lower, upper)` of every element of this output.
+    pub fn range(&self, result: &AnalysisResult) -> Result<Vec<(f64, f64)>> {
+        Ok(result.vector_output(&self.id)?.iter().map(|element| element.range).collect())
+    }
+
     /// Create a vector from scalars
+    #[track_caller]
     pub fn from_scalars(name: &str, scalars: Vec<&Scalar>) -> Self {
         let ids: Vec<Identifier> = scalars.iter().map(|s| s.id.clone()).collect();
         let new_id = create_construct_vector_expr(name, ids);
@@ -98,6 +117,7 @@ impl Vector {
     }
 
     /// Create a vector from another vector
+    #[track_caller]
     pub fn from_vector(name: &str, vector: &Vector) -> Self {
         let new_id = create_vector_constant(name, vector.value.clone());
         Self {
@@ -113,6 +133,7 @@ impl Vector {
 
     /// TODO: DEPRECATE THIS IN FAVOR OF AT
     /// Get a specific element by index
+    #[track_caller]
     pub fn get(&self, index: usize) -> Scalar {
         let size = self.size();
         assert!(
@@ -138,6 +159,7 @@ impl Vector {
         }
     }
 
+    #[track_caller]
     pub fn at(&self, index: usize) -> Scalar {
         self.get(index)
     }
@@ -148,6 +170,7 @@ impl Vector {
     }
 
     /// Set an element at a specific index, creating a new vector
+    #[track_caller]
     pub fn set(&mut self, index: usize, value: f64) {
         assert!(
             index < self.value.len(),
@@ -177,6 +200,7 @@ impl Vector {
     }
 
     /// Push a new element to the end of the vector, creating a new vector
+    #[track_caller]
     pub fn push_f64(&mut self, value: f64) {
         // Create scalars for all existing elements
         let mut scalars = Vec::new();
@@ -198,6 +222,7 @@ impl Vector {
     }
 
     /// Pop the last element from the vector, creating a new vector
+    #[track_caller]
     pub fn pop(&mut self) -> Real {
         assert!(!self.value.is_empty(), "Cannot pop from an empty vector");
 
@@ -234,6 +259,7 @@ impl Vector {
     }
 
     /// Dot product with another vector
+    #[track_caller]
     pub fn dot(&self, other: &Vector) -> Scalar {
         assert_eq!(
             self.size(),
@@ -262,6 +288,7 @@ impl Vector {
     }
 
     /// Cross product with another vector (only for 3D vectors)
+    #[track_caller]
     pub fn cross(&self, other: &Vector) -> Vector {
         assert_eq!(
             self.size(),